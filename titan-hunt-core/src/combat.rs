@@ -0,0 +1,412 @@
+//! Combat resolution for unit attacks
+//!
+//! Calculates damage from an attacker's firepower against a target's
+//! defenses and applies it through void shields to structure.
+
+use crate::hex::{Arc, HexCoord};
+use crate::rules::{GameMap, GameState, TerrainType, Tile, Unit, Weapon};
+use serde::{Deserialize, Serialize};
+
+/// Result of resolving one attack against a unit
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AttackOutcome {
+    /// Void shields collapsed by the attack
+    pub shields_lost: u32,
+    /// Structure points removed by the attack
+    pub structure_damage: u32,
+    /// Whether the target was destroyed
+    pub destroyed: bool,
+}
+
+/// Get the hexes strictly between two hexes along the line of fire
+fn intervening_hexes(map: &GameMap, from: HexCoord, to: HexCoord) -> Vec<HexCoord> {
+    let line = from.line_to(map.unwrap_toward(from, to));
+    if line.len() <= 2 {
+        return Vec::new();
+    }
+    line[1..line.len() - 1]
+        .iter()
+        .map(|hex| map.normalize(*hex))
+        .collect()
+}
+
+/// Get the elevation a hex must exceed to block sight between two hexes
+fn sightline_elevation(map: &GameMap, from: HexCoord, to: HexCoord) -> i32 {
+    let elevation = |hex| map.get_tile(hex).map(|t| t.elevation).unwrap_or(0);
+    elevation(from).max(elevation(to))
+}
+
+/// Check whether tall terrain on the `step`th of `steps` hexes along a line
+/// of fire rises into the sightline
+///
+/// The sightline climbs evenly from the viewer's elevation `start` to the
+/// target's `end`, so a viewer or target on a hill can see over low woods.
+fn reaches_sightline(tile: &Tile, start: i32, end: i32, step: i32, steps: i32) -> bool {
+    // Compare in units of 1/steps to keep the interpolation exact
+    let top = (tile.elevation + tile.terrain.height()) * steps;
+    let sightline = start * (steps - step) + end * step;
+    tile.terrain.height() > 0 && top > sightline
+}
+
+/// Check if there is a clear line of sight between two hexes
+///
+/// Impassable terrain blocks sight, as does any hex higher than both
+/// endpoints. Woods and other cover only obscure the line; see
+/// `los_obstruction`. The endpoints themselves never block.
+pub fn has_line_of_sight(map: &GameMap, from: HexCoord, to: HexCoord) -> bool {
+    let crest = sightline_elevation(map, from, to);
+    intervening_hexes(map, from, to).into_iter().all(|hex| {
+        map.get_tile(hex)
+            .is_some_and(|tile| tile.terrain != TerrainType::Impassable && tile.elevation <= crest)
+    })
+}
+
+/// Calculate how much of the line of fire between two hexes is obstructed
+///
+/// Returns 0.0 for a clear line up to 1.0 for a fully blocked one. Each
+/// intervening hex adds its terrain's cover, except woods the sightline
+/// passes over; impassable terrain, off-map hexes, and hexes higher than
+/// both endpoints block completely.
+pub fn los_obstruction(map: &GameMap, from: HexCoord, to: HexCoord) -> f32 {
+    let elevation = |hex| map.get_tile(hex).map(|t| t.elevation).unwrap_or(0);
+    let (start, end) = (elevation(from), elevation(to));
+    let crest = sightline_elevation(map, from, to);
+
+    let hexes = intervening_hexes(map, from, to);
+    let steps = hexes.len() as i32 + 1;
+    let mut obstruction = 0.0;
+
+    for (hex, step) in hexes.into_iter().zip(1..) {
+        let Some(tile) = map.get_tile(hex) else {
+            return 1.0;
+        };
+        if tile.elevation > crest {
+            return 1.0;
+        }
+        if tile.terrain.height() == 0 || reaches_sightline(tile, start, end, step, steps) {
+            obstruction += tile.terrain.cover();
+        }
+    }
+
+    obstruction.min(1.0)
+}
+
+/// Calculate structure damage an attack would deal once past void shields
+///
+/// Armor, including cover from the target's hex, absorbs half its value
+/// from the attacker's firepower.
+pub fn calculate_damage(map: &GameMap, attacker: &Unit, target: &Unit) -> u32 {
+    let arc = target.facing.arc_of(target.position, attacker.position);
+    let armor = target.armor_against(arc) + map.terrain_at(target.position).armor_bonus();
+    weapon_damage(attacker.unit_type.main_weapon(), armor)
+}
+
+/// Calculate structure damage a weapon would deal against an armor value
+pub fn weapon_damage(weapon: &Weapon, armor: u32) -> u32 {
+    weapon.firepower.saturating_sub(armor / 2)
+}
+
+/// Score an attack with the attacker's main weapon, higher being better
+///
+/// Expected structure damage counts ten points a point and a likely kill
+/// adds a hundred, both scaled by the chance to hit through cover. Each
+/// shield knocked down is worth five, and the target's heaviest firepower
+/// is added as its threat. Range and line of sight are not checked.
+pub fn score_target(state: &GameState, attacker: &Unit, target: &Unit) -> i32 {
+    let weapon = attacker.unit_type.main_weapon();
+    let obstruction = los_obstruction(&state.map, attacker.position, target.position);
+    let hit_percent = ((1.0 - obstruction) * 100.0).round() as i32;
+
+    let mut score = 0;
+    if target.void_shields > weapon.shield_piercing {
+        score += 5 * hit_percent / 100;
+    } else {
+        let arc = target.facing.arc_of(target.position, attacker.position);
+        let armor = target.armor_against(arc) + state.map.terrain_at(target.position).armor_bonus();
+        let multiplier = state.stat_modifier(attacker.owner).damage_multiplier;
+        let damage = (weapon_damage(weapon, armor) as f32 * multiplier.max(0.0)).round() as u32;
+        let damage = damage.min(target.structure);
+
+        score += 10 * damage as i32 * hit_percent / 100;
+        if damage > 0 && damage == target.structure {
+            score += 100 * hit_percent / 100;
+        }
+    }
+
+    let threat = target
+        .unit_type
+        .weapons()
+        .iter()
+        .map(|w| w.firepower)
+        .max()
+        .unwrap_or(0);
+    score + threat as i32
+}
+
+/// Apply an attack with the attacker's main weapon to a target, with cover
+/// from the target's hex
+pub fn resolve_attack(map: &GameMap, attacker: &Unit, target: &mut Unit) -> AttackOutcome {
+    let arc = target.facing.arc_of(target.position, attacker.position);
+    let armor_bonus = map.terrain_at(target.position).armor_bonus();
+    resolve_hit(
+        attacker.unit_type.main_weapon(),
+        target,
+        arc,
+        armor_bonus,
+        1.0,
+    )
+}
+
+/// Apply a hit from a weapon striking a target's given arc, with extra
+/// armor from cover
+///
+/// An active void shield absorbs the whole hit and collapses. Otherwise the
+/// damage is taken from structure, destroying the target at zero. Shield
+/// piercing weapons ignore that many shields, so they reach structure while
+/// the pierced shields stay up. Structure damage is scaled by
+/// `damage_multiplier` and rounded to the nearest point. Every hit that gets
+/// past the shields also cracks a point of the target's armor for good.
+pub fn resolve_hit(
+    weapon: &Weapon,
+    target: &mut Unit,
+    arc: Arc,
+    armor_bonus: u32,
+    damage_multiplier: f32,
+) -> AttackOutcome {
+    if target.void_shields > weapon.shield_piercing {
+        target.void_shields -= 1;
+        return AttackOutcome {
+            shields_lost: 1,
+            structure_damage: 0,
+            destroyed: false,
+        };
+    }
+
+    let damage = weapon_damage(weapon, target.armor_against(arc) + armor_bonus) as f32;
+    let damage = ((damage * damage_multiplier.max(0.0)).round() as u32).min(target.structure);
+    target.structure -= damage;
+    target.armor = target.armor.saturating_sub(1);
+
+    AttackOutcome {
+        shields_lost: 0,
+        structure_damage: damage,
+        destroyed: target.is_destroyed(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hex::Facing;
+    use crate::rules::{Phase, Player, UnitType};
+
+    fn unit(id: u32, unit_type: UnitType, owner: Player) -> Unit {
+        Unit::new(id, unit_type, owner, HexCoord::new(0, 0), Facing::East)
+    }
+
+    fn set_terrain(map: &mut GameMap, q: i32, r: i32, terrain: TerrainType) {
+        map.tiles.get_mut(&(q, r)).unwrap().terrain = terrain;
+    }
+
+    #[test]
+    fn test_los_obstruction() {
+        let from = HexCoord::new(0, 0);
+        let to = HexCoord::new(4, 0);
+
+        let mut map = GameMap::new(10, 10);
+        assert_eq!(los_obstruction(&map, from, to), 0.0);
+
+        // Cover at the endpoints doesn't count
+        set_terrain(&mut map, 0, 0, TerrainType::Woods);
+        set_terrain(&mut map, 4, 0, TerrainType::Woods);
+        assert_eq!(los_obstruction(&map, from, to), 0.0);
+        assert!(has_line_of_sight(&map, from, to));
+
+        // Woods obscure the shot without hiding the target
+        set_terrain(&mut map, 2, 0, TerrainType::Woods);
+        let partial = los_obstruction(&map, from, to);
+        assert!(partial > 0.25 && partial < 0.75);
+        assert!(has_line_of_sight(&map, from, to));
+
+        set_terrain(&mut map, 2, 0, TerrainType::Impassable);
+        assert!(los_obstruction(&map, from, to) > 0.99);
+        assert!(!has_line_of_sight(&map, from, to));
+    }
+
+    #[test]
+    fn test_high_ground_blocks_sight() {
+        let mut map = GameMap::new(10, 10);
+        let from = HexCoord::new(0, 0);
+        let to = HexCoord::new(4, 0);
+
+        map.tiles.get_mut(&(2, 0)).unwrap().elevation = 2;
+        assert!(!has_line_of_sight(&map, from, to));
+        assert_eq!(los_obstruction(&map, from, to), 1.0);
+
+        // A viewer on equally high ground sees over the crest
+        map.tiles.get_mut(&(0, 0)).unwrap().elevation = 2;
+        assert!(has_line_of_sight(&map, from, to));
+    }
+
+    #[test]
+    fn test_elevated_viewer_sees_over_woods() {
+        let mut map = GameMap::new(10, 10);
+        let from = HexCoord::new(0, 0);
+        let to = HexCoord::new(4, 0);
+
+        set_terrain(&mut map, 2, 0, TerrainType::Woods);
+        assert_eq!(los_obstruction(&map, from, to), 0.5);
+
+        // Halfway down from a height of 2 the sightline clears the canopy
+        map.tiles.get_mut(&(0, 0)).unwrap().elevation = 2;
+        assert_eq!(los_obstruction(&map, from, to), 0.0);
+        assert_eq!(los_obstruction(&map, to, from), 0.0);
+
+        // Woods nearer the low target still reach the sightline
+        set_terrain(&mut map, 2, 0, TerrainType::Clear);
+        set_terrain(&mut map, 3, 0, TerrainType::Woods);
+        assert_eq!(los_obstruction(&map, from, to), 0.5);
+
+        // Unless the target stands on a hill too
+        map.tiles.get_mut(&(4, 0)).unwrap().elevation = 1;
+        assert_eq!(los_obstruction(&map, from, to), 0.0);
+        assert!(has_line_of_sight(&map, from, to));
+    }
+
+    #[test]
+    fn test_void_shields_absorb_hits() {
+        let map = GameMap::new(10, 10);
+        let attacker = unit(1, UnitType::WarlordTitan, Player::Player1);
+        let mut target = unit(2, UnitType::ReaverTitan, Player::Player2);
+
+        let outcome = resolve_attack(&map, &attacker, &mut target);
+        assert_eq!(outcome.shields_lost, 1);
+        assert_eq!(outcome.structure_damage, 0);
+        assert_eq!(target.void_shields, 1);
+        assert_eq!(target.structure, 10);
+    }
+
+    #[test]
+    fn test_piercing_weapon_bypasses_shields() {
+        let map = GameMap::new(10, 10);
+        let attacker = unit(1, UnitType::Shadowsword, Player::Player1);
+
+        // Piercing 2 goes straight through a Reaver's two shields
+        let mut reaver = unit(2, UnitType::ReaverTitan, Player::Player2);
+        let outcome = resolve_attack(&map, &attacker, &mut reaver);
+        assert_eq!(outcome.shields_lost, 0);
+        assert_eq!(outcome.structure_damage, 4);
+        assert_eq!(reaver.void_shields, 2);
+        assert_eq!(reaver.structure, 6);
+
+        // A Warlord's four shields still leave two to stop the hit
+        let mut warlord = unit(3, UnitType::WarlordTitan, Player::Player2);
+        let outcome = resolve_attack(&map, &attacker, &mut warlord);
+        assert_eq!(outcome.shields_lost, 1);
+        assert_eq!(outcome.structure_damage, 0);
+        assert_eq!(warlord.void_shields, 3);
+    }
+
+    #[test]
+    fn test_rear_hits_strike_thinner_armor() {
+        let map = GameMap::new(10, 10);
+        let mut attacker = unit(1, UnitType::Shadowsword, Player::Player1);
+        let hit_from = |attacker: &mut Unit, position| {
+            attacker.position = position;
+            let mut target = unit(2, UnitType::Shadowsword, Player::Player2);
+            target.structure = 50;
+            resolve_attack(&map, attacker, &mut target).structure_damage
+        };
+
+        // The target faces east, so a shot from the west strikes its rear
+        let front = hit_from(&mut attacker, HexCoord::new(3, 0));
+        let side = hit_from(&mut attacker, HexCoord::new(-3, 3));
+        let rear = hit_from(&mut attacker, HexCoord::new(-3, 0));
+        assert_eq!((front, side, rear), (6, 7, 8));
+        assert!(rear > front);
+    }
+
+    #[test]
+    fn test_score_target_prefers_a_kill() {
+        let map = GameMap::new(10, 10);
+        let mut state = GameState::new_at(map, 1, Phase::Combat, Player::Player1);
+        let attacker = unit(1, UnitType::ReaverTitan, Player::Player1);
+        let mut tank = unit(2, UnitType::Shadowsword, Player::Player2);
+        tank.position = HexCoord::new(3, 0);
+        let mut titan = unit(3, UnitType::WarlordTitan, Player::Player2);
+        titan.position = HexCoord::new(3, 1);
+        state.add_unit(attacker.clone());
+        state.add_unit(tank.clone());
+        state.add_unit(titan.clone());
+
+        // The melta wrecks the tank outright but only dents the Warlord's shields
+        let tank_score = score_target(&state, &attacker, &tank);
+        let titan_score = score_target(&state, &attacker, &titan);
+        assert!(tank_score > titan_score);
+        assert_eq!(state.best_target(1), Some(2));
+
+        // Woods in the way make the kill less certain
+        set_terrain(&mut state.map, 2, 0, TerrainType::Woods);
+        assert!(score_target(&state, &attacker, &tank) < tank_score);
+    }
+
+    #[test]
+    fn test_hits_crack_armor() {
+        let map = GameMap::new(10, 10);
+        let attacker = unit(1, UnitType::Shadowsword, Player::Player1);
+        let mut target = unit(2, UnitType::Shadowsword, Player::Player2);
+        target.position = HexCoord::new(3, 0);
+        target.facing = Facing::West;
+        target.structure = 50;
+
+        let mut damage = Vec::new();
+        let mut armor = Vec::new();
+        for _ in 0..4 {
+            damage.push(resolve_attack(&map, &attacker, &mut target).structure_damage);
+            armor.push(target.armor);
+        }
+        assert_eq!(damage, vec![6, 7, 7, 8]);
+        assert_eq!(armor, vec![7, 6, 5, 4]);
+
+        // Cracked plating stays cracked into the next turn
+        target.reset_for_turn();
+        assert_eq!(target.armor, 4);
+
+        // Hits soaked by a shield the Volcano can't pierce leave the armor alone
+        target.void_shields = 3;
+        resolve_attack(&map, &attacker, &mut target);
+        assert_eq!(target.armor, 4);
+    }
+
+    #[test]
+    fn test_cover_in_target_hex_adds_armor() {
+        let mut map = GameMap::new(10, 10);
+        let attacker = unit(1, UnitType::ReaverTitan, Player::Player1);
+        let mut target = unit(2, UnitType::Shadowsword, Player::Player2);
+        target.position = HexCoord::new(3, 0);
+        target.facing = Facing::West;
+        target.structure = 50;
+        let open = calculate_damage(&map, &attacker, &target);
+
+        set_terrain(&mut map, 3, 0, TerrainType::Ruins);
+        let covered = calculate_damage(&map, &attacker, &target);
+        assert_eq!(covered, open - 1);
+        assert_eq!(
+            resolve_attack(&map, &attacker, &mut target).structure_damage,
+            covered
+        );
+    }
+
+    #[test]
+    fn test_unshielded_target_takes_structure_damage() {
+        let map = GameMap::new(10, 10);
+        let attacker = unit(1, UnitType::ReaverTitan, Player::Player1);
+        let mut target = unit(2, UnitType::Shadowsword, Player::Player2);
+
+        let outcome = resolve_attack(&map, &attacker, &mut target);
+        assert_eq!(outcome.shields_lost, 0);
+        assert_eq!(outcome.structure_damage, 6);
+        assert!(outcome.destroyed);
+        assert!(target.is_destroyed());
+    }
+}
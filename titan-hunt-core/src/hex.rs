@@ -4,7 +4,10 @@
 //! to cube coordinates for distance calculations.
 
 use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::HashSet;
 use std::f64::consts::PI;
+use std::ops::{Add, Mul, Sub};
 
 /// Axial hex coordinate using (q, r) system
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -32,8 +35,16 @@ pub const AXIAL_DIRECTIONS: [(i32, i32); 6] = [
     (0, 1),   // Southeast
 ];
 
-/// Facing direction for units on the hex grid
+/// Side of a unit something lies on, relative to its facing
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Arc {
+    Front,
+    Side,
+    Rear,
+}
+
+/// Facing direction for units on the hex grid
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 #[repr(u8)]
 pub enum Facing {
     East = 0,
@@ -75,6 +86,23 @@ impl Facing {
         *self as u8
     }
 
+    /// Get every facing in index order: E, NE, NW, W, SW, SE
+    pub fn all() -> [Facing; 6] {
+        [
+            Facing::East,
+            Facing::Northeast,
+            Facing::Northwest,
+            Facing::West,
+            Facing::Southwest,
+            Facing::Southeast,
+        ]
+    }
+
+    /// Get the axial (q, r) step one hex in this direction
+    pub fn offset(&self) -> (i32, i32) {
+        AXIAL_DIRECTIONS[self.index() as usize]
+    }
+
     /// Get the angle in radians for this facing (0 = East, counter-clockwise)
     pub fn to_radians(&self) -> f64 {
         match self {
@@ -100,6 +128,18 @@ impl Facing {
         }
     }
 
+    /// Convert facing to the nearest of the 8 sprite atlas directions
+    ///
+    /// The hex facings point at 0°, 60°, 120°, ... on screen while the atlas
+    /// is drawn every 45°, so each facing snaps to the closest frame. N and S
+    /// are never the nearest for a flat-top facing, so they go unused.
+    pub fn to_sprite_direction_8(&self) -> &'static str {
+        const DIRECTIONS: [&str; 8] = ["E", "NE", "N", "NW", "W", "SW", "S", "SE"];
+        let step = PI / 4.0;
+        let index = (self.to_radians() / step).round() as usize % DIRECTIONS.len();
+        DIRECTIONS[index]
+    }
+
     /// Check if a target hex is in the front arc (3 hex sides in front)
     pub fn is_in_front_arc(&self, from: HexCoord, target: HexCoord) -> bool {
         let direction = from.direction_to(target);
@@ -111,6 +151,42 @@ impl Facing {
         }
     }
 
+    /// Check if a target hex is in the rear arc (the hex side directly behind)
+    pub fn is_in_rear_arc(&self, from: HexCoord, target: HexCoord) -> bool {
+        from.direction_to(target) == Some(self.opposite())
+    }
+
+    /// Get the arc a target hex lies in for a unit at `from` with this facing
+    pub fn arc_of(&self, from: HexCoord, target: HexCoord) -> Arc {
+        if self.is_in_front_arc(from, target) {
+            Arc::Front
+        } else if self.is_in_rear_arc(from, target) {
+            Arc::Rear
+        } else {
+            Arc::Side
+        }
+    }
+
+    /// Split the neighbors of a hex into front, flank, and rear arcs
+    ///
+    /// Front is the 3 hexes covered by `is_in_front_arc`, rear is the hex
+    /// directly behind, and flank is the remaining hex on each side.
+    pub fn arc_hexes(&self, center: HexCoord) -> (Vec<HexCoord>, Vec<HexCoord>, Vec<HexCoord>) {
+        let mut front = Vec::with_capacity(3);
+        let mut flank = Vec::with_capacity(2);
+        let mut rear = Vec::with_capacity(1);
+
+        for neighbor in center.neighbors() {
+            match self.arc_of(center, neighbor) {
+                Arc::Front => front.push(neighbor),
+                Arc::Side => flank.push(neighbor),
+                Arc::Rear => rear.push(neighbor),
+            }
+        }
+
+        (front, flank, rear)
+    }
+
     /// Rotate clockwise by n steps
     pub fn rotate_cw(&self, steps: i32) -> Facing {
         let new_index = (self.index() as i32 - steps).rem_euclid(6) as u8;
@@ -122,6 +198,19 @@ impl Facing {
         let new_index = (self.index() as i32 + steps).rem_euclid(6) as u8;
         Facing::from_index(new_index).unwrap()
     }
+
+    /// Get the shortest turn to another facing in 60° steps
+    ///
+    /// Positive turns counter-clockwise and negative clockwise, so
+    /// `rotate_ccw(steps_to(other))` gives `other`. An about-face is +3.
+    pub fn steps_to(&self, other: Facing) -> i32 {
+        let diff = (other.index() as i32 - self.index() as i32).rem_euclid(6);
+        if diff > 3 {
+            diff - 6
+        } else {
+            diff
+        }
+    }
 }
 
 impl HexCoord {
@@ -135,6 +224,19 @@ impl HexCoord {
         HexCoord { q: 0, r: 0 }
     }
 
+    /// Convert an offset (column, row) coordinate to axial
+    ///
+    /// Uses the odd-r layout `GameMap::new` builds: odd rows are shoved half
+    /// a hex to the right, so column 0 of every row is the map's west edge.
+    pub fn from_offset(col: i32, row: i32) -> HexCoord {
+        HexCoord::new(col - (row - (row & 1)) / 2, row)
+    }
+
+    /// Convert to offset (column, row) coordinates in the odd-r layout
+    pub fn to_offset(&self) -> (i32, i32) {
+        (self.q + (self.r - (self.r & 1)) / 2, self.r)
+    }
+
     /// Convert to cube coordinates
     pub fn to_cube(&self) -> CubeCoord {
         CubeCoord {
@@ -146,20 +248,12 @@ impl HexCoord {
 
     /// Get all 6 neighboring hexes
     pub fn neighbors(&self) -> [HexCoord; 6] {
-        [
-            HexCoord::new(self.q + 1, self.r),      // East
-            HexCoord::new(self.q + 1, self.r - 1),  // Northeast
-            HexCoord::new(self.q, self.r - 1),      // Northwest
-            HexCoord::new(self.q - 1, self.r),      // West
-            HexCoord::new(self.q - 1, self.r + 1),  // Southwest
-            HexCoord::new(self.q, self.r + 1),      // Southeast
-        ]
+        AXIAL_DIRECTIONS.map(|direction| *self + HexCoord::from(direction))
     }
 
     /// Get neighbor in a specific direction
     pub fn neighbor(&self, facing: Facing) -> HexCoord {
-        let (dq, dr) = AXIAL_DIRECTIONS[facing.index() as usize];
-        HexCoord::new(self.q + dq, self.r + dr)
+        *self + HexCoord::from(facing.offset())
     }
 
     /// Calculate distance to another hex
@@ -169,6 +263,43 @@ impl HexCoord {
         ((a.x - b.x).abs() + (a.y - b.y).abs() + (a.z - b.z).abs()) as u32 / 2
     }
 
+    /// Get every hex exactly `radius` steps away, starting from the east
+    /// corner and going counterclockwise
+    ///
+    /// Radius 0 is just this hex.
+    pub fn ring(&self, radius: u32) -> Vec<HexCoord> {
+        self.ring_segment(radius, 0, (6 * radius as usize).max(1))
+    }
+
+    /// Get `count` consecutive hexes of the ring at `radius`
+    ///
+    /// The segment starts at the ring's corner in direction `start_dir` (an
+    /// index into `AXIAL_DIRECTIONS`) and runs counterclockwise, wrapping past
+    /// the east corner. It never repeats a hex, so `count` is capped at the
+    /// ring's length.
+    pub fn ring_segment(&self, radius: u32, start_dir: usize, count: usize) -> Vec<HexCoord> {
+        if radius == 0 {
+            return vec![*self; count.min(1)];
+        }
+
+        let side_len = radius as usize;
+        let count = count.min(6 * side_len);
+        let corner = HexCoord::from(AXIAL_DIRECTIONS[start_dir % 6]) * radius as i32;
+        let mut hex = *self + corner;
+        let mut hexes = Vec::with_capacity(count);
+        for i in 0..count {
+            hexes.push(hex);
+            let side = (start_dir + i / side_len) % 6;
+            hex = hex + HexCoord::from(AXIAL_DIRECTIONS[(side + 2) % 6]);
+        }
+        hexes
+    }
+
+    /// Get every hex within `radius` steps, ring by ring from this one
+    pub fn hexes_in_range(&self, radius: u32) -> Vec<HexCoord> {
+        (0..=radius).flat_map(|r| self.ring(r)).collect()
+    }
+
     /// Get all hexes on a line to another hex
     pub fn line_to(&self, target: HexCoord) -> Vec<HexCoord> {
         let n = self.distance_to(target) as i32;
@@ -186,15 +317,39 @@ impl HexCoord {
         results
     }
 
+    /// Get the hex halfway along the line to another hex
+    ///
+    /// When the distance is odd there is no center hex, so this picks the
+    /// one on this hex's side of the center.
+    pub fn midpoint(&self, other: HexCoord) -> HexCoord {
+        let line = self.line_to(other);
+        line[(line.len() - 1) / 2]
+    }
+
+    /// Rotate this hex about a pivot by 60° steps
+    ///
+    /// Positive steps turn clockwise, matching `Facing::rotate_cw`.
+    pub fn rotate_around(&self, pivot: HexCoord, steps: i32) -> HexCoord {
+        let mut cube = (*self - pivot).to_cube();
+        for _ in 0..steps.rem_euclid(6) {
+            cube = CubeCoord::new(-cube.z, -cube.x, -cube.y);
+        }
+        pivot + cube.to_axial()
+    }
+
     /// Get the direction from this hex to another
     pub fn direction_to(&self, target: HexCoord) -> Option<Facing> {
         if *self == target {
             return None;
         }
 
-        let dq = target.q - self.q;
-        let dr = target.r - self.r;
-        let angle = (dr as f64).atan2(dq as f64);
+        let dq = (target.q - self.q) as f64;
+        let dr = (target.r - self.r) as f64;
+
+        // Measure the angle in screen space (y up) so axial skew doesn't distort it
+        let x = 3.0_f64.sqrt() * (dq + dr / 2.0);
+        let y = -1.5 * dr;
+        let angle = y.atan2(x);
 
         // Convert angle to facing (0 = East, counter-clockwise)
         let normalized = (angle + 2.0 * PI) % (2.0 * PI);
@@ -210,10 +365,67 @@ impl HexCoord {
     }
 
     /// Convert pixel position to hex coordinate (flat-top orientation)
-    pub fn from_pixel(x: f64, y: f64, hex_size: f64) -> HexCoord {
+    ///
+    /// Fails on a non-finite position, a hex size that isn't a positive
+    /// finite number, or a position too far out for an `i32` coordinate.
+    pub fn from_pixel(x: f64, y: f64, hex_size: f64) -> Result<HexCoord, String> {
+        if !x.is_finite() || !y.is_finite() {
+            return Err(format!("Pixel position ({}, {}) is not finite", x, y));
+        }
+        if !hex_size.is_finite() || hex_size <= 0.0 {
+            return Err(format!("Hex size {} must be positive", hex_size));
+        }
+
         let q = (3.0_f64.sqrt() / 3.0 * x - 1.0 / 3.0 * y) / hex_size;
         let r = (2.0 / 3.0 * y) / hex_size;
-        hex_round(q, r)
+        let limit = i32::MAX as f64 / 2.0;
+        if q.abs() > limit || r.abs() > limit {
+            return Err(format!("Pixel position ({}, {}) is too far out", x, y));
+        }
+        Ok(hex_round(q, r))
+    }
+}
+
+/// Reading order: row by row from the top, left to right within a row
+impl Ord for HexCoord {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.r, self.q).cmp(&(other.r, other.q))
+    }
+}
+
+impl PartialOrd for HexCoord {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl From<(i32, i32)> for HexCoord {
+    fn from((q, r): (i32, i32)) -> Self {
+        HexCoord::new(q, r)
+    }
+}
+
+impl Add for HexCoord {
+    type Output = HexCoord;
+
+    fn add(self, other: HexCoord) -> HexCoord {
+        HexCoord::new(self.q + other.q, self.r + other.r)
+    }
+}
+
+impl Sub for HexCoord {
+    type Output = HexCoord;
+
+    fn sub(self, other: HexCoord) -> HexCoord {
+        HexCoord::new(self.q - other.q, self.r - other.r)
+    }
+}
+
+impl Mul<i32> for HexCoord {
+    type Output = HexCoord;
+
+    fn mul(self, factor: i32) -> HexCoord {
+        HexCoord::new(self.q * factor, self.r * factor)
     }
 }
 
@@ -254,12 +466,34 @@ fn hex_round(q: f64, r: f64) -> HexCoord {
     HexCoord::new(rq as i32, rr as i32)
 }
 
+/// Check that each hex in a path is adjacent to the one before it
+///
+/// Empty and single-hex paths are trivially contiguous.
+pub fn is_contiguous_path(path: &[HexCoord]) -> bool {
+    path.windows(2)
+        .all(|step| step[0].distance_to(step[1]) == 1)
+}
+
+/// Get every hex within `width` of the straight line between two hexes
+///
+/// Width 0 is just the line itself, endpoints included.
+pub fn corridor(from: HexCoord, to: HexCoord, width: u32) -> HashSet<HexCoord> {
+    let mut hexes = HashSet::new();
+    for hex in from.line_to(to) {
+        for radius in 0..=width {
+            hexes.extend(hex.ring(radius));
+        }
+    }
+    hexes
+}
+
 /// Get the 6 corner points of a hex for rendering
 pub fn hex_corners(center_x: f64, center_y: f64, size: f64) -> [(f64, f64); 6] {
     let mut corners = [(0.0, 0.0); 6];
-    for i in 0..6 {
-        let angle = PI / 3.0 * i as f64;
-        corners[i] = (center_x + size * angle.cos(), center_y + size * angle.sin());
+    for (i, corner) in corners.iter_mut().enumerate() {
+        // Offset by 30 degrees to match the layout used by `to_pixel`
+        let angle = PI / 6.0 + PI / 3.0 * i as f64;
+        *corner = (center_x + size * angle.cos(), center_y + size * angle.sin());
     }
     corners
 }
@@ -297,12 +531,118 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_coord_arithmetic() {
+        let origin = HexCoord::origin();
+        for (index, direction) in AXIAL_DIRECTIONS.iter().enumerate() {
+            let facing = Facing::from_index(index as u8).unwrap();
+            assert_eq!(origin + HexCoord::from(*direction), origin.neighbor(facing));
+        }
+
+        let a = HexCoord::new(3, -2);
+        let b = HexCoord::new(-1, 4);
+        assert_eq!(a - a, origin);
+        assert_eq!(a + b - b, a);
+        assert_eq!(HexCoord::from(AXIAL_DIRECTIONS[0]) * 3, HexCoord::new(3, 0));
+        assert_eq!(
+            a.distance_to(a + HexCoord::from(AXIAL_DIRECTIONS[4]) * 2),
+            2
+        );
+    }
+
+    #[test]
+    fn test_reading_order() {
+        let ordered = generate_rect_map(4, 3);
+        let mut shuffled = ordered.clone();
+        let mut rng = crate::rng::GameRng::new(7);
+        for i in (1..shuffled.len()).rev() {
+            let j = rng.next_below(i as u32 + 1) as usize;
+            shuffled.swap(i, j);
+        }
+        assert_ne!(shuffled, ordered);
+
+        shuffled.sort();
+        assert_eq!(shuffled, ordered);
+
+        // Ordering agrees with equality
+        let a = HexCoord::new(2, 1);
+        assert_eq!(a.cmp(&HexCoord::new(2, 1)), Ordering::Equal);
+        assert!(HexCoord::new(5, 0) < HexCoord::new(-1, 1));
+    }
+
     #[test]
     fn test_facing_opposite() {
         assert_eq!(Facing::East.opposite(), Facing::West);
         assert_eq!(Facing::Northeast.opposite(), Facing::Southwest);
     }
 
+    #[test]
+    fn test_facing_offsets() {
+        let origin = HexCoord::new(2, -3);
+        for (index, facing) in Facing::all().into_iter().enumerate() {
+            assert_eq!(facing.index() as usize, index);
+            assert_eq!(
+                origin.neighbor(facing),
+                origin + HexCoord::from(facing.offset())
+            );
+        }
+        assert_eq!(Facing::Southwest.offset(), (-1, 1));
+    }
+
+    #[test]
+    fn test_steps_to() {
+        assert_eq!(Facing::East.steps_to(Facing::Northwest), 2);
+        assert_eq!(Facing::Northeast.steps_to(Facing::Southeast), -2);
+        assert_eq!(Facing::Southeast.steps_to(Facing::East), 1);
+        assert_eq!(Facing::West.steps_to(Facing::East), 3);
+        assert_eq!(Facing::Southwest.steps_to(Facing::Southwest), 0);
+
+        for from in Facing::all() {
+            for to in Facing::all() {
+                assert_eq!(from.rotate_ccw(from.steps_to(to)), to);
+            }
+        }
+    }
+
+    #[test]
+    fn test_direction_to_neighbors() {
+        let origin = HexCoord::origin();
+        for index in 0..6 {
+            let facing = Facing::from_index(index).unwrap();
+            assert_eq!(origin.direction_to(origin.neighbor(facing)), Some(facing));
+        }
+        assert_eq!(origin.direction_to(origin), None);
+    }
+
+    #[test]
+    fn test_sprite_direction_8() {
+        const ATLAS: [&str; 8] = ["S", "SW", "W", "NW", "N", "NE", "E", "SE"];
+        let expected = ["E", "NE", "NW", "W", "SW", "SE"];
+        for index in 0..6 {
+            let facing = Facing::from_index(index).unwrap();
+            let direction = facing.to_sprite_direction_8();
+            assert!(ATLAS.contains(&direction));
+            assert_eq!(direction, expected[index as usize]);
+        }
+    }
+
+    #[test]
+    fn test_arc_hexes() {
+        let origin = HexCoord::origin();
+        let (front, flank, rear) = Facing::East.arc_hexes(origin);
+
+        assert_eq!(front.len(), 3);
+        assert!(front.contains(&origin.neighbor(Facing::East)));
+        assert!(front.contains(&origin.neighbor(Facing::Northeast)));
+        assert!(front.contains(&origin.neighbor(Facing::Southeast)));
+
+        assert_eq!(flank.len(), 2);
+        assert!(flank.contains(&origin.neighbor(Facing::Northwest)));
+        assert!(flank.contains(&origin.neighbor(Facing::Southwest)));
+
+        assert_eq!(rear, vec![origin.neighbor(Facing::West)]);
+    }
+
     #[test]
     fn test_cube_conversion() {
         let hex = HexCoord::new(3, -2);
@@ -311,6 +651,45 @@ mod tests {
         assert_eq!(cube.to_axial(), hex);
     }
 
+    #[test]
+    fn test_corridor() {
+        let from = HexCoord::new(0, 0);
+        let to = HexCoord::new(4, -2);
+        let line: HashSet<HexCoord> = from.line_to(to).into_iter().collect();
+
+        assert_eq!(corridor(from, to, 0), line);
+
+        let lane = corridor(from, to, 1);
+        assert!(lane.is_superset(&line));
+        for hex in &lane {
+            assert!(line.iter().any(|l| l.distance_to(*hex) <= 1));
+        }
+        for hex in &line {
+            assert!(hex.neighbors().iter().all(|n| lane.contains(n)));
+        }
+        assert!(!lane.contains(&HexCoord::new(0, 2)));
+    }
+
+    #[test]
+    fn test_is_contiguous_path() {
+        let path: Vec<HexCoord> = (0..4).map(|q| HexCoord::new(q, 0)).collect();
+        assert!(is_contiguous_path(&path));
+        assert!(is_contiguous_path(&path[..1]));
+        assert!(is_contiguous_path(&[]));
+
+        let gap = [
+            HexCoord::new(0, 0),
+            HexCoord::new(1, 0),
+            HexCoord::new(3, 0),
+        ];
+        assert!(!is_contiguous_path(&gap));
+        // Standing still is not a step either
+        assert!(!is_contiguous_path(&[
+            HexCoord::new(0, 0),
+            HexCoord::new(0, 0)
+        ]));
+    }
+
     #[test]
     fn test_line_to() {
         let start = HexCoord::new(0, 0);
@@ -325,7 +704,111 @@ mod tests {
     fn test_pixel_conversion() {
         let hex = HexCoord::new(2, 1);
         let (px, py) = hex.to_pixel(60.0);
-        let back = HexCoord::from_pixel(px, py, 60.0);
+        let back = HexCoord::from_pixel(px, py, 60.0).unwrap();
         assert_eq!(hex, back);
     }
+
+    #[test]
+    fn test_pixel_conversion_rejects_bad_input() {
+        assert!(HexCoord::from_pixel(f64::NAN, 10.0, 60.0).is_err());
+        assert!(HexCoord::from_pixel(10.0, f64::INFINITY, 60.0).is_err());
+        assert!(HexCoord::from_pixel(10.0, 10.0, 0.0).is_err());
+        assert!(HexCoord::from_pixel(10.0, 10.0, -60.0).is_err());
+        assert!(HexCoord::from_pixel(10.0, 10.0, f64::NAN).is_err());
+        assert!(HexCoord::from_pixel(1e300, 0.0, 60.0).is_err());
+        assert_eq!(HexCoord::from_pixel(0.0, 0.0, 60.0), Ok(HexCoord::origin()));
+    }
+
+    #[test]
+    fn test_rotate_around() {
+        let pivot = HexCoord::new(2, 3);
+        let east = pivot.neighbor(Facing::East);
+
+        for steps in 0..6 {
+            let facing = Facing::East.rotate_cw(steps);
+            assert_eq!(east.rotate_around(pivot, steps), pivot.neighbor(facing));
+        }
+        assert_eq!(
+            east.rotate_around(pivot, -1),
+            pivot.neighbor(Facing::Northeast)
+        );
+        assert_eq!(pivot.rotate_around(pivot, 2), pivot);
+
+        let far = HexCoord::new(5, 1);
+        assert_eq!(
+            far.rotate_around(pivot, 3).distance_to(pivot),
+            far.distance_to(pivot)
+        );
+        assert_eq!(far.rotate_around(pivot, 6), far);
+    }
+
+    #[test]
+    fn test_offset_round_trip() {
+        for (col, row) in [(0, 0), (3, 1), (7, 4), (-2, -3), (5, -1)] {
+            let coord = HexCoord::from_offset(col, row);
+            assert_eq!(coord.to_offset(), (col, row));
+            assert_eq!(HexCoord::from_offset(coord.to_offset().0, row), coord);
+        }
+        assert_eq!(HexCoord::from_offset(2, 3), HexCoord::new(1, 3));
+
+        // Reading the rectangle row by row gives the hexes in the same order
+        let (width, height) = (5, 4);
+        let from_offsets: Vec<HexCoord> = (0..height)
+            .flat_map(|row| (0..width).map(move |col| HexCoord::from_offset(col, row)))
+            .collect();
+        assert_eq!(generate_rect_map(width, height), from_offsets);
+    }
+
+    #[test]
+    fn test_ring_segments_rebuild_ring() {
+        let center = HexCoord::new(2, -1);
+        assert_eq!(center.ring(0), vec![center]);
+
+        for radius in 1..=3 {
+            let ring = center.ring(radius);
+            assert_eq!(ring.len(), 6 * radius as usize);
+            assert!(ring.iter().all(|h| center.distance_to(*h) == radius));
+
+            let rebuilt: Vec<HexCoord> = (0..6)
+                .flat_map(|dir| center.ring_segment(radius, dir, radius as usize))
+                .collect();
+            assert_eq!(rebuilt, ring);
+        }
+
+        // A segment may start at any corner and wraps past the east one
+        let arc = center.ring_segment(2, 5, 4);
+        assert_eq!(arc[0], center + HexCoord::new(0, 2));
+        assert_eq!(arc[2], center + HexCoord::new(2, 0));
+        assert_eq!(center.ring_segment(1, 0, 10).len(), 6);
+    }
+
+    #[test]
+    fn test_hexes_in_range() {
+        let center = HexCoord::new(2, -1);
+        assert_eq!(center.hexes_in_range(0), vec![center]);
+
+        let hexes = center.hexes_in_range(3);
+        assert_eq!(hexes.len(), 1 + 6 + 12 + 18);
+        let unique: std::collections::HashSet<_> = hexes.iter().collect();
+        assert_eq!(unique.len(), hexes.len());
+        assert!(hexes.iter().all(|h| center.distance_to(*h) <= 3));
+    }
+
+    #[test]
+    fn test_midpoint() {
+        let a = HexCoord::new(0, 0);
+        let b = HexCoord::new(2, 2);
+        assert_eq!(a.distance_to(b), 4);
+
+        let mid = a.midpoint(b);
+        assert_eq!(mid.distance_to(a), 2);
+        assert_eq!(mid.distance_to(b), 2);
+        assert_eq!(mid, HexCoord::new(1, 1));
+        assert_eq!(a.midpoint(a), a);
+
+        // Odd distances round toward the first hex
+        let c = HexCoord::new(3, 0);
+        assert_eq!(a.midpoint(c), HexCoord::new(1, 0));
+        assert_eq!(c.midpoint(a), HexCoord::new(2, 0));
+    }
 }
@@ -3,12 +3,23 @@
 //! This crate contains pure Rust game logic that can be compiled to WASM
 //! for use in a web-based frontend.
 
+pub mod combat;
 pub mod hex;
 pub mod movement;
+pub mod rng;
 pub mod rules;
 mod wasm_api;
 
 // Re-export commonly used types
-pub use hex::{CubeCoord, Facing, HexCoord, AXIAL_DIRECTIONS};
-pub use movement::{find_path, find_reachable, MovementResult};
-pub use rules::{Command, GameState, Phase, Player, Unit, UnitType};
+pub use hex::{corridor, is_contiguous_path, Arc, CubeCoord, Facing, HexCoord, AXIAL_DIRECTIONS};
+pub use movement::{
+    best_approach, find_path, find_path_with, find_reachable, find_reachable_with,
+    movement_cost_for, path_cost_breakdown, path_terrain, reachable_paths, step_cost,
+    unit_distance_matrix, HeuristicConfig, MoveStep, MovementResult, PathOptions,
+};
+pub use rng::GameRng;
+pub use rules::{
+    Command, CommandError, CommandKind, DeploymentIssue, EffectiveStats, GameState, InitiativeMode,
+    OccupancyIndex, Phase, PhaseRules, Player, PlayerTurnSummary, StatModifier, StatusEffect,
+    TurnSummary, UiContext, Unit, UnitType, VictoryCondition, Weapon, WeaponArc,
+};
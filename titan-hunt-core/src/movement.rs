@@ -3,10 +3,10 @@
 //! Implements A* pathfinding and movement cost calculations for the hex grid.
 
 use crate::hex::{Facing, HexCoord};
-use crate::rules::{GameMap, GameState, TerrainType, Unit};
+use crate::rules::{CommandError, GameMap, GameState, MapShape, Player, TerrainType, Unit, Wrap};
 use serde::{Deserialize, Serialize};
-use std::collections::{BinaryHeap, HashMap, HashSet};
-use std::cmp::Ordering;
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BinaryHeap, HashMap, HashSet, VecDeque};
 
 /// Result of a movement calculation
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -20,34 +20,349 @@ pub struct MovementResult {
 }
 
 /// Node for A* pathfinding
-#[derive(Debug, Clone, Eq, PartialEq)]
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd)]
 struct PathNode {
     coord: HexCoord,
     cost: u32,
+}
+
+/// Heap key for a `PathNode`: lowest priority first, then the hex with the
+/// greatest `(q, r)` on a tie
+type PathKey = (u32, Reverse<(i32, i32)>);
+
+/// Min-heap of `PathNode`s ordered by `(priority, tiebreak)`
+#[derive(Debug, Default)]
+struct PathFrontier {
+    heap: BinaryHeap<Reverse<(PathKey, PathNode)>>,
+}
+
+impl PathFrontier {
+    fn push(&mut self, node: PathNode, priority: u32) {
+        let tiebreak = Reverse((node.coord.q, node.coord.r));
+        self.heap.push(Reverse(((priority, tiebreak), node)));
+    }
+
+    fn pop(&mut self) -> Option<PathNode> {
+        self.heap.pop().map(|Reverse((_, node))| node)
+    }
+}
+
+/// Node for the canonical route searches behind `find_path` and `find_reachable`
+///
+/// Routes compare by movement cost, then by hexes entered, so on a cost tie
+/// the shorter route wins. `heuristic` is the hex distance still to go and is
+/// a lower bound on both. Among equal estimates the node with less progress
+/// pops first, which settles every optimal predecessor of a hex before the
+/// hex itself and keeps A* and the Dijkstra flood fill in agreement.
+#[derive(Debug, Clone, Eq, PartialEq, Ord, PartialOrd)]
+struct RouteNode {
+    coord: HexCoord,
+    cost: u32,
+    steps: u32,
+    /// Hexes still to go
+    heuristic: u32,
+    /// MP still to go, at least `heuristic` scaled by the heuristic weight
+    estimate: u32,
+}
+
+/// Heap key for a `RouteNode`: lowest estimated total first, then the least
+/// progress, then the hex with the least `(q, r)` on a tie
+type RouteKey = ((u32, u32), (u32, u32), HexCoord);
+
+/// Min-heap of `RouteNode`s ordered by `RouteKey`
+#[derive(Debug, Default)]
+struct RouteFrontier {
+    heap: BinaryHeap<Reverse<(RouteKey, RouteNode)>>,
+}
+
+impl RouteFrontier {
+    fn push(&mut self, node: RouteNode) {
+        let total = (node.cost + node.estimate, node.steps + node.heuristic);
+        let key = (total, (node.cost, node.steps), node.coord);
+        self.heap.push(Reverse((key, node)));
+    }
+
+    fn pop(&mut self) -> Option<RouteNode> {
+        self.heap.pop().map(|Reverse((_, node))| node)
+    }
+}
+
+/// Best known way into a hex during a route search
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Route {
+    cost: u32,
+    steps: u32,
+    prev: Option<HexCoord>,
+}
+
+impl Route {
+    /// Check whether entering from `prev` at this cost and length beats the current route
+    ///
+    /// Cheaper wins, then fewer hexes, then the predecessor earliest in
+    /// reading order, so every search settles on the same path.
+    fn improved_by(&self, cost: u32, steps: u32, prev: HexCoord) -> bool {
+        match (cost, steps).cmp(&(self.cost, self.steps)) {
+            Ordering::Less => true,
+            Ordering::Equal => self.prev.is_some_and(|p| prev < p),
+            Ordering::Greater => false,
+        }
+    }
+}
+
+/// Walk predecessor links back from a hex to build the path leading to it
+fn trace_route(routes: &HashMap<HexCoord, Route>, end: HexCoord) -> Vec<HexCoord> {
+    let mut path = vec![end];
+    let mut current = end;
+    while let Some(prev) = routes.get(&current).and_then(|r| r.prev) {
+        path.push(prev);
+        current = prev;
+    }
+    path.reverse();
+    path
+}
+
+/// Node for facing-aware A*, where turning on the spot costs movement
+#[derive(Debug, Clone, Eq, PartialEq, Ord, PartialOrd)]
+struct FacingNode {
+    coord: HexCoord,
+    facing: Facing,
+    cost: u32,
     priority: u32,
 }
 
-impl Ord for PathNode {
-    fn cmp(&self, other: &Self) -> Ordering {
-        // Reverse ordering for min-heap behavior
-        other.priority.cmp(&self.priority)
-            .then_with(|| self.coord.q.cmp(&other.coord.q))
-            .then_with(|| self.coord.r.cmp(&other.coord.r))
+/// Heap key for a `FacingNode`: lowest priority first, then the greatest
+/// hex and facing on a tie
+type FacingKey = (u32, Reverse<(HexCoord, Facing)>);
+
+/// Min-heap of `FacingNode`s ordered by `FacingKey`
+#[derive(Debug, Default)]
+struct FacingFrontier {
+    heap: BinaryHeap<Reverse<(FacingKey, FacingNode)>>,
+}
+
+impl FacingFrontier {
+    fn push(&mut self, node: FacingNode) {
+        let key = (node.priority, Reverse((node.coord, node.facing)));
+        self.heap.push(Reverse((key, node)));
+    }
+
+    fn pop(&mut self) -> Option<FacingNode> {
+        self.heap.pop().map(|Reverse((_, node))| node)
+    }
+}
+
+/// Largest map (in hexes) that gets a dense visited bitset
+const DENSE_VISITED_LIMIT: i32 = 1 << 20;
+
+/// Closed set for graph searches
+///
+/// Bounded, unwrapped rectangular maps use a bitset indexed by offset
+/// coordinates, which avoids hashing every hex. Other maps fall back to a
+/// hash set.
+enum VisitedSet {
+    Dense {
+        bits: Vec<u64>,
+        width: i32,
+        height: i32,
+    },
+    Sparse(HashSet<HexCoord>),
+}
+
+impl VisitedSet {
+    /// Pick the cheapest representation for a map
+    fn for_map(map: &GameMap) -> Self {
+        let cells = map.width.saturating_mul(map.height);
+        if map.wrap == Wrap::None
+            && map.shape == MapShape::Rectangle
+            && map.width > 0
+            && map.height > 0
+            && cells <= DENSE_VISITED_LIMIT
+        {
+            VisitedSet::Dense {
+                bits: vec![0; (cells as usize).div_ceil(64)],
+                width: map.width,
+                height: map.height,
+            }
+        } else {
+            VisitedSet::Sparse(HashSet::new())
+        }
+    }
+
+    /// Bit index of a coordinate, or None if it lies outside the map bounds
+    fn index(coord: HexCoord, width: i32, height: i32) -> Option<usize> {
+        let (col, row) = coord.to_offset();
+        if (0..width).contains(&col) && (0..height).contains(&row) {
+            Some((row * width + col) as usize)
+        } else {
+            None
+        }
+    }
+
+    /// Check whether a hex has been visited
+    fn contains(&self, coord: HexCoord) -> bool {
+        match self {
+            VisitedSet::Dense {
+                bits,
+                width,
+                height,
+            } => Self::index(coord, *width, *height)
+                .is_some_and(|i| bits[i / 64] & (1 << (i % 64)) != 0),
+            VisitedSet::Sparse(set) => set.contains(&coord),
+        }
+    }
+
+    /// Mark a hex visited, returning false if it already was
+    ///
+    /// Hexes outside a dense set's bounds can't be stored and are always
+    /// reported as newly visited; searches never step off the map.
+    fn insert(&mut self, coord: HexCoord) -> bool {
+        match self {
+            VisitedSet::Dense {
+                bits,
+                width,
+                height,
+            } => match Self::index(coord, *width, *height) {
+                Some(i) => {
+                    let mask = 1 << (i % 64);
+                    let fresh = bits[i / 64] & mask == 0;
+                    bits[i / 64] |= mask;
+                    fresh
+                }
+                None => true,
+            },
+            VisitedSet::Sparse(set) => set.insert(coord),
+        }
     }
 }
 
-impl PartialOrd for PathNode {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.cmp(other))
+/// Optional rules for path searches
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PathOptions {
+    /// MP charged for each 60° the unit turns before stepping to the next hex
+    ///
+    /// Zero ignores facing entirely, as `find_path` does.
+    pub pivot_cost: u32,
+    /// Hexes to treat as impassable for this query only
+    #[serde(default)]
+    pub avoid: HashSet<HexCoord>,
+    /// How the search estimates the cost still to go
+    #[serde(default)]
+    pub heuristic: HeuristicConfig,
+}
+
+/// Tuning for the A* distance heuristic
+///
+/// By default each remaining hex is assumed to cost 1 MP, which is always
+/// safe but explores widely on maps where nothing is that cheap.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HeuristicConfig {
+    /// Scale the estimate by the cheapest hex on the map for the unit
+    ///
+    /// No step costs less than that, so the search still finds the
+    /// cheapest path while expanding fewer hexes.
+    pub weight_by_min_cost: bool,
+}
+
+impl HeuristicConfig {
+    /// Get the MP the heuristic charges per remaining hex
+    fn weight(&self, map: &GameMap, unit: &Unit) -> u32 {
+        if !self.weight_by_min_cost {
+            return 1;
+        }
+
+        map.tiles
+            .keys()
+            .map(|&(q, r)| HexCoord::new(q, r))
+            .filter_map(|hex| movement_cost_for(unit, map, hex, hex))
+            .min()
+            .unwrap_or(1)
     }
 }
 
+/// Count the 60° turns needed to get from one facing to another
+fn pivot_steps(from: Facing, to: Facing) -> u32 {
+    from.steps_to(to).unsigned_abs()
+}
+
 /// Calculate movement cost between two adjacent hexes
 pub fn movement_cost(map: &GameMap, _from: HexCoord, to: HexCoord) -> Option<u32> {
     map.get_tile(to)
         .and_then(|tile| tile.terrain.movement_cost())
 }
 
+/// Calculate movement cost between two adjacent hexes for a specific unit
+///
+/// Applies the unit type's terrain multiplier to the base terrain cost,
+/// rounding up so a penalty is never lost to truncation.
+pub fn movement_cost_for(unit: &Unit, map: &GameMap, from: HexCoord, to: HexCoord) -> Option<u32> {
+    if unit.ignore_water && map.terrain_at(to) == TerrainType::Water {
+        return Some(1);
+    }
+
+    let base = movement_cost(map, from, to)?;
+    let multiplier = unit.unit_type.terrain_multiplier(map.terrain_at(to));
+    Some((base as f32 * multiplier).ceil() as u32)
+}
+
+/// Calculate the full cost for a unit to step between two adjacent hexes
+///
+/// Adds the game's `pass_through_penalty` to the terrain cost when the hex
+/// being entered already holds a friendly unit.
+pub fn step_cost(state: &GameState, unit: &Unit, from: HexCoord, to: HexCoord) -> Option<u32> {
+    let cost = movement_cost_for(unit, &state.map, from, to)?;
+    if state.pass_through_penalty == 0 {
+        return Some(cost);
+    }
+    let congested = state
+        .units_at(to)
+        .iter()
+        .any(|u| u.id != unit.id && u.owner == unit.owner);
+    Some(if congested {
+        cost + state.pass_through_penalty
+    } else {
+        cost
+    })
+}
+
+/// `step_cost` for the many edges of one search
+///
+/// The hexes holding the unit's friends are gathered once up front instead
+/// of scanning every unit per step, and not at all when there is no
+/// `pass_through_penalty` to charge.
+struct StepCosts<'a> {
+    state: &'a GameState,
+    unit: &'a Unit,
+    friendly: HashSet<HexCoord>,
+}
+
+impl<'a> StepCosts<'a> {
+    fn new(state: &'a GameState, unit: &'a Unit) -> Self {
+        let friendly = if state.pass_through_penalty == 0 {
+            HashSet::new()
+        } else {
+            state
+                .player_units_iter(unit.owner)
+                .filter(|u| u.id != unit.id && !u.is_embarked())
+                .map(|u| u.position)
+                .collect()
+        };
+        StepCosts {
+            state,
+            unit,
+            friendly,
+        }
+    }
+
+    fn cost(&self, from: HexCoord, to: HexCoord) -> Option<u32> {
+        let cost = movement_cost_for(self.unit, &self.state.map, from, to)?;
+        Some(if self.friendly.contains(&to) {
+            cost + self.state.pass_through_penalty
+        } else {
+            cost
+        })
+    }
+}
+
 /// Check if a hex is blocked (by terrain or unit)
 pub fn is_blocked(state: &GameState, coord: HexCoord, moving_unit_id: u32) -> bool {
     // Check terrain
@@ -56,19 +371,13 @@ pub fn is_blocked(state: &GameState, coord: HexCoord, moving_unit_id: u32) -> bo
     }
 
     let terrain = state.map.terrain_at(coord);
-    if terrain == TerrainType::Impassable {
+    if terrain.movement_cost().is_none() {
         return true;
     }
 
-    // Check for enemy units (friendly units can be moved through but not stopped on)
-    if let Some(unit) = state.unit_at(coord) {
-        if unit.id != moving_unit_id {
-            // Can't stop on a hex with another unit
-            return true;
-        }
-    }
-
-    false
+    // Check for units (friendly units can be moved through, but only stopped on
+    // while the stacking limit allows)
+    !state.has_room(coord, moving_unit_id)
 }
 
 /// Check if a hex can be passed through (for pathfinding)
@@ -79,100 +388,742 @@ pub fn can_pass_through(state: &GameState, coord: HexCoord, moving_unit: &Unit)
     }
 
     let terrain = state.map.terrain_at(coord);
-    if terrain == TerrainType::Impassable {
+    if terrain.movement_cost().is_none() {
         return false;
     }
 
-    // Check for units
-    if let Some(unit) = state.unit_at(coord) {
-        if unit.id == moving_unit.id {
-            return true; // Can always be at own position
+    // Can pass through friendly units but not enemy units
+    state
+        .units_at(coord)
+        .iter()
+        .all(|unit| unit.id == moving_unit.id || unit.owner == moving_unit.owner)
+}
+
+/// Validate a move path for a unit, pairing each hex with the MP left on reaching it
+///
+/// The path must start at the unit, step only between adjacent passable
+/// hexes, end on a free hex, and fit within the unit's remaining movement.
+/// A path of just the start hex holds position. A loop back to the start is
+/// checked like any other move, so the unit must have room to re-enter it.
+pub(crate) fn validate_path(
+    state: &GameState,
+    unit: &Unit,
+    path: &[HexCoord],
+) -> Result<Vec<(HexCoord, u32)>, CommandError> {
+    let (&start, &end) = match (path.first(), path.last()) {
+        (Some(start), Some(end)) => (start, end),
+        _ => return Err(CommandError::EmptyPath),
+    };
+
+    if start != unit.position {
+        return Err(CommandError::PathStartMismatch(start));
+    }
+
+    if !state.map.is_valid(end) {
+        return Err(CommandError::InvalidDestination(end));
+    }
+
+    if path.len() > 1 && !state.has_room(end, unit.id) {
+        return Err(CommandError::Occupied(end));
+    }
+
+    let budget = unit.effective_movement();
+    let mut spent = 0;
+    let mut steps = Vec::with_capacity(path.len());
+    steps.push((start, budget));
+
+    for step in path.windows(2) {
+        let (from, to) = (step[0], step[1]);
+
+        if state.map.distance(from, to) != 1 {
+            return Err(CommandError::NotAdjacent(to));
         }
-        // Can pass through friendly units but not enemy units
-        if unit.owner == moving_unit.owner {
-            return true;
+
+        if !can_pass_through(state, to, unit) {
+            return Err(CommandError::PathBlocked(to));
         }
-        return false;
+
+        let cost = step_cost(state, unit, from, to).ok_or(CommandError::PathBlocked(to))?;
+        spent += cost;
+        steps.push((to, budget.saturating_sub(spent)));
     }
 
-    true
+    if spent > budget {
+        return Err(CommandError::InsufficientMovement {
+            needed: spent,
+            available: budget,
+        });
+    }
+
+    Ok(steps)
+}
+
+/// Preview a move path, pairing each hex with the MP the unit has left on reaching it
+pub fn path_with_costs(
+    state: &GameState,
+    unit: &Unit,
+    path: &[HexCoord],
+) -> Result<Vec<(HexCoord, u32)>, String> {
+    validate_path(state, unit, path).map_err(|e| e.to_string())
+}
+
+/// Get the terrain of every hex along a path, start included
+///
+/// Off-map hexes read as Impassable, so a preview can flag them without
+/// validating the path first.
+pub fn path_terrain(map: &GameMap, path: &[HexCoord]) -> Vec<TerrainType> {
+    path.iter().map(|coord| map.terrain_at(*coord)).collect()
 }
 
 /// Find all reachable hexes from a starting position within movement budget
 pub fn find_reachable(state: &GameState, unit: &Unit) -> HashMap<HexCoord, u32> {
-    let mut reachable = HashMap::new();
-    let mut visited = HashSet::new();
-    let mut frontier: BinaryHeap<PathNode> = BinaryHeap::new();
+    find_reachable_with(state, unit, &PathOptions::default())
+}
+
+/// Find all reachable hexes under extra path rules
+///
+/// Only `avoid` applies here; the flood fill ignores facing, so
+/// `pivot_cost` has no effect.
+pub fn find_reachable_with(
+    state: &GameState,
+    unit: &Unit,
+    options: &PathOptions,
+) -> HashMap<HexCoord, u32> {
+    let budget = unit.effective_movement();
+    let mut routes = reachable_within(state, unit, VisitedSet::for_map(&state.map), &options.avoid);
+
+    // Remove hexes where we can't actually stop
+    routes.retain(|coord, _| !is_blocked(state, *coord, unit.id) || *coord == unit.position);
+
+    routes
+        .into_iter()
+        .map(|(coord, route)| (coord, budget.saturating_sub(route.cost)))
+        .collect()
+}
+
+/// Find the path to every reachable hex
+///
+/// Each path is the one `find_path` returns for that hex: cheapest first,
+/// then fewest hexes, so a hover preview matches the move a click makes.
+pub fn reachable_paths(state: &GameState, unit: &Unit) -> HashMap<HexCoord, Vec<HexCoord>> {
+    let routes = reachable_within(
+        state,
+        unit,
+        VisitedSet::for_map(&state.map),
+        &HashSet::new(),
+    );
+
+    routes
+        .keys()
+        .filter(|coord| !is_blocked(state, **coord, unit.id) || **coord == unit.position)
+        .map(|coord| (*coord, trace_route(&routes, *coord)))
+        .collect()
+}
+
+/// Find the path toward a hex that gets a unit as close as it can this turn
+///
+/// Picks the reachable hex nearest `target`, breaking ties by the lowest
+/// movement cost and then reading order. If the target itself is reachable
+/// this is its cheapest path; a unit that can't get any closer stays put.
+pub fn best_approach(
+    state: &GameState,
+    unit: &Unit,
+    target: HexCoord,
+) -> Option<(Vec<HexCoord>, u32)> {
+    let routes = reachable_within(
+        state,
+        unit,
+        VisitedSet::for_map(&state.map),
+        &HashSet::new(),
+    );
+
+    let (end, route) = routes
+        .iter()
+        .filter(|(coord, _)| !is_blocked(state, **coord, unit.id) || **coord == unit.position)
+        .min_by_key(|(coord, route)| (state.map.distance(**coord, target), route.cost, **coord))?;
+
+    Some((trace_route(&routes, *end), route.cost))
+}
+
+/// Dijkstra flood fill behind `find_reachable`, with the closed set supplied
+///
+/// Returns the best route into every hex the unit can enter, including
+/// hexes it may only pass through.
+fn reachable_within(
+    state: &GameState,
+    unit: &Unit,
+    mut visited: VisitedSet,
+    avoid: &HashSet<HexCoord>,
+) -> HashMap<HexCoord, Route> {
+    let mut routes: HashMap<HexCoord, Route> = HashMap::new();
+    let mut frontier = RouteFrontier::default();
 
     let start = unit.position;
     let budget = unit.effective_movement();
 
-    frontier.push(PathNode {
+    routes.insert(
+        start,
+        Route {
+            cost: 0,
+            steps: 0,
+            prev: None,
+        },
+    );
+    frontier.push(RouteNode {
+        coord: start,
+        cost: 0,
+        steps: 0,
+        heuristic: 0,
+        estimate: 0,
+    });
+
+    let step_costs = StepCosts::new(state, unit);
+
+    while let Some(current) = frontier.pop() {
+        if !visited.insert(current.coord) {
+            continue;
+        }
+
+        // Explore neighbors
+        for neighbor in state.map.neighbors(current.coord) {
+            if visited.contains(neighbor) || avoid.contains(&neighbor) {
+                continue;
+            }
+
+            // Friendly-occupied hexes can be passed through; the caller
+            // drops the ones the unit can't stop in
+            if !can_pass_through(state, neighbor, unit) {
+                continue;
+            }
+
+            if let Some(cost) = step_costs.cost(current.coord, neighbor) {
+                let new_cost = current.cost + cost;
+                let new_steps = current.steps + 1;
+                if new_cost > budget {
+                    continue;
+                }
+
+                let better = routes
+                    .get(&neighbor)
+                    .is_none_or(|r| r.improved_by(new_cost, new_steps, current.coord));
+                if better {
+                    routes.insert(
+                        neighbor,
+                        Route {
+                            cost: new_cost,
+                            steps: new_steps,
+                            prev: Some(current.coord),
+                        },
+                    );
+                    frontier.push(RouteNode {
+                        coord: neighbor,
+                        cost: new_cost,
+                        steps: new_steps,
+                        heuristic: 0,
+                        estimate: 0,
+                    });
+                }
+            }
+        }
+    }
+
+    routes
+}
+
+/// List reachable hexes in a stable order for keyboard navigation
+///
+/// Sorted nearest first by hex distance from the unit, then by q and r.
+pub fn reachable_sorted(state: &GameState, unit: &Unit) -> Vec<(HexCoord, u32)> {
+    let mut reachable: Vec<(HexCoord, u32)> = find_reachable(state, unit).into_iter().collect();
+    reachable
+        .sort_by_key(|(coord, _)| (state.map.distance(unit.position, *coord), coord.q, coord.r));
+    reachable
+}
+
+/// Find the shortest path between two hexes using A*
+///
+/// Among equally cheap paths the one entering the fewest hexes wins, with
+/// any remaining tie broken the same way as `reachable_paths`.
+pub fn find_path(
+    state: &GameState,
+    unit: &Unit,
+    target: HexCoord,
+    max_cost: Option<u32>,
+) -> Option<(Vec<HexCoord>, u32)> {
+    let heuristic = HeuristicConfig::default();
+    search_route(state, unit, target, max_cost, &HashSet::new(), heuristic).0
+}
+
+/// A* behind `find_path`, never entering a hex in `avoid`
+///
+/// Also returns how many hexes the search expanded.
+fn search_route(
+    state: &GameState,
+    unit: &Unit,
+    target: HexCoord,
+    max_cost: Option<u32>,
+    avoid: &HashSet<HexCoord>,
+    heuristic: HeuristicConfig,
+) -> (Option<(Vec<HexCoord>, u32)>, usize) {
+    let start = unit.position;
+    let budget = max_cost.unwrap_or(unit.effective_movement());
+    let weight = heuristic.weight(&state.map, unit);
+    let mut expanded = 0;
+
+    if start == target {
+        return (Some((vec![start], 0)), expanded);
+    }
+
+    if is_blocked(state, target, unit.id) || avoid.contains(&target) {
+        return (None, expanded);
+    }
+
+    let mut open_set = RouteFrontier::default();
+    let mut routes: HashMap<HexCoord, Route> = HashMap::new();
+
+    routes.insert(
+        start,
+        Route {
+            cost: 0,
+            steps: 0,
+            prev: None,
+        },
+    );
+
+    let distance = state.map.distance(start, target);
+    open_set.push(RouteNode {
+        coord: start,
+        cost: 0,
+        steps: 0,
+        heuristic: distance,
+        estimate: distance * weight,
+    });
+
+    let step_costs = StepCosts::new(state, unit);
+
+    while let Some(current) = open_set.pop() {
+        let best = routes[&current.coord];
+        if (current.cost, current.steps) != (best.cost, best.steps) {
+            // Stale entry superseded by a better route
+            continue;
+        }
+
+        if current.coord == target {
+            return (Some((trace_route(&routes, target), best.cost)), expanded);
+        }
+        expanded += 1;
+
+        for neighbor in state.map.neighbors(current.coord) {
+            if avoid.contains(&neighbor) || !can_pass_through(state, neighbor, unit) {
+                continue;
+            }
+
+            if let Some(cost) = step_costs.cost(current.coord, neighbor) {
+                let tentative_g = current.cost + cost;
+                let steps = current.steps + 1;
+
+                if tentative_g > budget {
+                    continue;
+                }
+
+                let better = routes
+                    .get(&neighbor)
+                    .is_none_or(|r| r.improved_by(tentative_g, steps, current.coord));
+                if better {
+                    let pushed = routes.get(&neighbor).map(|r| (r.cost, r.steps));
+                    routes.insert(
+                        neighbor,
+                        Route {
+                            cost: tentative_g,
+                            steps,
+                            prev: Some(current.coord),
+                        },
+                    );
+
+                    // A new predecessor at the same cost needs no new entry
+                    if pushed != Some((tentative_g, steps)) {
+                        let distance = state.map.distance(neighbor, target);
+                        open_set.push(RouteNode {
+                            coord: neighbor,
+                            cost: tentative_g,
+                            steps,
+                            heuristic: distance,
+                            estimate: distance * weight,
+                        });
+                    }
+                }
+            }
+        }
+    }
+
+    (None, expanded)
+}
+
+/// Measure the cheapest movement cost from a unit to a hex
+///
+/// Unlike `find_path` this ignores the unit's remaining movement, so the
+/// result can exceed what it could move this turn. The target itself may be
+/// occupied; only the hexes on the way must be passable.
+pub fn measure(state: &GameState, unit: &Unit, target: HexCoord) -> Option<u32> {
+    let mut visited = VisitedSet::for_map(&state.map);
+    let mut frontier = PathFrontier::default();
+
+    frontier.push(
+        PathNode {
+            coord: unit.position,
+            cost: 0,
+        },
+        0,
+    );
+
+    let step_costs = StepCosts::new(state, unit);
+
+    while let Some(current) = frontier.pop() {
+        if current.coord == target {
+            return Some(current.cost);
+        }
+        if !visited.insert(current.coord) {
+            continue;
+        }
+
+        for neighbor in state.map.neighbors(current.coord) {
+            if visited.contains(neighbor) {
+                continue;
+            }
+            if neighbor != target && !can_pass_through(state, neighbor, unit) {
+                continue;
+            }
+
+            if let Some(cost) = step_costs.cost(current.coord, neighbor) {
+                let new_cost = current.cost + cost;
+                frontier.push(
+                    PathNode {
+                        coord: neighbor,
+                        cost: new_cost,
+                    },
+                    new_cost,
+                );
+            }
+        }
+    }
+
+    None
+}
+
+/// Find the cheapest path between two hexes under extra path rules
+///
+/// With a pivot cost the search state becomes (hex, facing): the unit starts
+/// at its current facing and pays `pivot_cost` per 60° it turns to step
+/// toward each hex, so the returned cost includes the MP spent turning.
+/// Hexes in `avoid` are never entered, and an avoided target has no path.
+/// The heuristic setting changes how much is searched, never the cost found.
+pub fn find_path_with(
+    state: &GameState,
+    unit: &Unit,
+    target: HexCoord,
+    max_cost: Option<u32>,
+    options: &PathOptions,
+) -> Option<(Vec<HexCoord>, u32)> {
+    if options.pivot_cost == 0 {
+        return search_route(
+            state,
+            unit,
+            target,
+            max_cost,
+            &options.avoid,
+            options.heuristic,
+        )
+        .0;
+    }
+
+    let start = unit.position;
+    let budget = max_cost.unwrap_or(unit.effective_movement());
+    let weight = options.heuristic.weight(&state.map, unit);
+
+    if start == target {
+        return Some((vec![start], 0));
+    }
+
+    if is_blocked(state, target, unit.id) || options.avoid.contains(&target) {
+        return None;
+    }
+
+    let mut open_set = FacingFrontier::default();
+    let mut came_from: HashMap<(HexCoord, Facing), (HexCoord, Facing)> = HashMap::new();
+    let mut g_score: HashMap<(HexCoord, Facing), u32> = HashMap::new();
+
+    g_score.insert((start, unit.facing), 0);
+    open_set.push(FacingNode {
         coord: start,
+        facing: unit.facing,
         cost: 0,
-        priority: 0,
+        priority: state.map.distance(start, target) * weight,
     });
 
+    let step_costs = StepCosts::new(state, unit);
+
+    while let Some(current) = open_set.pop() {
+        let key = (current.coord, current.facing);
+        if current.cost > *g_score.get(&key).unwrap_or(&u32::MAX) {
+            continue;
+        }
+
+        if current.coord == target {
+            let mut path = vec![target];
+            let mut node = key;
+            while let Some(&prev) = came_from.get(&node) {
+                path.push(prev.0);
+                node = prev;
+            }
+            path.reverse();
+            return Some((path, current.cost));
+        }
+
+        for (index, neighbor) in state.map.neighbors(current.coord).into_iter().enumerate() {
+            if options.avoid.contains(&neighbor) || !can_pass_through(state, neighbor, unit) {
+                continue;
+            }
+
+            let Some(step) = step_costs.cost(current.coord, neighbor) else {
+                continue;
+            };
+            let facing = Facing::from_index(index as u8).unwrap();
+            let tentative_g =
+                current.cost + step + pivot_steps(current.facing, facing) * options.pivot_cost;
+
+            if tentative_g > budget {
+                continue;
+            }
+
+            let next = (neighbor, facing);
+            if tentative_g < *g_score.get(&next).unwrap_or(&u32::MAX) {
+                came_from.insert(next, key);
+                g_score.insert(next, tentative_g);
+                open_set.push(FacingNode {
+                    coord: neighbor,
+                    facing,
+                    cost: tentative_g,
+                    priority: tentative_g + state.map.distance(neighbor, target) * weight,
+                });
+            }
+        }
+    }
+
+    None
+}
+
+/// Total the MP a path costs, including turns when pivots are charged
+///
+/// Returns None if any step is not between adjacent enterable hexes or
+/// enters an avoided hex.
+pub fn path_cost_with(
+    state: &GameState,
+    unit: &Unit,
+    path: &[HexCoord],
+    options: &PathOptions,
+) -> Option<u32> {
+    let mut facing = unit.facing;
+    let mut total = 0;
+
+    for step in path.windows(2) {
+        let (from, to) = (step[0], step[1]);
+        if options.avoid.contains(&to) {
+            return None;
+        }
+        let index = state.map.neighbors(from).iter().position(|n| *n == to)?;
+        let heading = Facing::from_index(index as u8).unwrap();
+        total += step_cost(state, unit, from, to)?;
+        total += pivot_steps(facing, heading) * options.pivot_cost;
+        facing = heading;
+    }
+
+    Some(total)
+}
+
+/// One step of a path with the movement cost of entering its hex split by source
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MoveStep {
+    /// Hex the step enters
+    pub hex: HexCoord,
+    /// Terrain cost after the unit type's multiplier
+    pub terrain: u32,
+    /// Penalty for entering a hex held by a friendly unit
+    pub congestion: u32,
+    /// Cost of turning to face the hex, when pivots are charged
+    pub pivot: u32,
+}
+
+impl MoveStep {
+    /// Get the step's total movement cost
+    pub fn total(&self) -> u32 {
+        self.terrain + self.congestion + self.pivot
+    }
+}
+
+/// Break a path's movement cost down step by step
+///
+/// The steps add up to `path_cost_with`. Returns None if any step is not
+/// between adjacent enterable hexes.
+pub fn path_cost_breakdown(
+    state: &GameState,
+    unit: &Unit,
+    path: &[HexCoord],
+    options: &PathOptions,
+) -> Option<Vec<MoveStep>> {
+    let mut facing = unit.facing;
+    let mut steps = Vec::with_capacity(path.len().saturating_sub(1));
+
+    for step in path.windows(2) {
+        let (from, to) = (step[0], step[1]);
+        let index = state.map.neighbors(from).iter().position(|n| *n == to)?;
+        let heading = Facing::from_index(index as u8).unwrap();
+        let terrain = movement_cost_for(unit, &state.map, from, to)?;
+        steps.push(MoveStep {
+            hex: to,
+            terrain,
+            congestion: step_cost(state, unit, from, to)? - terrain,
+            pivot: pivot_steps(facing, heading) * options.pivot_cost,
+        });
+        facing = heading;
+    }
+
+    Some(steps)
+}
+
+/// Measure the cheapest movement cost between every ordered pair of live units
+///
+/// Runs one unbounded Dijkstra per unit, so compute it once per turn and
+/// reuse it. Costs use the moving unit's terrain rules, so (a, b) and
+/// (b, a) can differ; None means the other unit can't be reached at all.
+pub fn unit_distance_matrix(state: &GameState) -> HashMap<(u32, u32), Option<u32>> {
+    let live: Vec<&Unit> = state.units.iter().filter(|u| !u.is_destroyed()).collect();
+    let mut matrix = HashMap::new();
+
+    for from in &live {
+        let costs = cost_field(state, from);
+        for to in &live {
+            if from.id != to.id {
+                matrix.insert((from.id, to.id), costs.get(&to.position).copied());
+            }
+        }
+    }
+
+    matrix
+}
+
+/// Cheapest unbounded movement cost from a unit to every hex it can reach
+///
+/// Like `measure`, a hex the unit can't pass through still gets a cost, but
+/// the search doesn't continue past it.
+fn cost_field(state: &GameState, unit: &Unit) -> HashMap<HexCoord, u32> {
+    let mut costs = HashMap::new();
+    let mut visited = VisitedSet::for_map(&state.map);
+    let mut frontier = PathFrontier::default();
+
+    frontier.push(
+        PathNode {
+            coord: unit.position,
+            cost: 0,
+        },
+        0,
+    );
+
+    let step_costs = StepCosts::new(state, unit);
+
     while let Some(current) = frontier.pop() {
-        if visited.contains(&current.coord) {
+        if !visited.insert(current.coord) {
+            continue;
+        }
+        costs.insert(current.coord, current.cost);
+
+        if current.coord != unit.position && !can_pass_through(state, current.coord, unit) {
             continue;
         }
-        visited.insert(current.coord);
 
-        // Record remaining MP at this hex
-        let remaining = budget.saturating_sub(current.cost);
-        reachable.insert(current.coord, remaining);
-
-        // Explore neighbors
-        for neighbor in current.coord.neighbors() {
-            if visited.contains(&neighbor) {
+        for neighbor in state.map.neighbors(current.coord) {
+            if visited.contains(neighbor) {
                 continue;
             }
 
-            if !can_pass_through(state, neighbor, unit) {
-                continue;
+            if let Some(cost) = step_costs.cost(current.coord, neighbor) {
+                let new_cost = current.cost + cost;
+                frontier.push(
+                    PathNode {
+                        coord: neighbor,
+                        cost: new_cost,
+                    },
+                    new_cost,
+                );
             }
+        }
+    }
 
-            if let Some(cost) = movement_cost(&state.map, current.coord, neighbor) {
-                let new_cost = current.cost + cost;
-                if new_cost <= budget {
-                    // Check if we can stop here (not just pass through)
-                    let can_stop = !is_blocked(state, neighbor, unit.id);
-                    if can_stop {
-                        frontier.push(PathNode {
-                            coord: neighbor,
-                            cost: new_cost,
-                            priority: new_cost,
-                        });
-                    } else {
-                        // Can pass through but not stop - still add to frontier for pathfinding
-                        // but don't add to reachable
-                        frontier.push(PathNode {
-                            coord: neighbor,
-                            cost: new_cost,
-                            priority: new_cost,
-                        });
-                    }
-                }
+    costs
+}
+
+/// Collect every hex connected to a start hex through passable terrain
+///
+/// A breadth-first flood fill that ignores units and movement costs, for
+/// map analysis such as checking that deployment zones can reach each
+/// other. Returns an empty set if the start itself isn't passable.
+pub fn connected_region(
+    map: &GameMap,
+    start: HexCoord,
+    passable: impl Fn(TerrainType) -> bool,
+) -> HashSet<HexCoord> {
+    let mut region = HashSet::new();
+    if !map.is_valid(start) || !passable(map.terrain_at(start)) {
+        return region;
+    }
+
+    let mut queue = VecDeque::from([start]);
+    region.insert(start);
+
+    while let Some(current) = queue.pop_front() {
+        for neighbor in map.neighbors(current) {
+            if map.is_valid(neighbor)
+                && passable(map.terrain_at(neighbor))
+                && region.insert(neighbor)
+            {
+                queue.push_back(neighbor);
             }
         }
     }
 
-    // Remove hexes where we can't actually stop
-    reachable.retain(|coord, _| !is_blocked(state, *coord, unit.id) || *coord == start);
+    region
+}
 
-    reachable
+/// Get all map hexes within weapon range of a player's units
+pub fn threat_hexes(state: &GameState, player: Player) -> HashSet<HexCoord> {
+    let mut threatened = HashSet::new();
+    let hexes = state.map.all_hexes();
+
+    for unit in state.player_units_iter(player) {
+        let range = unit.unit_type.weapon_range();
+        for hex in &hexes {
+            if state.map.distance(unit.position, *hex) <= range {
+                threatened.insert(*hex);
+            }
+        }
+    }
+
+    threatened
 }
 
-/// Find the shortest path between two hexes using A*
-pub fn find_path(
+/// Find a path that avoids hexes threatened by the enemy where practical
+///
+/// Each threatened hex entered adds `threat_penalty` to the route's search
+/// priority, so the path trades extra distance for safety. The returned cost
+/// is the true movement cost of the path; the penalty only affects which
+/// route is chosen.
+pub fn find_safe_path(
     state: &GameState,
     unit: &Unit,
     target: HexCoord,
-    max_cost: Option<u32>,
+    threat_penalty: u32,
 ) -> Option<(Vec<HexCoord>, u32)> {
     let start = unit.position;
-    let budget = max_cost.unwrap_or(unit.effective_movement());
+    let budget = unit.effective_movement();
 
     if start == target {
         return Some((vec![start], 0));
@@ -182,21 +1133,30 @@ pub fn find_path(
         return None;
     }
 
-    let mut open_set: BinaryHeap<PathNode> = BinaryHeap::new();
+    let threats = threat_hexes(state, unit.owner.opponent());
+
+    let mut open_set = PathFrontier::default();
     let mut came_from: HashMap<HexCoord, HexCoord> = HashMap::new();
+    // Penalized score used for route choice
     let mut g_score: HashMap<HexCoord, u32> = HashMap::new();
+    // True movement cost along the chosen route
+    let mut mp_spent: HashMap<HexCoord, u32> = HashMap::new();
 
     g_score.insert(start, 0);
+    mp_spent.insert(start, 0);
 
-    open_set.push(PathNode {
-        coord: start,
-        cost: 0,
-        priority: start.distance_to(target),
-    });
+    open_set.push(
+        PathNode {
+            coord: start,
+            cost: 0,
+        },
+        state.map.distance(start, target),
+    );
+
+    let step_costs = StepCosts::new(state, unit);
 
     while let Some(current) = open_set.pop() {
         if current.coord == target {
-            // Reconstruct path
             let mut path = vec![target];
             let mut current_coord = target;
             while let Some(&prev) = came_from.get(&current_coord) {
@@ -204,33 +1164,44 @@ pub fn find_path(
                 current_coord = prev;
             }
             path.reverse();
-            return Some((path, *g_score.get(&target).unwrap()));
+            return Some((path, *mp_spent.get(&target).unwrap()));
         }
 
         let current_g = *g_score.get(&current.coord).unwrap_or(&u32::MAX);
+        let current_mp = *mp_spent.get(&current.coord).unwrap_or(&u32::MAX);
 
-        for neighbor in current.coord.neighbors() {
+        for neighbor in state.map.neighbors(current.coord) {
             if !can_pass_through(state, neighbor, unit) {
                 continue;
             }
 
-            if let Some(cost) = movement_cost(&state.map, current.coord, neighbor) {
-                let tentative_g = current_g + cost;
+            if let Some(cost) = step_costs.cost(current.coord, neighbor) {
+                let tentative_mp = current_mp + cost;
 
-                if tentative_g > budget {
+                if tentative_mp > budget {
                     continue;
                 }
 
+                let penalty = if threats.contains(&neighbor) {
+                    threat_penalty
+                } else {
+                    0
+                };
+                let tentative_g = current_g + cost + penalty;
+
                 if tentative_g < *g_score.get(&neighbor).unwrap_or(&u32::MAX) {
                     came_from.insert(neighbor, current.coord);
                     g_score.insert(neighbor, tentative_g);
+                    mp_spent.insert(neighbor, tentative_mp);
 
-                    let f_score = tentative_g + neighbor.distance_to(target);
-                    open_set.push(PathNode {
-                        coord: neighbor,
-                        cost: tentative_g,
-                        priority: f_score,
-                    });
+                    let f_score = tentative_g + state.map.distance(neighbor, target);
+                    open_set.push(
+                        PathNode {
+                            coord: neighbor,
+                            cost: tentative_g,
+                        },
+                        f_score,
+                    );
                 }
             }
         }
@@ -281,7 +1252,7 @@ impl MovementPath {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::rules::{GameMap, GameState, Player, UnitType};
+    use crate::rules::{GameMap, GameState, Player, UnitType, Wrap};
 
     fn setup_test_state() -> GameState {
         let map = GameMap::new(10, 10);
@@ -316,6 +1287,26 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_path_frontier_pop_order() {
+        let mut frontier = PathFrontier::default();
+        for (q, r, priority) in [(0, 0, 3), (1, 0, 1), (0, 2, 1), (2, 0, 1), (0, 1, 0)] {
+            frontier.push(
+                PathNode {
+                    coord: HexCoord::new(q, r),
+                    cost: priority,
+                },
+                priority,
+            );
+        }
+
+        let order: Vec<(i32, i32)> = std::iter::from_fn(|| frontier.pop())
+            .map(|n| (n.coord.q, n.coord.r))
+            .collect();
+        // Lowest priority first; ties go to the greatest (q, r)
+        assert_eq!(order, vec![(0, 1), (2, 0), (1, 0), (0, 2), (0, 0)]);
+    }
+
     #[test]
     fn test_find_path() {
         let state = setup_test_state();
@@ -336,9 +1327,624 @@ mod tests {
         assert_eq!(cost, 3);
     }
 
+    #[test]
+    fn test_heavy_units_pay_more_in_woods() {
+        let mut map = GameMap::new(10, 10);
+        for q in 1..=3 {
+            map.tiles.get_mut(&(q, 0)).unwrap().terrain = TerrainType::Woods;
+        }
+        let mut state = GameState::new(map);
+        state.add_unit(Unit::new(
+            1,
+            UnitType::WarlordTitan,
+            Player::Player1,
+            HexCoord::new(0, 0),
+            Facing::East,
+        ));
+        state.add_unit(Unit::new(
+            2,
+            UnitType::Shadowsword,
+            Player::Player1,
+            HexCoord::new(0, 0),
+            Facing::East,
+        ));
+
+        let titan = state.get_unit(1).unwrap();
+        let tank = state.get_unit(2).unwrap();
+        let path = [
+            HexCoord::new(0, 0),
+            HexCoord::new(1, 0),
+            HexCoord::new(2, 0),
+        ];
+
+        let titan_cost: u32 = path
+            .windows(2)
+            .map(|step| movement_cost_for(titan, &state.map, step[0], step[1]).unwrap())
+            .sum();
+        let tank_cost: u32 = path
+            .windows(2)
+            .map(|step| movement_cost_for(tank, &state.map, step[0], step[1]).unwrap())
+            .sum();
+
+        assert_eq!(tank_cost, 4);
+        assert_eq!(titan_cost, 8);
+        assert!(titan_cost > tank_cost);
+    }
+
+    #[test]
+    fn test_path_cost_breakdown() {
+        let mut map = GameMap::new(10, 10);
+        map.tiles.get_mut(&(2, 0)).unwrap().terrain = TerrainType::Rough;
+        let mut state = GameState::new(map);
+        state.pass_through_penalty = 1;
+        state.add_unit(Unit::new(
+            1,
+            UnitType::Shadowsword,
+            Player::Player1,
+            HexCoord::new(0, 0),
+            Facing::East,
+        ));
+        state.add_unit(Unit::new(
+            2,
+            UnitType::KriegSquad,
+            Player::Player1,
+            HexCoord::new(3, 0),
+            Facing::East,
+        ));
+        let tank = state.get_unit(1).unwrap();
+        let options = PathOptions {
+            pivot_cost: 1,
+            ..PathOptions::default()
+        };
+        // Across the rough ground, through a friendly squad and then a
+        // turn to the southeast
+        let path = [
+            HexCoord::new(0, 0),
+            HexCoord::new(1, 0),
+            HexCoord::new(2, 0),
+            HexCoord::new(3, 0),
+            HexCoord::new(3, 1),
+        ];
+
+        let steps = path_cost_breakdown(&state, tank, &path, &options).unwrap();
+        let parts: Vec<(u32, u32, u32)> = steps
+            .iter()
+            .map(|s| (s.terrain, s.congestion, s.pivot))
+            .collect();
+        assert_eq!(parts, vec![(1, 0, 0), (2, 0, 0), (1, 1, 0), (1, 0, 1)]);
+        assert_eq!(steps[0].hex, HexCoord::new(1, 0));
+
+        let total: u32 = steps.iter().map(MoveStep::total).sum();
+        assert_eq!(Some(total), path_cost_with(&state, tank, &path, &options));
+        assert!(path_cost_breakdown(&state, tank, &[path[0], path[2]], &options).is_none());
+    }
+
+    #[test]
+    fn test_friendly_units_congest_paths() {
+        let mut state = GameState::new(GameMap::new(10, 10));
+        state.pass_through_penalty = 1;
+        let positions = [(0, 0), (1, 0), (2, 0)];
+        for (id, (q, r)) in (1..).zip(positions) {
+            state.add_unit(Unit::new(
+                id,
+                UnitType::Shadowsword,
+                Player::Player1,
+                HexCoord::new(q, r),
+                Facing::East,
+            ));
+        }
+        let tank = state.get_unit(1).unwrap();
+        let options = PathOptions::default();
+
+        // Both routes take three steps; only the first threads the column
+        let crowded: Vec<HexCoord> = (0..4).map(|q| HexCoord::new(q, 0)).collect();
+        let clear = vec![
+            HexCoord::new(0, 0),
+            HexCoord::new(0, 1),
+            HexCoord::new(1, 1),
+            HexCoord::new(2, 1),
+        ];
+
+        let crowded_cost = path_cost_with(&state, tank, &crowded, &options).unwrap();
+        let clear_cost = path_cost_with(&state, tank, &clear, &options).unwrap();
+        assert_eq!(crowded_cost, clear_cost + 2);
+
+        // Searches price steps from a prebuilt set of friendly hexes
+        let step_costs = StepCosts::new(&state, tank);
+        for hex in tank.position.hexes_in_range(3) {
+            for neighbor in state.map.neighbors(hex) {
+                assert_eq!(
+                    step_costs.cost(hex, neighbor),
+                    step_cost(&state, tank, hex, neighbor)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_safe_path_detours_around_threat() {
+        let map = GameMap::new(10, 10);
+        let mut state = GameState::new(map);
+        state.add_unit(Unit::new(
+            1,
+            UnitType::Shadowsword,
+            Player::Player1,
+            HexCoord::new(0, 4),
+            Facing::East,
+        ));
+        state.add_unit(Unit::new(
+            2,
+            UnitType::KriegSquad,
+            Player::Player2,
+            HexCoord::new(1, 6),
+            Facing::Northwest,
+        ));
+
+        let unit = state.get_unit(1).unwrap();
+        let target = HexCoord::new(4, 4);
+        let threats = threat_hexes(&state, Player::Player2);
+
+        // The direct route runs through the enemy's threat range
+        let (direct, direct_cost) = find_path(&state, unit, target, None).unwrap();
+        assert!(direct.iter().any(|hex| threats.contains(hex)));
+
+        // With no penalty the safe path is just the shortest path
+        let (_, free_cost) = find_safe_path(&state, unit, target, 0).unwrap();
+        assert_eq!(free_cost, direct_cost);
+
+        // A high penalty detours around the threatened hexes
+        let (safe, safe_cost) = find_safe_path(&state, unit, target, 100).unwrap();
+        assert_eq!(safe.first(), Some(&HexCoord::new(0, 4)));
+        assert_eq!(safe.last(), Some(&target));
+        assert!(!safe.iter().any(|hex| threats.contains(hex)));
+        assert_eq!(direct_cost, 4);
+        assert!(safe_cost > direct_cost);
+        assert_eq!(safe_cost, (safe.len() - 1) as u32);
+    }
+
+    #[test]
+    fn test_find_path_across_wrapped_edge() {
+        let map = GameMap::new_wrapped(10, 10, Wrap::Horizontal).unwrap();
+        let mut state = GameState::new(map);
+        state.add_unit(Unit::new(
+            1,
+            UnitType::Shadowsword,
+            Player::Player1,
+            HexCoord::new(9, 0),
+            Facing::East,
+        ));
+        let unit = state.get_unit(1).unwrap();
+
+        let (path, cost) = find_path(&state, unit, HexCoord::new(1, 0), None).unwrap();
+        assert_eq!(path, vec![HexCoord::new(9, 0), HexCoord::new(0, 0), HexCoord::new(1, 0)]);
+        assert_eq!(cost, 2);
+
+        let reachable = find_reachable(&state, unit);
+        assert_eq!(reachable.get(&HexCoord::new(1, 0)), Some(&3));
+    }
+
+    #[test]
+    fn test_path_with_costs() {
+        let mut state = setup_test_state();
+        state.map.tiles.get_mut(&(2, 0)).unwrap().terrain = TerrainType::Rough;
+        state.map.tiles.get_mut(&(3, 0)).unwrap().terrain = TerrainType::Impassable;
+        let unit = state.get_unit(1).unwrap();
+
+        let path = [
+            HexCoord::new(0, 0),
+            HexCoord::new(1, 0),
+            HexCoord::new(2, 0),
+        ];
+        let steps = path_with_costs(&state, unit, &path).unwrap();
+        assert_eq!(
+            steps,
+            vec![
+                (HexCoord::new(0, 0), 5),
+                (HexCoord::new(1, 0), 4),
+                (HexCoord::new(2, 0), 2),
+            ]
+        );
+
+        assert!(path_with_costs(&state, unit, &[]).is_err());
+        let blocked = [
+            HexCoord::new(0, 0),
+            HexCoord::new(1, 0),
+            HexCoord::new(2, 0),
+            HexCoord::new(3, 0),
+        ];
+        assert_eq!(
+            path_with_costs(&state, unit, &blocked),
+            Err("Path blocked at (3, 0)".to_string())
+        );
+    }
+
     #[test]
     fn test_suggest_facing() {
         let facing = suggest_facing(HexCoord::new(0, 0), HexCoord::new(1, 0));
         assert_eq!(facing, Facing::East);
     }
+
+    #[test]
+    fn test_reachable_sorted() {
+        let mut state = setup_test_state();
+        state.map.tiles.get_mut(&(1, 1)).unwrap().terrain = TerrainType::Woods;
+        let unit = state.get_unit(1).unwrap();
+
+        let sorted = reachable_sorted(&state, unit);
+        let reachable = find_reachable(&state, unit);
+        assert_eq!(sorted.len(), reachable.len());
+        for (coord, remaining) in &sorted {
+            assert_eq!(reachable.get(coord), Some(remaining));
+        }
+
+        assert_eq!(sorted[0].0, unit.position);
+        let keys: Vec<_> = sorted
+            .iter()
+            .map(|(c, _)| (state.map.distance(unit.position, *c), c.q, c.r))
+            .collect();
+        assert!(keys.windows(2).all(|pair| pair[0] < pair[1]));
+
+        assert_eq!(reachable_sorted(&state, unit), sorted);
+    }
+
+    #[test]
+    fn test_measure_counts_terrain_beyond_budget() {
+        let mut state = setup_test_state();
+        for q in 1..=8 {
+            state.map.tiles.get_mut(&(q, 0)).unwrap().terrain = TerrainType::Rough;
+        }
+        for r in 1..=2 {
+            for q in -1..=9 {
+                if let Some(tile) = state.map.tiles.get_mut(&(q, r)) {
+                    tile.terrain = TerrainType::Rough;
+                }
+            }
+        }
+        let unit = state.get_unit(1).unwrap();
+        let target = HexCoord::new(8, 0);
+
+        let cost = measure(&state, unit, target).unwrap();
+        assert!(cost > state.map.distance(unit.position, target));
+        assert!(cost > unit.effective_movement());
+        assert!(find_path(&state, unit, target, None).is_none());
+        assert_eq!(measure(&state, unit, unit.position), Some(0));
+    }
+
+    #[test]
+    fn test_weighted_heuristic_expands_less() {
+        let mut map = GameMap::new(12, 12);
+        for tile in map.tiles.values_mut() {
+            tile.terrain = TerrainType::Rough;
+        }
+        let mut state = GameState::new(map);
+        state.add_unit(Unit::new(
+            1,
+            UnitType::Shadowsword,
+            Player::Player1,
+            HexCoord::new(0, 0),
+            Facing::East,
+        ));
+        let unit = state.get_unit(1).unwrap();
+        let target = HexCoord::new(4, 8);
+        let avoid = HashSet::new();
+        let weighted = HeuristicConfig {
+            weight_by_min_cost: true,
+        };
+
+        let (plain_path, plain_expanded) = search_route(
+            &state,
+            unit,
+            target,
+            Some(100),
+            &avoid,
+            HeuristicConfig::default(),
+        );
+        let (weighted_path, weighted_expanded) =
+            search_route(&state, unit, target, Some(100), &avoid, weighted);
+        assert_eq!(plain_path.unwrap().1, weighted_path.unwrap().1);
+        assert!(weighted_expanded < plain_expanded);
+
+        // Pivot searches find the same cost too
+        let options = PathOptions {
+            pivot_cost: 1,
+            ..Default::default()
+        };
+        let weighted_options = PathOptions {
+            heuristic: weighted,
+            ..options.clone()
+        };
+        assert_eq!(
+            find_path_with(&state, unit, target, Some(100), &options).map(|p| p.1),
+            find_path_with(&state, unit, target, Some(100), &weighted_options).map(|p| p.1)
+        );
+    }
+
+    #[test]
+    fn test_pivot_cost_favors_straight_paths() {
+        let state = setup_test_state();
+        let unit = state.get_unit(1).unwrap();
+        let options = PathOptions {
+            pivot_cost: 1,
+            ..Default::default()
+        };
+
+        let straight: Vec<HexCoord> = (0..=4).map(|q| HexCoord::new(q, 0)).collect();
+        let zig_zag = vec![
+            HexCoord::new(0, 0),
+            HexCoord::new(0, 1),
+            HexCoord::new(1, 0),
+            HexCoord::new(1, 1),
+            HexCoord::new(2, 0),
+        ];
+        let plain = PathOptions::default();
+        assert_eq!(path_cost_with(&state, unit, &zig_zag, &plain), Some(4));
+        assert_eq!(path_cost_with(&state, unit, &straight, &options), Some(4));
+        assert!(path_cost_with(&state, unit, &zig_zag, &options).unwrap() > 4);
+
+        let (path, cost) =
+            find_path_with(&state, unit, HexCoord::new(4, 0), Some(20), &options).unwrap();
+        assert_eq!(path, straight);
+        assert_eq!(cost, 4);
+
+        // Heading south-east first costs one pivot on top of the terrain
+        let target = HexCoord::new(0, 3);
+        let (path, cost) = find_path_with(&state, unit, target, Some(20), &options).unwrap();
+        assert_eq!(path.len(), 4);
+        assert_eq!(cost, 4);
+        assert_eq!(path_cost_with(&state, unit, &path, &options), Some(cost));
+
+        assert_eq!(
+            find_path_with(&state, unit, target, Some(20), &plain).map(|(_, c)| c),
+            Some(3)
+        );
+    }
+
+    #[test]
+    fn test_avoid_routes_around_clear_hex() {
+        let state = setup_test_state();
+        let unit = state.get_unit(1).unwrap();
+        let target = HexCoord::new(2, 0);
+        let blocked = HexCoord::new(1, 0);
+        let options = PathOptions {
+            avoid: HashSet::from([blocked]),
+            ..Default::default()
+        };
+
+        let (direct, _) = find_path(&state, unit, target, None).unwrap();
+        assert!(direct.contains(&blocked));
+
+        let (path, cost) = find_path_with(&state, unit, target, None, &options).unwrap();
+        assert!(!path.contains(&blocked));
+        assert_eq!(path.len(), 4);
+        assert_eq!(cost, 3);
+        assert_eq!(path_cost_with(&state, unit, &direct, &options), None);
+
+        assert!(find_path_with(&state, unit, blocked, None, &options).is_none());
+        let reachable = find_reachable_with(&state, unit, &options);
+        assert!(!reachable.contains_key(&blocked));
+        assert!(reachable.contains_key(&target));
+    }
+
+    #[test]
+    fn test_path_terrain() {
+        let mut state = setup_test_state();
+        state.map.tiles.get_mut(&(1, 0)).unwrap().terrain = TerrainType::Rough;
+        state.map.tiles.get_mut(&(2, 0)).unwrap().terrain = TerrainType::Water;
+        let path: Vec<HexCoord> = (0..4).map(|q| HexCoord::new(q, 0)).collect();
+
+        assert_eq!(
+            path_terrain(&state.map, &path),
+            vec![
+                TerrainType::Clear,
+                TerrainType::Rough,
+                TerrainType::Water,
+                TerrainType::Clear
+            ]
+        );
+        assert_eq!(
+            path_terrain(&state.map, &[HexCoord::new(0, 0), HexCoord::new(-1, 0)]),
+            vec![TerrainType::Clear, TerrainType::Impassable]
+        );
+        assert!(path_terrain(&state.map, &[]).is_empty());
+    }
+
+    #[test]
+    fn test_dense_visited_matches_hash_set() {
+        let map = GameMap::generate_random(60, 60, 31, &crate::rules::TerrainWeights::default());
+        let mut state = GameState::new(map);
+        let mut unit = Unit::new(
+            1,
+            UnitType::KriegSquad,
+            Player::Player1,
+            HexCoord::new(15, 30),
+            Facing::East,
+        );
+        unit.movement_remaining = 40;
+        state.map.tiles.get_mut(&(15, 30)).unwrap().terrain = TerrainType::Clear;
+        state.add_unit(unit.clone());
+
+        let dense = VisitedSet::for_map(&state.map);
+        assert!(matches!(dense, VisitedSet::Dense { .. }));
+
+        let with_bits = reachable_within(&state, &unit, dense, &HashSet::new());
+        let with_hash = reachable_within(
+            &state,
+            &unit,
+            VisitedSet::Sparse(HashSet::new()),
+            &HashSet::new(),
+        );
+
+        assert!(with_bits.len() > 100);
+        assert_eq!(with_bits, with_hash);
+
+        let wrapped = GameMap::new_wrapped(10, 10, Wrap::Horizontal).unwrap();
+        assert!(matches!(
+            VisitedSet::for_map(&wrapped),
+            VisitedSet::Sparse(_)
+        ));
+    }
+
+    #[test]
+    fn test_amphibious_unit_fords_river() {
+        let mut state = setup_test_state();
+        for q in 2..=4 {
+            for r in 0..10 {
+                if let Some(tile) = state.map.tiles.get_mut(&(q - r / 2, r)) {
+                    tile.terrain = TerrainType::Water;
+                }
+            }
+        }
+        let island = HexCoord::new(5, 0);
+
+        let mut normal = Unit::new(
+            2,
+            UnitType::KriegSquad,
+            Player::Player1,
+            HexCoord::new(0, 1),
+            Facing::East,
+        );
+        normal.movement_remaining = 5;
+        let mut amphibious = normal.clone();
+        amphibious.ignore_water = true;
+
+        assert!(find_path(&state, &normal, island, None).is_none());
+        assert!(!find_reachable(&state, &normal).contains_key(&island));
+
+        let (_, cost) = find_path(&state, &amphibious, island, None).unwrap();
+        assert_eq!(cost, 5);
+        assert!(find_reachable(&state, &amphibious).contains_key(&island));
+    }
+
+    #[test]
+    fn test_unit_distance_matrix() {
+        let map = GameMap::new(12, 12);
+        let mut state = GameState::new(map);
+        let spots = [
+            (0, 0, Player::Player1),
+            (6, 1, Player::Player1),
+            (2, 8, Player::Player2),
+        ];
+        for (id, (q, r, owner)) in spots.into_iter().enumerate() {
+            state.add_unit(Unit::new(
+                id as u32 + 1,
+                UnitType::Shadowsword,
+                owner,
+                HexCoord::new(q, r),
+                Facing::East,
+            ));
+        }
+
+        let matrix = unit_distance_matrix(&state);
+        assert_eq!(matrix.len(), 6);
+        for a in 1..=3 {
+            for b in 1..=3 {
+                if a == b {
+                    continue;
+                }
+                assert_eq!(matrix[&(a, b)], matrix[&(b, a)]);
+
+                let from = state.get_unit(a).unwrap();
+                let to = state.get_unit(b).unwrap();
+                assert_eq!(matrix[&(a, b)], measure(&state, from, to.position));
+            }
+        }
+        // Open ground: cost is just the hex distance
+        let distance = state.map.distance(HexCoord::new(0, 0), HexCoord::new(6, 1));
+        assert_eq!(matrix[&(1, 2)], Some(distance));
+
+        // Walling a unit in leaves it unreachable
+        for neighbor in state.map.neighbors(HexCoord::new(2, 8)) {
+            state
+                .map
+                .tiles
+                .get_mut(&(neighbor.q, neighbor.r))
+                .unwrap()
+                .terrain = TerrainType::Impassable;
+        }
+        let matrix = unit_distance_matrix(&state);
+        assert_eq!(matrix[&(1, 3)], None);
+        assert_eq!(matrix[&(3, 2)], None);
+    }
+
+    #[test]
+    fn test_connected_region_split_by_river() {
+        let mut map = GameMap::new(10, 10);
+        for r in 0..10 {
+            map.tiles.get_mut(&(5 - r / 2, r)).unwrap().terrain = TerrainType::Impassable;
+        }
+        let passable = |terrain: TerrainType| terrain.movement_cost().is_some();
+
+        let west = connected_region(&map, HexCoord::new(0, 0), passable);
+        let east = connected_region(&map, HexCoord::new(9, 0), passable);
+        assert_eq!(west.len(), 50);
+        assert_eq!(east.len(), 40);
+        assert!(west.is_disjoint(&east));
+
+        assert!(connected_region(&map, HexCoord::new(5, 0), passable).is_empty());
+        assert_eq!(
+            connected_region(&map, HexCoord::new(0, 0), |_| true).len(),
+            100
+        );
+    }
+
+    #[test]
+    fn test_reachable_paths_match_find_path() {
+        let mut state = setup_test_state();
+
+        // Through the rough hex and around it both cost 3; fewer hexes wins
+        state.map.tiles.get_mut(&(1, 0)).unwrap().terrain = TerrainType::Rough;
+        let unit = state.get_unit(1).unwrap().clone();
+        let (path, cost) = find_path(&state, &unit, HexCoord::new(2, 0), None).unwrap();
+        assert_eq!(cost, 3);
+        assert_eq!(
+            path,
+            vec![
+                HexCoord::new(0, 0),
+                HexCoord::new(1, 0),
+                HexCoord::new(2, 0)
+            ]
+        );
+
+        let map = GameMap::generate_random(16, 16, 7, &crate::rules::TerrainWeights::default());
+        let mut state = GameState::new(map);
+        let mut unit = Unit::new(
+            1,
+            UnitType::KriegSquad,
+            Player::Player1,
+            HexCoord::new(4, 8),
+            Facing::East,
+        );
+        unit.movement_remaining = 8;
+        state.map.tiles.get_mut(&(4, 8)).unwrap().terrain = TerrainType::Clear;
+        state.add_unit(unit.clone());
+
+        let paths = reachable_paths(&state, &unit);
+        assert!(paths.len() > 20);
+        for (target, stored) in &paths {
+            let (path, _) = find_path(&state, &unit, *target, None).unwrap();
+            assert_eq!(&path, stored, "path to {:?}", target);
+        }
+    }
+
+    #[test]
+    fn test_best_approach_stops_at_closest_hex() {
+        let state = setup_test_state();
+        let unit = state.get_unit(1).unwrap();
+        let target = HexCoord::new(9, 0);
+        assert_eq!(unit.effective_movement(), 5);
+        assert!(find_path(&state, unit, target, None).is_none());
+
+        let (path, cost) = best_approach(&state, unit, target).unwrap();
+        assert_eq!(path.first(), Some(&unit.position));
+        assert_eq!(path.last(), Some(&HexCoord::new(5, 0)));
+        assert_eq!(cost, 5);
+
+        // A reachable target is approached all the way
+        let (path, cost) = best_approach(&state, unit, HexCoord::new(2, 1)).unwrap();
+        assert_eq!(path.last(), Some(&HexCoord::new(2, 1)));
+        assert_eq!(
+            Some((path, cost)),
+            find_path(&state, unit, HexCoord::new(2, 1), None)
+        );
+    }
 }
@@ -0,0 +1,80 @@
+//! Deterministic random number generation
+//!
+//! A small seedable generator (Mulberry32) whose whole state is a single
+//! `u32`, so it serializes cleanly to JSON/JS and replays identically.
+
+use serde::{Deserialize, Serialize};
+
+/// Seeded pseudo-random number generator
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GameRng {
+    state: u32,
+}
+
+impl GameRng {
+    /// Create a generator from a seed
+    pub fn new(seed: u32) -> Self {
+        GameRng { state: seed }
+    }
+
+    /// Get the raw generator state
+    pub fn state(&self) -> u32 {
+        self.state
+    }
+
+    /// Generate the next 32-bit value
+    pub fn next_u32(&mut self) -> u32 {
+        self.state = self.state.wrapping_add(0x6D2B_79F5);
+        let mut t = self.state;
+        t = (t ^ (t >> 15)).wrapping_mul(t | 1);
+        t ^= t.wrapping_add((t ^ (t >> 7)).wrapping_mul(t | 61));
+        t ^ (t >> 14)
+    }
+
+    /// Generate a value in the range 0..bound (bound must be non-zero)
+    pub fn next_below(&mut self, bound: u32) -> u32 {
+        debug_assert!(bound > 0, "Bound must be non-zero");
+        ((self.next_u32() as u64 * bound as u64) >> 32) as u32
+    }
+
+    /// Roll a die with the given number of sides (1..=sides)
+    pub fn roll(&mut self, sides: u32) -> u32 {
+        self.next_below(sides) + 1
+    }
+}
+
+impl Default for GameRng {
+    fn default() -> Self {
+        GameRng::new(0x7174_6E48)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_same_seed_same_sequence() {
+        let mut a = GameRng::new(42);
+        let mut b = GameRng::new(42);
+        for _ in 0..100 {
+            assert_eq!(a.next_u32(), b.next_u32());
+        }
+        assert_eq!(a, b);
+
+        let mut c = GameRng::new(43);
+        assert_ne!(GameRng::new(42).next_u32(), c.next_u32());
+    }
+
+    #[test]
+    fn test_roll_range() {
+        let mut rng = GameRng::new(7);
+        let mut seen = [false; 6];
+        for _ in 0..600 {
+            let roll = rng.roll(6);
+            assert!((1..=6).contains(&roll));
+            seen[(roll - 1) as usize] = true;
+        }
+        assert!(seen.iter().all(|&s| s));
+    }
+}
@@ -2,9 +2,16 @@
 //!
 //! Contains the core game state, unit types, and command processing.
 
-use crate::hex::{Facing, HexCoord};
+use crate::combat::{has_line_of_sight, los_obstruction, resolve_hit, score_target, AttackOutcome};
+use crate::hex::{hex_corners, is_contiguous_path, Arc, Facing, HexCoord};
+use crate::movement::{
+    best_approach, find_path, find_reachable, is_blocked, movement_cost_for, reachable_sorted,
+    step_cost, suggest_facing, validate_path,
+};
+use crate::rng::GameRng;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
 
 /// Game phases in turn order
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -73,6 +80,16 @@ pub enum UnitType {
 }
 
 impl UnitType {
+    /// Every unit type, in declaration order
+    pub const ALL: [UnitType; 6] = [
+        UnitType::ReaverTitan,
+        UnitType::WarlordTitan,
+        UnitType::Shadowsword,
+        UnitType::Shadowsword2,
+        UnitType::Shadowsword3,
+        UnitType::KriegSquad,
+    ];
+
     /// Get the sprite atlas key for this unit type
     pub fn sprite_key(&self) -> &'static str {
         match self {
@@ -105,6 +122,20 @@ impl UnitType {
         }
     }
 
+    /// Get armor on the side of the unit a hit strikes
+    ///
+    /// Vehicles and titans are thickest at the front, three quarters as
+    /// thick on the sides and half as thick at the rear. Infantry have no
+    /// facing to speak of.
+    pub fn armor_facing(&self, arc: Arc) -> u32 {
+        let base = self.base_armor();
+        match (self, arc) {
+            (UnitType::KriegSquad, _) | (_, Arc::Front) => base,
+            (_, Arc::Side) => base * 3 / 4,
+            (_, Arc::Rear) => base / 2,
+        }
+    }
+
     /// Get base structure (health) value
     pub fn base_structure(&self) -> u32 {
         match self {
@@ -125,6 +156,165 @@ impl UnitType {
         }
     }
 
+    /// Get the structure this unit type restores with a Repair (0 if it can't)
+    pub fn repair_amount(&self) -> u32 {
+        match self {
+            UnitType::KriegSquad => 2,
+            _ => 0,
+        }
+    }
+
+    /// Get the weapons this unit type carries, main weapon first
+    pub fn weapons(&self) -> &'static [Weapon] {
+        match self {
+            UnitType::ReaverTitan => &[
+                Weapon {
+                    name: "Melta Cannon",
+                    range: 6,
+                    firepower: 10,
+                    dice: 1,
+                    arc: WeaponArc::Turret,
+                    shield_piercing: 0,
+                    inflicts: Some(StatusEffect::Burning {
+                        damage: 1,
+                        turns: 2,
+                    }),
+                },
+                Weapon {
+                    name: "Apocalypse Missile Launcher",
+                    range: 10,
+                    firepower: 6,
+                    dice: 2,
+                    arc: WeaponArc::Turret,
+                    shield_piercing: 0,
+                    inflicts: None,
+                },
+            ],
+            UnitType::WarlordTitan => &[
+                Weapon {
+                    name: "Plasma Annihilator",
+                    range: 8,
+                    firepower: 12,
+                    dice: 1,
+                    arc: WeaponArc::Turret,
+                    shield_piercing: 0,
+                    inflicts: None,
+                },
+                Weapon {
+                    name: "Gatling Blaster",
+                    range: 4,
+                    firepower: 8,
+                    dice: 3,
+                    arc: WeaponArc::Turret,
+                    shield_piercing: 0,
+                    inflicts: None,
+                },
+                Weapon {
+                    name: "Apocalypse Missile Launcher",
+                    range: 12,
+                    firepower: 6,
+                    dice: 2,
+                    arc: WeaponArc::Turret,
+                    shield_piercing: 0,
+                    inflicts: None,
+                },
+            ],
+            // The Volcano cannon is hull-mounted and built to punch through
+            // titan shields; the sponsons traverse
+            UnitType::Shadowsword | UnitType::Shadowsword2 | UnitType::Shadowsword3 => &[
+                Weapon {
+                    name: "Volcano Cannon",
+                    range: 6,
+                    firepower: 10,
+                    dice: 1,
+                    arc: WeaponArc::Front,
+                    shield_piercing: 2,
+                    inflicts: None,
+                },
+                Weapon {
+                    name: "Heavy Bolter Sponsons",
+                    range: 3,
+                    firepower: 4,
+                    dice: 2,
+                    arc: WeaponArc::Turret,
+                    shield_piercing: 0,
+                    inflicts: None,
+                },
+            ],
+            UnitType::KriegSquad => &[Weapon {
+                name: "Lasguns",
+                range: 2,
+                firepower: 2,
+                dice: 1,
+                arc: WeaponArc::Turret,
+                shield_piercing: 0,
+                inflicts: None,
+            }],
+        }
+    }
+
+    /// Get the main weapon, the one subject to cooldown
+    pub fn main_weapon(&self) -> &'static Weapon {
+        &self.weapons()[0]
+    }
+
+    /// Get main weapon range in hexes
+    pub fn weapon_range(&self) -> u32 {
+        self.main_weapon().range
+    }
+
+    /// Get the longest range of any of this unit type's weapons, in hexes
+    pub fn max_weapon_range(&self) -> u32 {
+        self.weapons().iter().map(|w| w.range).max().unwrap_or(0)
+    }
+
+    /// Get main weapon firepower (damage before armor)
+    pub fn firepower(&self) -> u32 {
+        self.main_weapon().firepower
+    }
+
+    /// Get how many void shields the main weapon passes straight through
+    pub fn shield_piercing(&self) -> u32 {
+        self.main_weapon().shield_piercing
+    }
+
+    /// Get the number of turns the main weapon needs to cool after firing
+    ///
+    /// Only titan weapons run hot. Counted in turns of either player, so a
+    /// cooldown of 3 sits out the unit's next turn and is ready again on the
+    /// one after.
+    pub fn weapon_cooldown(&self) -> u32 {
+        match self {
+            UnitType::ReaverTitan | UnitType::WarlordTitan => 3,
+            UnitType::Shadowsword | UnitType::Shadowsword2 | UnitType::Shadowsword3 => 0,
+            UnitType::KriegSquad => 0,
+        }
+    }
+
+    /// Get how the main weapon is mounted
+    pub fn weapon_arc(&self) -> WeaponArc {
+        self.main_weapon().arc
+    }
+
+    /// Get how many units this type can carry (0 if it isn't a transport)
+    pub fn transport_capacity(&self) -> u32 {
+        match self {
+            UnitType::ReaverTitan | UnitType::WarlordTitan => 0,
+            UnitType::Shadowsword | UnitType::Shadowsword2 | UnitType::Shadowsword3 => 2,
+            UnitType::KriegSquad => 0,
+        }
+    }
+
+    /// Check if this unit can ride in a transport
+    pub fn can_embark(&self) -> bool {
+        matches!(self, UnitType::KriegSquad)
+    }
+
+    /// Check if this unit leaves impassable wreckage when destroyed
+    pub fn leaves_wreckage(&self) -> bool {
+        self.is_titan()
+    }
+
     /// Check if this is a Titan
     pub fn is_titan(&self) -> bool {
         matches!(self, UnitType::ReaverTitan | UnitType::WarlordTitan)
@@ -141,6 +331,72 @@ impl UnitType {
             UnitType::KriegSquad => "Krieg Infantry Squad",
         }
     }
+
+    /// Get the movement cost multiplier this unit type pays in a terrain
+    ///
+    /// Heavier units bog down in dense terrain; a multiplier of 1.0 means
+    /// the unit pays the terrain's normal cost.
+    pub fn terrain_multiplier(&self, terrain: TerrainType) -> f32 {
+        match (self, terrain) {
+            (UnitType::WarlordTitan, TerrainType::Woods) => 2.0,
+            (UnitType::WarlordTitan, TerrainType::Rough) => 1.5,
+            (UnitType::WarlordTitan, TerrainType::Ruins) => 1.5,
+            (UnitType::ReaverTitan, TerrainType::Woods) => 1.5,
+            _ => 1.0,
+        }
+    }
+}
+
+/// How a weapon can be aimed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum WeaponArc {
+    /// Fixed weapon covering only the front arc
+    Front,
+    /// Weapon that can traverse to any direction
+    Turret,
+}
+
+/// A weapon carried by a unit type
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Weapon {
+    pub name: &'static str,
+    /// Maximum range in hexes
+    pub range: u32,
+    /// Damage before armor
+    pub firepower: u32,
+    /// Shots per attack, each rolled to hit on its own
+    pub dice: u32,
+    pub arc: WeaponArc,
+    /// Void shields the weapon passes straight through
+    pub shield_piercing: u32,
+    /// Lingering effect left on a unit the weapon hits
+    pub inflicts: Option<StatusEffect>,
+}
+
+/// A lingering condition on a unit, ticked at the start of each turn
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum StatusEffect {
+    /// Deals `damage` straight to structure, ignoring armor and shields,
+    /// for `turns` more turns
+    Burning { damage: u32, turns: u32 },
+}
+
+impl StatusEffect {
+    /// Get the number of turns the effect has left to run
+    pub fn turns(&self) -> u32 {
+        match self {
+            StatusEffect::Burning { turns, .. } => *turns,
+        }
+    }
+}
+
+/// What a unit's status effects did to it at the start of a turn
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StatusTick {
+    /// Structure lost to the effects
+    pub damage: u32,
+    /// Effects that ran out this turn
+    pub expired: Vec<StatusEffect>,
 }
 
 /// Terrain type for map hexes
@@ -153,6 +409,8 @@ pub enum TerrainType {
     Water,
     Ruins,
     Impassable,
+    /// Hulk of a destroyed titan
+    Wreckage,
 }
 
 impl TerrainType {
@@ -165,15 +423,49 @@ impl TerrainType {
             TerrainType::Water => Some(3),
             TerrainType::Ruins => Some(2),
             TerrainType::Impassable => None,
+            TerrainType::Wreckage => None,
+        }
+    }
+
+    /// Get the armor a unit standing in this terrain gains against hits
+    pub fn armor_bonus(&self) -> u32 {
+        match self {
+            TerrainType::Woods => 1,
+            TerrainType::Ruins | TerrainType::Wreckage => 2,
+            _ => 0,
+        }
+    }
+
+    /// Get the fraction of a line of fire this terrain obstructs (0.0 - 1.0)
+    pub fn cover(&self) -> f32 {
+        match self {
+            TerrainType::Clear => 0.0,
+            TerrainType::Rough => 0.0,
+            TerrainType::Woods => 0.5,
+            TerrainType::Water => 0.0,
+            TerrainType::Ruins => 0.35,
+            TerrainType::Impassable => 1.0,
+            TerrainType::Wreckage => 0.5,
+        }
+    }
+
+    /// Get how many elevation levels this terrain rises above its hex
+    pub fn height(&self) -> i32 {
+        match self {
+            TerrainType::Woods => 1,
+            _ => 0,
         }
     }
 }
 
 /// A hex tile on the game map
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Tile {
     pub terrain: TerrainType,
     pub elevation: i32,
+    /// Player allowed to deploy on this hex, if it lies in a deployment zone
+    #[serde(default)]
+    pub deployment_zone: Option<Player>,
 }
 
 impl Default for Tile {
@@ -181,16 +473,198 @@ impl Default for Tile {
         Tile {
             terrain: TerrainType::Clear,
             elevation: 0,
+            deployment_zone: None,
+        }
+    }
+}
+
+/// Relative weights for terrain types in a generated map
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct TerrainWeights {
+    pub clear: u32,
+    pub rough: u32,
+    pub woods: u32,
+    pub water: u32,
+    pub ruins: u32,
+    /// Mirror the map through its center so both sides get the same terrain
+    pub mirrored: bool,
+}
+
+impl Default for TerrainWeights {
+    fn default() -> Self {
+        TerrainWeights {
+            clear: 6,
+            rough: 2,
+            woods: 2,
+            water: 1,
+            ruins: 1,
+            mirrored: true,
+        }
+    }
+}
+
+impl TerrainWeights {
+    /// Pick a terrain type using these weights
+    fn pick(&self, rng: &mut GameRng) -> TerrainType {
+        let table = [
+            (TerrainType::Clear, self.clear),
+            (TerrainType::Rough, self.rough),
+            (TerrainType::Woods, self.woods),
+            (TerrainType::Water, self.water),
+            (TerrainType::Ruins, self.ruins),
+        ];
+        let total: u32 = table.iter().map(|(_, weight)| weight).sum();
+        if total == 0 {
+            return TerrainType::Clear;
+        }
+
+        let mut roll = rng.next_below(total);
+        for (terrain, weight) in table {
+            if roll < weight {
+                return terrain;
+            }
+            roll -= weight;
         }
+        TerrainType::Clear
     }
 }
 
+/// Edge wrapping mode for the game map
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Default)]
+pub enum Wrap {
+    /// Map edges are hard boundaries
+    #[default]
+    None,
+    /// East and west edges connect (cylinder)
+    Horizontal,
+    /// East/west and north/south edges connect (torus)
+    ///
+    /// Needs an even height so rows keep their offset parity across the seam.
+    Toroidal,
+}
+
+/// A side of the map, for scenarios that send units off the board
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum MapEdge {
+    North,
+    South,
+    East,
+    West,
+}
+
+/// Outline of the game map
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Default)]
+pub enum MapShape {
+    /// Offset rectangle of width x height hexes
+    #[default]
+    Rectangle,
+    /// Every hex within `radius` of the origin
+    Hexagon { radius: i32 },
+}
+
 /// The game map
-#[derive(Debug, Clone, Serialize, Deserialize)]
+///
+/// Serializes compactly: only tiles that differ from `Tile::default()` are
+/// written, and the rest of the rectangle is rebuilt from the width and
+/// height when loading.
+#[derive(Debug, Clone, PartialEq)]
 pub struct GameMap {
     pub width: i32,
     pub height: i32,
     pub tiles: HashMap<(i32, i32), Tile>,
+    pub wrap: Wrap,
+    pub shape: MapShape,
+}
+
+/// A non-default tile in the serialized map
+#[derive(Serialize, Deserialize)]
+struct PaintedTile<T> {
+    q: i32,
+    r: i32,
+    tile: T,
+}
+
+/// Serialized form of `GameMap`
+#[derive(Serialize, Deserialize)]
+struct CompactMap<T> {
+    width: i32,
+    height: i32,
+    #[serde(default)]
+    wrap: Wrap,
+    #[serde(default, skip_serializing_if = "is_rectangle")]
+    shape: MapShape,
+    /// Tiles that differ from the default, in reading order
+    #[serde(default)]
+    tiles: Vec<PaintedTile<T>>,
+    /// Hexes of the blank map's outline that have no tile
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    holes: Vec<(i32, i32)>,
+}
+
+fn is_rectangle(shape: &MapShape) -> bool {
+    *shape == MapShape::Rectangle
+}
+
+/// Rejects a toroidal wrap over an odd number of rows
+fn check_wrap(height: i32, wrap: Wrap) -> Result<(), String> {
+    if wrap == Wrap::Toroidal && height % 2 != 0 {
+        return Err(format!("Toroidal maps need an even height, got {}", height));
+    }
+    Ok(())
+}
+
+impl Serialize for GameMap {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let default = Tile::default();
+        let mut tiles: Vec<PaintedTile<&Tile>> = self
+            .tiles
+            .iter()
+            .filter(|(_, tile)| **tile != default)
+            .map(|(&(q, r), tile)| PaintedTile { q, r, tile })
+            .collect();
+        tiles.sort_by_key(|t| HexCoord::new(t.q, t.r));
+
+        let mut holes: Vec<(i32, i32)> = self
+            .blank()
+            .tiles
+            .into_keys()
+            .filter(|key| !self.tiles.contains_key(key))
+            .collect();
+        holes.sort_by_key(|&(q, r)| HexCoord::new(q, r));
+
+        CompactMap {
+            width: self.width,
+            height: self.height,
+            wrap: self.wrap,
+            shape: self.shape,
+            tiles,
+            holes,
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for GameMap {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let compact = CompactMap::<Tile>::deserialize(deserializer)?;
+        check_wrap(compact.height, compact.wrap).map_err(serde::de::Error::custom)?;
+
+        let mut map = GameMap {
+            width: compact.width,
+            height: compact.height,
+            tiles: HashMap::new(),
+            wrap: compact.wrap,
+            shape: compact.shape,
+        }
+        .blank();
+        for key in compact.holes {
+            map.tiles.remove(&key);
+        }
+        for painted in compact.tiles {
+            map.tiles.insert((painted.q, painted.r), painted.tile);
+        }
+        Ok(map)
+    }
 }
 
 impl GameMap {
@@ -207,7 +681,167 @@ impl GameMap {
             width,
             height,
             tiles,
+            wrap: Wrap::None,
+            shape: MapShape::Rectangle,
+        }
+    }
+
+    /// Create a new empty hexagon-shaped map centered on the origin
+    ///
+    /// Holds every hex within `radius` of (0, 0); width and height are the
+    /// hexes across the board.
+    pub fn new_hexagonal(radius: i32) -> Self {
+        let mut tiles = HashMap::new();
+        for q in -radius..=radius {
+            for r in (-radius).max(-q - radius)..=radius.min(radius - q) {
+                tiles.insert((q, r), Tile::default());
+            }
+        }
+        GameMap {
+            width: 2 * radius + 1,
+            height: 2 * radius + 1,
+            tiles,
+            wrap: Wrap::None,
+            shape: MapShape::Hexagon { radius },
+        }
+    }
+
+    /// A copy of this map's outline and wrapping with every tile reset
+    fn blank(&self) -> GameMap {
+        let mut map = match self.shape {
+            MapShape::Rectangle => GameMap::new(self.width, self.height),
+            MapShape::Hexagon { radius } => GameMap::new_hexagonal(radius),
+        };
+        map.wrap = self.wrap;
+        map
+    }
+
+    /// Create a new empty map whose edges wrap around
+    ///
+    /// Fails for a toroidal map with an odd height, whose north and south
+    /// rows would not line up across the seam.
+    pub fn new_wrapped(width: i32, height: i32, wrap: Wrap) -> Result<Self, String> {
+        check_wrap(height, wrap)?;
+        let mut map = GameMap::new(width, height);
+        map.wrap = wrap;
+        Ok(map)
+    }
+
+    /// Generate a map with random terrain and elevation from a seed
+    ///
+    /// The same seed and weights always produce the same map. When the
+    /// weights ask for mirroring, each hex copies the tile of its reflection
+    /// through the map center so neither side has a terrain advantage.
+    pub fn generate_random(width: i32, height: i32, seed: u32, terrain_weights: &TerrainWeights) -> Self {
+        let mut map = GameMap::new(width, height);
+        let mut rng = GameRng::new(seed);
+
+        // Walk rows in a fixed order so generation never depends on HashMap order
+        for row in 0..height {
+            for col in 0..width {
+                let coord = HexCoord::from_offset(col, row);
+                let mirror = map.mirror(coord);
+                let (mirror_col, mirror_row) = mirror.to_offset();
+                let mirror_done = (mirror_row, mirror_col) < (row, col);
+
+                let tile = if terrain_weights.mirrored && mirror_done {
+                    map.tiles[&(mirror.q, mirror.r)].clone()
+                } else {
+                    let terrain = terrain_weights.pick(&mut rng);
+                    let elevation = match terrain {
+                        TerrainType::Water => 0,
+                        _ => rng.next_below(3) as i32,
+                    };
+                    Tile {
+                        terrain,
+                        elevation,
+                        deployment_zone: None,
+                    }
+                };
+
+                map.tiles.insert((coord.q, coord.r), tile);
+            }
+        }
+
+        map
+    }
+
+    /// Reflect a coordinate through the center of the map
+    ///
+    /// Uses point symmetry, so a hex in one player's corner maps to the
+    /// matching hex in the opposite corner.
+    pub fn mirror(&self, coord: HexCoord) -> HexCoord {
+        if let MapShape::Hexagon { .. } = self.shape {
+            return coord * -1;
+        }
+        let (col, row) = coord.to_offset();
+        HexCoord::from_offset(self.width - 1 - col, self.height - 1 - row)
+    }
+
+    /// Map a coordinate that has stepped over a wrapped edge back onto the map
+    pub fn normalize(&self, coord: HexCoord) -> HexCoord {
+        let (col, row) = coord.to_offset();
+        match self.wrap {
+            Wrap::None => coord,
+            Wrap::Horizontal => HexCoord::from_offset(col.rem_euclid(self.width), row),
+            Wrap::Toroidal => {
+                HexCoord::from_offset(col.rem_euclid(self.width), row.rem_euclid(self.height))
+            }
+        }
+    }
+
+    /// Get all 6 neighbors of a hex, wrapped across connected edges
+    pub fn neighbors(&self, coord: HexCoord) -> [HexCoord; 6] {
+        coord.neighbors().map(|neighbor| self.normalize(neighbor))
+    }
+
+    /// Get the neighbors of a hex that exist on this map
+    pub fn valid_neighbors(&self, coord: HexCoord) -> Vec<HexCoord> {
+        self.neighbors(coord)
+            .into_iter()
+            .filter(|neighbor| self.is_valid(*neighbor))
+            .collect()
+    }
+
+    /// Check that each hex in a path is adjacent to the one before it
+    ///
+    /// Like `hex::is_contiguous_path`, but a step over a wrapped edge counts.
+    pub fn is_contiguous_path(&self, path: &[HexCoord]) -> bool {
+        match self.wrap {
+            Wrap::None => is_contiguous_path(path),
+            _ => path
+                .windows(2)
+                .all(|step| self.distance(step[0], step[1]) == 1),
+        }
+    }
+
+    /// Calculate distance between two hexes, taking the short way across wrapped edges
+    pub fn distance(&self, a: HexCoord, b: HexCoord) -> u32 {
+        a.distance_to(self.unwrap_toward(a, b))
+    }
+
+    /// Get the copy of `b` across wrapped edges that lies closest to `a`
+    ///
+    /// Lines drawn from `a` to the result take the short way over any seam;
+    /// normalize the hexes along it to get back onto the map.
+    pub fn unwrap_toward(&self, a: HexCoord, b: HexCoord) -> HexCoord {
+        let (col, row) = b.to_offset();
+        let (col_shifts, row_shifts): (&[i32], &[i32]) = match self.wrap {
+            Wrap::None => return b,
+            Wrap::Horizontal => (&[-1, 0, 1], &[0]),
+            Wrap::Toroidal => (&[-1, 0, 1], &[-1, 0, 1]),
+        };
+
+        let mut best = b;
+        for dx in col_shifts {
+            for dy in row_shifts {
+                let shifted = HexCoord::from_offset(col + dx * self.width, row + dy * self.height);
+                if a.distance_to(shifted) < a.distance_to(best) {
+                    best = shifted;
+                }
+            }
         }
+        best
     }
 
     /// Get a tile at the given coordinate
@@ -220,14 +854,79 @@ impl GameMap {
         self.tiles.contains_key(&(coord.q, coord.r))
     }
 
-    /// Get all valid hex coordinates on this map
+    /// Check whether a hex lies on the given side of the map
+    ///
+    /// A hex is on an edge when the way off the map in that direction is
+    /// open: east or west for those edges, both diagonals for north and
+    /// south. Wrapped edges have no hexes on them.
+    pub fn is_on_edge(&self, coord: HexCoord, edge: MapEdge) -> bool {
+        let outward: &[Facing] = match edge {
+            MapEdge::North => &[Facing::Northeast, Facing::Northwest],
+            MapEdge::South => &[Facing::Southwest, Facing::Southeast],
+            MapEdge::East => &[Facing::East],
+            MapEdge::West => &[Facing::West],
+        };
+        self.is_valid(coord)
+            && outward
+                .iter()
+                .all(|facing| !self.is_valid(self.normalize(coord.neighbor(*facing))))
+    }
+
+    /// Get all valid hex coordinates on this map in reading order
     pub fn all_hexes(&self) -> Vec<HexCoord> {
-        self.tiles
+        let mut hexes: Vec<HexCoord> = self
+            .tiles
             .keys()
             .map(|(q, r)| HexCoord::new(*q, *r))
+            .collect();
+        hexes.sort();
+        hexes
+    }
+
+    /// Get every hex with its elevation, in reading order
+    pub fn elevation_grid(&self) -> Vec<(HexCoord, i32)> {
+        self.all_hexes()
+            .into_iter()
+            .map(|coord| (coord, self.tiles[&(coord.q, coord.r)].elevation))
             .collect()
     }
 
+    /// Get the lowest and highest elevation on the map, or (0, 0) if it has no hexes
+    pub fn elevation_range(&self) -> (i32, i32) {
+        let elevations = self.tiles.values().map(|tile| tile.elevation);
+        match (elevations.clone().min(), elevations.max()) {
+            (Some(min), Some(max)) => (min, max),
+            _ => (0, 0),
+        }
+    }
+
+    /// Get the pixel extent of every hex corner as (min_x, min_y, max_x, max_y)
+    pub fn pixel_bounds(&self, hex_size: f64) -> (f64, f64, f64, f64) {
+        let mut bounds = (
+            f64::INFINITY,
+            f64::INFINITY,
+            f64::NEG_INFINITY,
+            f64::NEG_INFINITY,
+        );
+        for hex in self.all_hexes() {
+            let (x, y) = hex.to_pixel(hex_size);
+            for (cx, cy) in hex_corners(x, y, hex_size) {
+                bounds.0 = bounds.0.min(cx);
+                bounds.1 = bounds.1.min(cy);
+                bounds.2 = bounds.2.max(cx);
+                bounds.3 = bounds.3.max(cy);
+            }
+        }
+        bounds
+    }
+
+    /// Check whether any hex is marked as a player's deployment zone
+    pub fn has_deployment_zone(&self, player: Player) -> bool {
+        self.tiles
+            .values()
+            .any(|t| t.deployment_zone == Some(player))
+    }
+
     /// Get the terrain at a coordinate
     pub fn terrain_at(&self, coord: HexCoord) -> TerrainType {
         self.get_tile(coord)
@@ -254,6 +953,32 @@ pub struct Unit {
     pub movement_remaining: u32,
     pub has_moved: bool,
     pub has_attacked: bool,
+    /// Turns until the main weapon can fire again
+    #[serde(default)]
+    pub main_weapon_cooldown: u32,
+    /// Amphibious units ford Water as if it were Clear
+    #[serde(default)]
+    pub ignore_water: bool,
+    /// Transport this unit is riding in, if any
+    ///
+    /// Embarked units are off the board: they share the transport's
+    /// position but don't occupy the hex and can't be targeted.
+    #[serde(default)]
+    pub embarked_on: Option<u32>,
+
+    // Running combat tallies for end-of-game stats
+    /// Structure damage this unit has inflicted
+    #[serde(default)]
+    pub damage_dealt: u32,
+    /// Structure damage this unit has suffered
+    #[serde(default)]
+    pub damage_taken: u32,
+    /// Lingering effects such as burning
+    #[serde(default)]
+    pub status_effects: Vec<StatusEffect>,
+    /// Whether the unit has taken its activation this turn
+    #[serde(default)]
+    pub activated: bool,
 }
 
 impl Unit {
@@ -271,6 +996,13 @@ impl Unit {
             movement_remaining: unit_type.base_movement(),
             has_moved: false,
             has_attacked: false,
+            main_weapon_cooldown: 0,
+            ignore_water: false,
+            embarked_on: None,
+            damage_dealt: 0,
+            damage_taken: 0,
+            status_effects: Vec::new(),
+            activated: false,
         }
     }
 
@@ -279,6 +1011,51 @@ impl Unit {
         self.structure == 0
     }
 
+    /// Check if the unit is riding in a transport
+    pub fn is_embarked(&self) -> bool {
+        self.embarked_on.is_some()
+    }
+
+    /// Get the hexes the unit's model covers on screen, its own hex first
+    ///
+    /// A Warlord's model spills into the hex behind it. This is for drawing
+    /// only: for the rules every unit stands on its one hex.
+    pub fn footprint(&self) -> Vec<HexCoord> {
+        match self.unit_type {
+            UnitType::WarlordTitan => vec![
+                self.position,
+                self.position.neighbor(self.facing.opposite()),
+            ],
+            _ => vec![self.position],
+        }
+    }
+
+    /// Get the pixel corners of each footprint hex, six per hex in
+    /// `footprint` order
+    pub fn footprint_pixel_corners(&self, hex_size: f64) -> Vec<(f64, f64)> {
+        self.footprint()
+            .into_iter()
+            .flat_map(|hex| {
+                let (x, y) = hex.to_pixel(hex_size);
+                hex_corners(x, y, hex_size)
+            })
+            .collect()
+    }
+
+    /// Get the facing that points the unit at a hex
+    ///
+    /// Keeps the current facing when the target is the unit's own hex.
+    pub fn facing_toward(&self, target: HexCoord) -> Facing {
+        self.position.direction_to(target).unwrap_or(self.facing)
+    }
+
+    /// Get the unit's void shields as (current, max, next turn)
+    ///
+    /// Collapsed shields don't come back up, so next turn matches current.
+    pub fn shield_forecast(&self) -> (u32, u32, u32) {
+        (self.void_shields, self.unit_type.void_shields(), self.void_shields)
+    }
+
     /// Get the sprite key for the current facing
     pub fn sprite_frame(&self) -> String {
         format!(
@@ -288,47 +1065,594 @@ impl Unit {
         )
     }
 
-    /// Reset movement for a new turn
-    pub fn reset_for_turn(&mut self) {
+    /// Reset movement for a new turn and apply status effects
+    ///
+    /// Each effect ticks once and is dropped when its turns run out. A
+    /// destroyed unit's effects don't tick.
+    pub fn reset_for_turn(&mut self) -> StatusTick {
         self.movement_remaining = self.unit_type.base_movement();
         self.has_moved = false;
         self.has_attacked = false;
+        self.activated = false;
+        self.main_weapon_cooldown = self.main_weapon_cooldown.saturating_sub(1);
+
+        let mut tick = StatusTick::default();
+        if self.is_destroyed() {
+            return tick;
+        }
+
+        for effect in &mut self.status_effects {
+            match effect {
+                StatusEffect::Burning { damage, turns } => {
+                    let dealt = (*damage).min(self.structure);
+                    self.structure -= dealt;
+                    self.damage_taken += dealt;
+                    tick.damage += dealt;
+                    *turns = turns.saturating_sub(1);
+                }
+            }
+        }
+        self.status_effects.retain(|effect| {
+            if effect.turns() == 0 {
+                tick.expired.push(*effect);
+            }
+            effect.turns() > 0
+        });
+
+        tick
+    }
+
+    /// Get the neighboring hexes this unit's main weapon can bear on
+    pub fn targetable_neighbors(&self) -> Vec<HexCoord> {
+        match self.unit_type.weapon_arc() {
+            WeaponArc::Front => self.facing.arc_hexes(self.position).0,
+            WeaponArc::Turret => self.position.neighbors().to_vec(),
+        }
+    }
+
+    /// Check if the unit is down to half its structure or less
+    pub fn is_crippled(&self) -> bool {
+        self.structure * 2 <= self.unit_type.base_structure()
     }
 
     /// Get effective movement after damage
+    ///
+    /// A crippled unit moves at half speed, rounded up.
     pub fn effective_movement(&self) -> u32 {
-        self.movement_remaining
+        if self.is_crippled() {
+            self.movement_remaining.div_ceil(2)
+        } else {
+            self.movement_remaining
+        }
+    }
+
+    /// Spend movement points on part of a move
+    ///
+    /// A crippled unit moves at half speed, so it pays double from its
+    /// remaining movement to keep `effective_movement` dropping by `cost`.
+    pub fn spend_movement(&mut self, cost: u32) {
+        let spent = if self.is_crippled() { cost * 2 } else { cost };
+        self.movement_remaining = self.movement_remaining.saturating_sub(spent);
+    }
+
+    /// Get armor against a hit striking the given arc
+    ///
+    /// The unit's own armor is thinned by the gap between its type's front
+    /// and `armor_facing` values for that arc.
+    pub fn armor_against(&self, arc: Arc) -> u32 {
+        let thinning = self.unit_type.base_armor() - self.unit_type.armor_facing(arc);
+        self.armor.saturating_sub(thinning)
+    }
+
+    /// Get armor against hits, including cover from the hex the unit stands in
+    pub fn effective_armor(&self, map: &GameMap) -> u32 {
+        self.armor + map.terrain_at(self.position).armor_bonus()
+    }
+
+    /// Get the unit's current stats with every modifier applied
+    pub fn effective_stats(&self, state: &GameState) -> EffectiveStats {
+        let weapons = self.unit_type.weapons();
+        EffectiveStats {
+            movement: self.effective_movement(),
+            armor: self.effective_armor(&state.map),
+            void_shields: self.void_shields,
+            range: self.unit_type.max_weapon_range(),
+            arcs: weapons.iter().map(|w| w.arc).collect(),
+        }
+    }
+}
+
+/// A unit's stats after damage, terrain and other modifiers
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EffectiveStats {
+    /// Movement points available this turn
+    pub movement: u32,
+    /// Armor including cover from the unit's hex
+    pub armor: u32,
+    pub void_shields: u32,
+    /// Longest range of any weapon, in hexes
+    pub range: u32,
+    /// Firing arc of each weapon, in `UnitType::weapons` order
+    pub arcs: Vec<WeaponArc>,
+}
+
+/// How the first player of each turn is chosen
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Default)]
+pub enum InitiativeMode {
+    /// Players take turns in strict alternation
+    #[default]
+    Alternating,
+    /// A die roll on the game RNG picks who goes first each turn
+    Rolled,
+}
+
+/// A way for a scenario to be won
+///
+/// Scenarios that give the sides different goals treat Player1 as the
+/// attacker and Player2 as the defender.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum VictoryCondition {
+    /// A player wins when the other has had units destroyed and has none left
+    /// on the board
+    Annihilation,
+    /// A player wins on scoring this many victory points
+    VictoryPoints(u32),
+    /// The defender wins once this turn is over with a unit still standing
+    SurviveTurns(u32),
+    /// The attacker wins once this many of its units stand on the edge
+    ExitUnits { edge: MapEdge, count: u32 },
+}
+
+/// Per-player handicap applied to a side's units
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct StatModifier {
+    /// Scale applied to structure damage the player's attacks deal
+    pub damage_multiplier: f32,
+    /// Extra MP (or fewer, if negative) each unit gets when movement refreshes
+    pub movement_bonus: i32,
+}
+
+impl Default for StatModifier {
+    fn default() -> Self {
+        StatModifier {
+            damage_multiplier: 1.0,
+            movement_bonus: 0,
+        }
     }
 }
 
 /// Player commands
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum Command {
+    /// Place a unit on a hex in its owner's deployment zone
+    Deploy {
+        unit_id: u32,
+        position: HexCoord,
+        facing: Facing,
+    },
+    /// Lock in the active player's deployment in a blind setup
+    ConfirmDeployment,
     /// Move a unit along a path
     Move {
         unit_id: u32,
         path: Vec<HexCoord>,
         final_facing: Facing,
     },
+    /// Move a unit a single hex, turning it to face the way it went
+    ///
+    /// Lets a move be resolved hex by hex so the opponent can react between
+    /// steps. Steps can follow one another until the unit runs out of
+    /// movement, and each one must end on a hex the unit may stop on.
+    StepMove { unit_id: u32, to: HexCoord },
+    /// Resolve both players' planned Move commands at once, for simultaneous play
+    Simultaneous {
+        p1_moves: Vec<Command>,
+        p2_moves: Vec<Command>,
+    },
+    /// Load a unit into an adjacent friendly transport
+    Embark { unit_id: u32, transport_id: u32 },
+    /// Unload a unit from its transport into an adjacent hex
+    Disembark { unit_id: u32, position: HexCoord },
+    /// Attack an enemy unit with one of the attacker's weapons
+    Attack {
+        attacker_id: u32,
+        target_id: u32,
+        /// Index into the attacker's `UnitType::weapons`
+        #[serde(default)]
+        weapon_index: usize,
+    },
+    /// Shell a hex, damaging whatever units are in it
+    Bombard { attacker_id: u32, target: HexCoord },
+    /// Patch up an adjacent friendly unit, using the engineer's attack
+    Repair { engineer_id: u32, target_id: u32 },
+    /// Give a unit a standing order to move to a destination over several turns
+    SetOrder {
+        unit_id: u32,
+        destination: HexCoord,
+    },
+    /// Let a unit move and attack again this turn
+    Refresh { unit_id: u32 },
+    /// Rotate a group of units about a pivot by 60° steps, keeping its shape
+    RotateFormation {
+        unit_ids: Vec<u32>,
+        pivot: HexCoord,
+        steps: i32,
+    },
+    /// Start a unit's activation, for alternating-activation play
+    Activate { unit_id: u32 },
+    /// Finish the open activation so another unit can be activated
+    EndActivation,
+    /// Surrender, handing the game to the opponent
+    Concede { player: Player },
     /// End the current phase
     EndPhase,
     /// End the current turn
     EndTurn,
 }
 
-/// Events generated by the game
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub enum GameEvent {
-    /// Unit moved
-    UnitMoved {
-        unit_id: u32,
-        from: HexCoord,
-        to: HexCoord,
-        facing: Facing,
-    },
-    /// Phase changed
-    PhaseChanged {
-        from: Phase,
+impl Command {
+    /// Get the kind of action this command is, for phase checks
+    ///
+    /// Orders, refreshes, formation rotations, activations, concessions and
+    /// phase changes are allowed in every phase and have no kind.
+    pub fn kind(&self) -> Option<CommandKind> {
+        match self {
+            Command::Deploy { .. } | Command::ConfirmDeployment => Some(CommandKind::Deploy),
+            Command::Move { .. } | Command::StepMove { .. } | Command::Simultaneous { .. } => {
+                Some(CommandKind::Move)
+            }
+            Command::Embark { .. } | Command::Disembark { .. } => Some(CommandKind::Transport),
+            Command::Attack { .. } | Command::Bombard { .. } | Command::Repair { .. } => {
+                Some(CommandKind::Attack)
+            }
+            Command::SetOrder { .. }
+            | Command::Refresh { .. }
+            | Command::RotateFormation { .. }
+            | Command::Activate { .. }
+            | Command::EndActivation
+            | Command::Concede { .. }
+            | Command::EndPhase
+            | Command::EndTurn => None,
+        }
+    }
+
+    /// Get the unit that acts on the board in this command, if any
+    pub fn acting_unit(&self) -> Option<u32> {
+        match *self {
+            Command::Move { unit_id, .. }
+            | Command::StepMove { unit_id, .. }
+            | Command::Embark { unit_id, .. }
+            | Command::Disembark { unit_id, .. } => Some(unit_id),
+            Command::Attack { attacker_id, .. } | Command::Bombard { attacker_id, .. } => {
+                Some(attacker_id)
+            }
+            Command::Repair { engineer_id, .. } => Some(engineer_id),
+            _ => None,
+        }
+    }
+}
+
+/// Kinds of action that phase rules allow or forbid
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum CommandKind {
+    /// Placing units before the game starts
+    Deploy,
+    /// Moving units across the map
+    Move,
+    /// Boarding and leaving transports
+    Transport,
+    /// Attacking units, bombarding hexes or repairing friends
+    Attack,
+}
+
+/// Which kinds of command are legal in each phase
+///
+/// The default is the standard sequence: deploy, then move and use
+/// transports, then fight. Rule variants can open up other combinations,
+/// such as letting units fire during the movement phase.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PhaseRules {
+    pub allowed: HashMap<Phase, Vec<CommandKind>>,
+}
+
+impl Default for PhaseRules {
+    fn default() -> Self {
+        PhaseRules {
+            allowed: HashMap::from([
+                (Phase::Deployment, vec![CommandKind::Deploy]),
+                (
+                    Phase::Movement,
+                    vec![CommandKind::Move, CommandKind::Transport],
+                ),
+                (Phase::Combat, vec![CommandKind::Attack]),
+                (Phase::End, vec![]),
+            ]),
+        }
+    }
+}
+
+impl PhaseRules {
+    /// Check whether a kind of command may be issued in a phase
+    pub fn allows(&self, phase: Phase, kind: CommandKind) -> bool {
+        self.allowed
+            .get(&phase)
+            .is_some_and(|kinds| kinds.contains(&kind))
+    }
+
+    /// Allow a kind of command in a phase as well as what it already allows
+    pub fn allow(mut self, phase: Phase, kind: CommandKind) -> Self {
+        let kinds = self.allowed.entry(phase).or_default();
+        if !kinds.contains(&kind) {
+            kinds.push(kind);
+        }
+        self
+    }
+}
+
+/// Reasons a command can be rejected
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "code", content = "detail")]
+pub enum CommandError {
+    /// Game has already ended
+    GameOver,
+    /// Command is not allowed in the current phase
+    WrongPhase(Phase),
+    /// No unit with this ID exists
+    UnitNotFound(u32),
+    /// Unit belongs to the player who is not active
+    NotOwner(u32),
+    /// Acting unit has been destroyed
+    UnitDestroyed(u32),
+    /// Unit has already moved this turn
+    AlreadyMoved(u32),
+    /// Unit has already attacked this turn
+    AlreadyAttacked(u32),
+    /// Unit's main weapon is still cooling down
+    WeaponCoolingDown { unit_id: u32, turns: u32 },
+    /// Unit has no weapon at this index
+    NoSuchWeapon { unit_id: u32, weapon_index: usize },
+    /// Move path contains no hexes
+    EmptyPath,
+    /// Move path does not begin at the unit's position
+    PathStartMismatch(HexCoord),
+    /// Move path jumps to a hex that isn't adjacent to the previous one
+    NotAdjacent(HexCoord),
+    /// Move path costs more movement than the unit has left
+    InsufficientMovement { needed: u32, available: u32 },
+    /// Destination is not on the map
+    InvalidDestination(HexCoord),
+    /// Path cannot pass through this hex
+    PathBlocked(HexCoord),
+    /// Destination hex is already occupied
+    Occupied(HexCoord),
+    /// Target unit is already destroyed
+    TargetDestroyed(u32),
+    /// Target unit belongs to the attacking player
+    FriendlyTarget(u32),
+    /// Target is beyond weapon range
+    OutOfRange { distance: u32, range: u32 },
+    /// Target is outside the firing arc of a fixed weapon
+    OutOfArc(u32),
+    /// Terrain blocks the line of sight to the target
+    NoLineOfSight(u32),
+    /// Bombardment target is not on the map
+    TargetOffMap(HexCoord),
+    /// Terrain blocks the line of sight to the target hex
+    HexNotVisible(HexCoord),
+    /// Target hex is outside the firing arc of a fixed weapon
+    HexOutOfArc(HexCoord),
+    /// Hex lies outside the player's deployment zone
+    OutsideDeploymentZone(HexCoord),
+    /// Unit is riding in a transport and can't act on the board
+    UnitEmbarked(u32),
+    /// Unit is not riding in a transport
+    NotEmbarked(u32),
+    /// Unit can't carry the unit trying to board it
+    NotATransport(u32),
+    /// Transport has no room for another passenger
+    TransportFull(u32),
+    /// Player has already locked in their deployment
+    DeploymentConfirmed(Player),
+    /// Deployment is open, so there is nothing to confirm
+    DeploymentNotHidden,
+    /// Blind deployment can't end until this player confirms
+    DeploymentNotConfirmed(Player),
+    /// Unit must be the one mid-activation to act
+    NotActivated(u32),
+    /// Unit has already had its activation this turn
+    AlreadyActivated(u32),
+    /// This unit's activation must end before another starts
+    ActivationOpen(u32),
+    /// No unit is mid-activation
+    NoActivation,
+    /// Unit has no way to repair others
+    CannotRepair(u32),
+    /// Unit is already at full structure
+    FullStructure(u32),
+    /// Simultaneous resolution was given something other than a Move
+    NotAMove,
+}
+
+impl fmt::Display for CommandError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CommandError::GameOver => {
+                write!(f, "The game is over; no further commands are accepted")
+            }
+            CommandError::WrongPhase(phase) => {
+                write!(f, "Command not allowed during the {:?} phase", phase)
+            }
+            CommandError::UnitNotFound(id) => write!(f, "Unit {} not found", id),
+            CommandError::NotOwner(id) => write!(f, "Unit {} belongs to the opponent", id),
+            CommandError::UnitDestroyed(id) => write!(f, "Unit {} is destroyed", id),
+            CommandError::AlreadyMoved(id) => {
+                write!(f, "Unit {} has already moved this turn", id)
+            }
+            CommandError::AlreadyAttacked(id) => {
+                write!(f, "Unit {} has already attacked this turn", id)
+            }
+            CommandError::WeaponCoolingDown { unit_id, turns } => write!(
+                f,
+                "Unit {} must wait {} more turn(s) before firing again",
+                unit_id, turns
+            ),
+            CommandError::NoSuchWeapon {
+                unit_id,
+                weapon_index,
+            } => write!(f, "Unit {} has no weapon {}", unit_id, weapon_index),
+            CommandError::EmptyPath => write!(f, "Path is empty"),
+            CommandError::PathStartMismatch(hex) => {
+                write!(
+                    f,
+                    "Path must start at the unit's hex, not ({}, {})",
+                    hex.q, hex.r
+                )
+            }
+            CommandError::NotAdjacent(hex) => {
+                write!(f, "Path jumps to non-adjacent hex ({}, {})", hex.q, hex.r)
+            }
+            CommandError::InsufficientMovement { needed, available } => write!(
+                f,
+                "Path needs {} movement but only {} remains",
+                needed, available
+            ),
+            CommandError::InvalidDestination(hex) => {
+                write!(f, "Invalid destination ({}, {})", hex.q, hex.r)
+            }
+            CommandError::PathBlocked(hex) => write!(f, "Path blocked at ({}, {})", hex.q, hex.r),
+            CommandError::Occupied(hex) => {
+                write!(f, "Destination ({}, {}) occupied", hex.q, hex.r)
+            }
+            CommandError::TargetDestroyed(id) => write!(f, "Target {} already destroyed", id),
+            CommandError::FriendlyTarget(id) => write!(f, "Cannot attack friendly unit {}", id),
+            CommandError::OutOfRange { distance, range } => write!(
+                f,
+                "Target out of range ({} hexes, weapon range {})",
+                distance, range
+            ),
+            CommandError::OutOfArc(id) => write!(f, "Unit {} is outside the weapon's arc", id),
+            CommandError::NoLineOfSight(id) => write!(f, "No line of sight to unit {}", id),
+            CommandError::TargetOffMap(hex) => {
+                write!(f, "Target hex ({}, {}) is off the map", hex.q, hex.r)
+            }
+            CommandError::HexNotVisible(hex) => {
+                write!(f, "No line of sight to hex ({}, {})", hex.q, hex.r)
+            }
+            CommandError::HexOutOfArc(hex) => {
+                write!(f, "Hex ({}, {}) is outside the weapon's arc", hex.q, hex.r)
+            }
+            CommandError::OutsideDeploymentZone(hex) => {
+                write!(f, "({}, {}) is outside the deployment zone", hex.q, hex.r)
+            }
+            CommandError::UnitEmbarked(id) => write!(f, "Unit {} is embarked in a transport", id),
+            CommandError::NotEmbarked(id) => write!(f, "Unit {} is not embarked", id),
+            CommandError::NotATransport(id) => write!(f, "Unit {} can't carry that unit", id),
+            CommandError::TransportFull(id) => write!(f, "Transport {} is full", id),
+            CommandError::DeploymentConfirmed(player) => {
+                write!(f, "{:?} has already confirmed their deployment", player)
+            }
+            CommandError::DeploymentNotConfirmed(player) => {
+                write!(f, "{:?} has not confirmed their deployment", player)
+            }
+            CommandError::DeploymentNotHidden => {
+                write!(f, "Deployment is not hidden; there is nothing to confirm")
+            }
+            CommandError::NotActivated(id) => write!(f, "Unit {} has not been activated", id),
+            CommandError::AlreadyActivated(id) => {
+                write!(f, "Unit {} has already been activated this turn", id)
+            }
+            CommandError::ActivationOpen(id) => {
+                write!(f, "Unit {}'s activation must end first", id)
+            }
+            CommandError::NoActivation => write!(f, "No unit is being activated"),
+            CommandError::CannotRepair(id) => write!(f, "Unit {} can't make repairs", id),
+            CommandError::FullStructure(id) => {
+                write!(f, "Unit {} is already at full structure", id)
+            }
+            CommandError::NotAMove => {
+                write!(f, "Only Move commands can be resolved simultaneously")
+            }
+        }
+    }
+}
+
+impl std::error::Error for CommandError {}
+
+/// Events generated by the game
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum GameEvent {
+    /// Unit placed during deployment
+    UnitDeployed {
+        unit_id: u32,
+        position: HexCoord,
+        facing: Facing,
+    },
+    /// Scheduled reinforcement came onto the board
+    UnitArrived { unit_id: u32, position: HexCoord },
+    /// Player locked in their deployment
+    DeploymentConfirmed { player: Player },
+    /// Both players confirmed, so every deployed unit is now visible
+    DeploymentRevealed,
+    /// Unit moved
+    UnitMoved {
+        unit_id: u32,
+        from: HexCoord,
+        to: HexCoord,
+        facing: Facing,
+    },
+    /// Unit's simultaneous move collided with another and it stayed put
+    MoveBounced { unit_id: u32, destination: HexCoord },
+    /// Unit boarded a transport
+    UnitEmbarked { unit_id: u32, transport_id: u32 },
+    /// Unit left its transport
+    UnitDisembarked { unit_id: u32, position: HexCoord },
+    /// Unit attacked another unit
+    UnitAttacked {
+        attacker_id: u32,
+        target_id: u32,
+        hit: bool,
+        shields_lost: u32,
+        structure_damage: u32,
+    },
+    /// Shell landed on a hex, whether or not anything was there
+    Bombarded { attacker_id: u32, target: HexCoord },
+    /// Engineer restored structure to a friendly unit
+    UnitRepaired {
+        engineer_id: u32,
+        target_id: u32,
+        amount: u32,
+    },
+    /// Hit left a lingering effect on a unit
+    StatusApplied { unit_id: u32, effect: StatusEffect },
+    /// Unit's status effects dealt damage at the start of a turn
+    StatusDamage { unit_id: u32, damage: u32 },
+    /// Status effect on a unit ran out
+    StatusExpired { unit_id: u32, effect: StatusEffect },
+    /// Standing move order given to a unit
+    OrderSet {
+        unit_id: u32,
+        destination: HexCoord,
+    },
+    /// Standing move order dropped because the unit can get no closer
+    OrderCancelled {
+        unit_id: u32,
+        destination: HexCoord,
+    },
+    /// Unit's actions were refreshed so it can act again this turn
+    UnitRefreshed { unit_id: u32 },
+    /// Group of units turned about a pivot
+    FormationRotated {
+        unit_ids: Vec<u32>,
+        pivot: HexCoord,
+        steps: i32,
+    },
+    /// Unit's activation began
+    UnitActivated { unit_id: u32 },
+    /// Unit's activation finished
+    ActivationEnded { unit_id: u32 },
+    /// Phase changed
+    PhaseChanged {
+        from: Phase,
         to: Phase,
     },
     /// Turn changed
@@ -339,6 +1663,153 @@ pub enum GameEvent {
     UnitDestroyed {
         unit_id: u32,
     },
+    /// Terrain of a hex changed during play
+    TerrainChanged {
+        coord: HexCoord,
+        terrain: TerrainType,
+    },
+    /// Player lost their last unit
+    PlayerEliminated { player: Player },
+    /// Game ended, with no winner if it was drawn
+    GameOver { winner: Option<Player> },
+}
+
+/// A problem with a unit's starting placement
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "code", content = "detail")]
+pub enum DeploymentIssue {
+    /// Unit is placed outside the map
+    OffMap { unit_id: u32, position: HexCoord },
+    /// Unit shares its hex with an enemy or with more units than `max_stack`
+    Occupied { unit_id: u32, position: HexCoord },
+    /// Unit is outside its owner's deployment zone
+    OutOfZone { unit_id: u32, position: HexCoord },
+}
+
+/// What one player did over a turn
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlayerTurnSummary {
+    /// Moves made, counting standing-order moves
+    pub units_moved: u32,
+    /// Attacks and bombardments made
+    pub attacks_made: u32,
+    /// Void shields collapsed on enemy units
+    pub shields_stripped: u32,
+    /// Structure points removed from enemy units
+    pub damage_dealt: u32,
+    /// Enemy units destroyed
+    pub units_destroyed: u32,
+}
+
+/// Per-player totals for one turn, aggregated from the event log
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TurnSummary {
+    pub turn: u32,
+    pub player1: PlayerTurnSummary,
+    pub player2: PlayerTurnSummary,
+}
+
+impl TurnSummary {
+    /// Get one player's totals
+    pub fn for_player(&self, player: Player) -> &PlayerTurnSummary {
+        match player {
+            Player::Player1 => &self.player1,
+            Player::Player2 => &self.player2,
+        }
+    }
+
+    fn for_player_mut(&mut self, player: Player) -> &mut PlayerTurnSummary {
+        match player {
+            Player::Player1 => &mut self.player1,
+            Player::Player2 => &mut self.player2,
+        }
+    }
+}
+
+/// What the UI needs to pick up where it left off after a reload
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UiContext {
+    pub selected_unit: Option<u32>,
+    pub active_player: Player,
+    pub phase: Phase,
+    pub turn: u32,
+}
+
+/// One unit's move in a simultaneous resolution
+#[derive(Debug, Clone)]
+struct PlannedMove {
+    unit_id: u32,
+    owner: Player,
+    start: HexCoord,
+    destination: HexCoord,
+    facing: Facing,
+    path: Vec<HexCoord>,
+}
+
+impl PlannedMove {
+    /// Check whether two moves trade hexes on some step, passing through each other
+    fn crosses(&self, other: &PlannedMove) -> bool {
+        self.path.windows(2).any(|step| {
+            other
+                .path
+                .windows(2)
+                .any(|theirs| step[0] == theirs[1] && step[1] == theirs[0])
+        })
+    }
+}
+
+/// Live on-board unit ids grouped by hex, for answering many area queries
+///
+/// Built by `GameState::occupancy` and stale once any unit moves. A scan like
+/// `units_within` visits every unit per query, while a lookup here visits
+/// every hex in the area. The index wins when units are sparse next to the
+/// area (fewer hexes in range than units on the board) or when enough
+/// queries share one build to pay back its pass over the units. For a
+/// single query over a wide radius the plain scan is cheaper.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OccupancyIndex {
+    hexes: HashMap<HexCoord, Vec<u32>>,
+}
+
+impl OccupancyIndex {
+    /// Get the ids of the units on a hex
+    pub fn at(&self, hex: HexCoord) -> &[u32] {
+        self.hexes.get(&hex).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    /// Get the ids of units within a hex radius of a point, boundary included,
+    /// in ascending order
+    ///
+    /// Matches `GameState::units_within`, wrapped edges included.
+    pub fn units_in_range(&self, map: &GameMap, center: HexCoord, radius: u32) -> Vec<u32> {
+        let hexes: HashSet<HexCoord> = center
+            .hexes_in_range(radius)
+            .into_iter()
+            .map(|hex| map.normalize(hex))
+            .collect();
+        let mut ids: Vec<u32> = hexes
+            .iter()
+            .flat_map(|hex| self.at(*hex))
+            .copied()
+            .collect();
+        ids.sort_unstable();
+        ids
+    }
+
+    /// Get the ids of units inside an offset-coordinate rectangle, corners
+    /// included, in ascending order
+    ///
+    /// Takes the (col, row) corners a screen selection box covers; it does
+    /// not reach across wrapped edges.
+    pub fn units_in_rect(&self, min: (i32, i32), max: (i32, i32)) -> Vec<u32> {
+        let mut ids: Vec<u32> = (min.1..=max.1)
+            .flat_map(|row| (min.0..=max.0).map(move |col| HexCoord::from_offset(col, row)))
+            .flat_map(|hex| self.at(hex))
+            .copied()
+            .collect();
+        ids.sort_unstable();
+        ids
+    }
 }
 
 /// Complete game state
@@ -346,6 +1817,12 @@ pub enum GameEvent {
 pub struct GameState {
     pub map: GameMap,
     pub units: Vec<Unit>,
+    /// Units waiting off the board to be deployed
+    #[serde(default)]
+    pub reserves: Vec<Unit>,
+    /// Units due to arrive on their own, as (turn, unit, arrival hex)
+    #[serde(default)]
+    pub reinforcements: Vec<(u32, Unit, HexCoord)>,
     pub current_turn: u32,
     pub current_phase: Phase,
     pub active_player: Player,
@@ -353,29 +1830,198 @@ pub struct GameState {
     pub events: Vec<GameEvent>,
     pub game_over: bool,
     pub winner: Option<Player>,
+    #[serde(default)]
+    pub rng: GameRng,
+    /// Standing move orders by unit ID, advanced each movement phase
+    #[serde(default)]
+    pub orders: HashMap<u32, HexCoord>,
+    /// When set, EndPhase also skips any following phase in which the
+    /// active player has nothing legal to do
+    #[serde(default)]
+    pub auto_skip_empty_phases: bool,
+    /// Most friendly units allowed to share a hex
+    #[serde(default = "default_max_stack")]
+    pub max_stack: usize,
+    /// Which commands each phase allows
+    #[serde(default)]
+    pub phase_rules: PhaseRules,
+    /// Handicaps by player; players without an entry play unmodified
+    #[serde(default)]
+    pub stat_modifiers: HashMap<Player, StatModifier>,
+    /// When set, each player deploys unseen and both sides are revealed
+    /// once both have sent ConfirmDeployment
+    #[serde(default)]
+    pub hidden_deployment: bool,
+    /// Players who have confirmed their blind deployment
+    #[serde(default)]
+    pub deployment_confirmed: HashSet<Player>,
+    /// How the active player is chosen when a new turn starts
+    #[serde(default)]
+    pub initiative_mode: InitiativeMode,
+    /// Extra MP to enter a hex holding a friendly unit, so packed formations
+    /// are slower to move through
+    #[serde(default)]
+    pub pass_through_penalty: u32,
+    /// Whether area attacks also hit the attacker's own units
+    #[serde(default = "default_friendly_fire")]
+    pub friendly_fire: bool,
+    /// When set, only the unit mid-activation may move, fight or use transports
+    #[serde(default)]
+    pub alternating_activation: bool,
+    /// Unit whose activation is open, until EndActivation or the turn ends
+    #[serde(default)]
+    pub activation: Option<u32>,
+    /// Turns in a row without an attack before the game is drawn; 0 never draws
+    #[serde(default)]
+    pub stalemate_limit: u32,
+    /// Turn of the latest attack, or the turn before play began
+    #[serde(default)]
+    pub last_combat_turn: u32,
+    /// Ways the game can be won, checked in order as units fall and turns end
+    #[serde(default = "default_victory_conditions")]
+    pub victory_conditions: Vec<VictoryCondition>,
+    /// Every command `process_command` has accepted, in order
+    #[serde(default)]
+    pub command_log: Vec<Command>,
+    /// Snapshot taken just before the first command logged since the game
+    /// was created or loaded, for replays
+    ///
+    /// Shared between clones and left out of saves.
+    #[serde(skip)]
+    pub origin: Option<std::sync::Arc<GameState>>,
+}
+
+fn default_max_stack() -> usize {
+    1
+}
+
+fn default_friendly_fire() -> bool {
+    true
+}
+
+fn default_victory_conditions() -> Vec<VictoryCondition> {
+    vec![VictoryCondition::Annihilation]
 }
 
 impl GameState {
     /// Create a new game state with the given map
     pub fn new(map: GameMap) -> Self {
+        Self::new_at(map, 1, Phase::Deployment, Player::Player1)
+    }
+
+    /// Create a game state that starts partway through a game
+    ///
+    /// For scenarios loaded mid-game: play begins at the given turn and phase
+    /// with the given player to act.
+    pub fn new_at(map: GameMap, turn: u32, phase: Phase, active_player: Player) -> Self {
         GameState {
             map,
             units: Vec::new(),
-            current_turn: 1,
-            current_phase: Phase::Deployment,
-            active_player: Player::Player1,
+            reserves: Vec::new(),
+            reinforcements: Vec::new(),
+            current_turn: turn,
+            current_phase: phase,
+            active_player,
             selected_unit: None,
             events: Vec::new(),
             game_over: false,
             winner: None,
+            rng: GameRng::default(),
+            orders: HashMap::new(),
+            auto_skip_empty_phases: false,
+            max_stack: default_max_stack(),
+            phase_rules: PhaseRules::default(),
+            stat_modifiers: HashMap::new(),
+            hidden_deployment: false,
+            deployment_confirmed: HashSet::new(),
+            initiative_mode: InitiativeMode::default(),
+            pass_through_penalty: 0,
+            friendly_fire: default_friendly_fire(),
+            alternating_activation: false,
+            activation: None,
+            stalemate_limit: 0,
+            last_combat_turn: turn.saturating_sub(1),
+            victory_conditions: default_victory_conditions(),
+            command_log: Vec::new(),
+            origin: None,
         }
     }
 
     /// Add a unit to the game
-    pub fn add_unit(&mut self, unit: Unit) {
+    pub fn add_unit(&mut self, mut unit: Unit) {
+        let bonus = self.stat_modifier(unit.owner).movement_bonus;
+        unit.movement_remaining = unit.movement_remaining.saturating_add_signed(bonus);
         self.units.push(unit);
     }
 
+    /// Hold a unit off the board until a Deploy command brings it on
+    pub fn add_reserve(&mut self, unit: Unit) {
+        self.reserves.push(unit);
+    }
+
+    /// Schedule a unit to arrive on a hex at the start of a turn
+    pub fn add_reinforcement(&mut self, turn: u32, unit: Unit, position: HexCoord) {
+        self.reinforcements.push((turn, unit, position));
+    }
+
+    /// Bring on every reinforcement due by the current turn
+    ///
+    /// A unit whose arrival hex is impassable, full, or held by the enemy
+    /// waits and tries again at the start of the next turn.
+    fn arrive_reinforcements(&mut self) -> Vec<GameEvent> {
+        let mut events = Vec::new();
+        let mut waiting = Vec::new();
+
+        for (turn, mut unit, position) in std::mem::take(&mut self.reinforcements) {
+            let passable = self
+                .map
+                .get_tile(position)
+                .is_some_and(|t| t.terrain.movement_cost().is_some());
+            let occupants = self.units_at(position);
+            let room =
+                occupants.len() < self.max_stack && occupants.iter().all(|u| u.owner == unit.owner);
+
+            if turn > self.current_turn || !passable || !room {
+                waiting.push((turn, unit, position));
+                continue;
+            }
+
+            unit.position = position;
+            events.push(GameEvent::UnitArrived {
+                unit_id: unit.id,
+                position,
+            });
+            self.add_unit(unit);
+        }
+
+        self.reinforcements = waiting;
+        events
+    }
+
+    /// Get a player's undeployed units
+    pub fn reserves(&self, player: Player) -> Vec<&Unit> {
+        self.reserves.iter().filter(|u| u.owner == player).collect()
+    }
+
+    /// Get a player's handicap
+    pub fn stat_modifier(&self, player: Player) -> StatModifier {
+        self.stat_modifiers
+            .get(&player)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Handicap a player, adjusting the movement of units yet to move
+    pub fn set_stat_modifier(&mut self, player: Player, modifier: StatModifier) {
+        let change = modifier.movement_bonus - self.stat_modifier(player).movement_bonus;
+        for unit in &mut self.units {
+            if unit.owner == player && !unit.has_moved {
+                unit.movement_remaining = unit.movement_remaining.saturating_add_signed(change);
+            }
+        }
+        self.stat_modifiers.insert(player, modifier);
+    }
+
     /// Get a unit by ID
     pub fn get_unit(&self, id: u32) -> Option<&Unit> {
         self.units.iter().find(|u| u.id == id)
@@ -386,170 +2032,2366 @@ impl GameState {
         self.units.iter_mut().find(|u| u.id == id)
     }
 
-    /// Get the unit at a position
-    pub fn unit_at(&self, pos: HexCoord) -> Option<&Unit> {
-        self.units.iter().find(|u| u.position == pos && !u.is_destroyed())
+    /// Get a unit by ID if the active player can see it
+    pub fn visible_unit(&self, id: u32) -> Option<&Unit> {
+        self.get_unit(id).filter(|u| !self.hidden_from_active(u))
     }
 
-    /// Get units owned by a player
-    pub fn player_units(&self, player: Player) -> Vec<&Unit> {
+    /// Check whether a unit is one of the opponent's, placed during an unconfirmed blind deployment
+    fn hidden_from_active(&self, unit: &Unit) -> bool {
+        unit.owner != self.active_player && !self.deployment_revealed()
+    }
+
+    /// Get the live units on the board at a position
+    pub fn units_at(&self, pos: HexCoord) -> Vec<&Unit> {
         self.units
             .iter()
-            .filter(|u| u.owner == player && !u.is_destroyed())
+            .filter(|u| u.position == pos && !u.is_destroyed() && !u.is_embarked())
             .collect()
     }
 
-    /// Process a command
-    pub fn process_command(&mut self, command: Command) -> Result<Vec<GameEvent>, String> {
-        let mut events = Vec::new();
-
-        match command {
-            Command::Move {
-                unit_id,
-                path,
-                final_facing,
-            } => {
-                if self.current_phase != Phase::Movement {
-                    return Err("Cannot move outside of movement phase".to_string());
-                }
-
-                let unit = self
-                    .get_unit(unit_id)
-                    .ok_or("Unit not found")?;
+    /// Get the live units on the board within a hex radius of a point, boundary included
+    pub fn units_within(&self, center: HexCoord, radius: u32) -> Vec<&Unit> {
+        self.units
+            .iter()
+            .filter(|u| {
+                !u.is_destroyed()
+                    && !u.is_embarked()
+                    && self.map.distance(center, u.position) <= radius
+            })
+            .collect()
+    }
 
-                if unit.owner != self.active_player {
-                    return Err("Cannot move opponent's unit".to_string());
-                }
+    /// Index the live units on the board by hex for repeated area queries
+    pub fn occupancy(&self) -> OccupancyIndex {
+        let mut index = OccupancyIndex::default();
+        for unit in self
+            .units
+            .iter()
+            .filter(|u| !u.is_destroyed() && !u.is_embarked())
+        {
+            index.hexes.entry(unit.position).or_default().push(unit.id);
+        }
+        index
+    }
 
-                if unit.has_moved {
-                    return Err("Unit has already moved this turn".to_string());
-                }
+    /// Check whether a unit may end its move on a hex
+    ///
+    /// Units can only share a hex with friends, and only up to `max_stack`.
+    pub fn has_room(&self, pos: HexCoord, unit_id: u32) -> bool {
+        let owner = self.get_unit(unit_id).map(|u| u.owner);
+        let others: Vec<&Unit> = self
+            .units_at(pos)
+            .into_iter()
+            .filter(|u| u.id != unit_id)
+            .collect();
 
-                if path.is_empty() {
-                    return Err("Path is empty".to_string());
-                }
+        others.len() < self.max_stack && others.iter().all(|u| Some(u.owner) == owner)
+    }
 
-                let start = unit.position;
-                let end = *path.last().unwrap();
+    /// Get units owned by a player
+    pub fn player_units(&self, player: Player) -> Vec<&Unit> {
+        self.player_units_iter(player).collect()
+    }
 
-                // Validate path (simplified - just check final position is valid)
-                if !self.map.is_valid(end) {
-                    return Err("Invalid destination".to_string());
-                }
+    /// Iterate over a player's surviving units without collecting them
+    pub fn player_units_iter(&self, player: Player) -> impl Iterator<Item = &Unit> {
+        self.units
+            .iter()
+            .filter(move |u| u.owner == player && !u.is_destroyed())
+    }
 
-                if self.unit_at(end).is_some() && end != start {
-                    return Err("Destination occupied".to_string());
-                }
+    /// Check whether both sides can see each other's deployment
+    ///
+    /// Always true unless deployment is blind and a player has yet to confirm.
+    pub fn deployment_revealed(&self) -> bool {
+        !self.hidden_deployment
+            || [Player::Player1, Player::Player2]
+                .iter()
+                .all(|p| self.deployment_confirmed.contains(p))
+    }
 
-                // Apply movement
-                let unit = self.get_unit_mut(unit_id).unwrap();
-                unit.position = end;
-                unit.facing = final_facing;
-                unit.has_moved = true;
-                unit.movement_remaining = 0;
+    /// Get the units a player is allowed to see
+    ///
+    /// During a blind deployment the opponent's units stay hidden until both
+    /// players confirm.
+    pub fn visible_units(&self, viewer: Player) -> Vec<&Unit> {
+        let revealed = self.deployment_revealed();
+        self.units
+            .iter()
+            .filter(|u| revealed || u.owner == viewer)
+            .collect()
+    }
 
-                events.push(GameEvent::UnitMoved {
-                    unit_id,
-                    from: start,
-                    to: end,
-                    facing: final_facing,
-                });
-            }
+    /// Check every unit's placement, reporting all problems rather than the first
+    ///
+    /// Zone checks only apply to players that have a deployment zone marked
+    /// on the map.
+    pub fn validate_deployment(&self) -> Vec<DeploymentIssue> {
+        let mut issues = Vec::new();
+        let zoned: HashSet<Player> = [Player::Player1, Player::Player2]
+            .into_iter()
+            .filter(|p| self.map.has_deployment_zone(*p))
+            .collect();
 
-            Command::EndPhase => {
-                let old_phase = self.current_phase;
-                self.current_phase = self.current_phase.next();
+        for unit in self
+            .units
+            .iter()
+            .filter(|u| !u.is_destroyed() && !u.is_embarked())
+        {
+            let unit_id = unit.id;
+            let position = unit.position;
 
-                if self.current_phase == Phase::End {
-                    // End of turn, reset and go to next turn
-                    self.end_turn();
-                    events.push(GameEvent::TurnChanged {
-                        turn: self.current_turn,
-                    });
-                }
+            let Some(tile) = self.map.get_tile(position) else {
+                issues.push(DeploymentIssue::OffMap { unit_id, position });
+                continue;
+            };
 
-                events.push(GameEvent::PhaseChanged {
-                    from: old_phase,
-                    to: self.current_phase,
-                });
+            let sharing = self.units_at(position);
+            if sharing.len() > self.max_stack || sharing.iter().any(|u| u.owner != unit.owner) {
+                issues.push(DeploymentIssue::Occupied { unit_id, position });
             }
 
-            Command::EndTurn => {
-                let old_phase = self.current_phase;
-                self.end_turn();
-
-                events.push(GameEvent::PhaseChanged {
-                    from: old_phase,
-                    to: Phase::Movement,
-                });
-                events.push(GameEvent::TurnChanged {
-                    turn: self.current_turn,
-                });
+            if zoned.contains(&unit.owner) && tile.deployment_zone != Some(unit.owner) {
+                issues.push(DeploymentIssue::OutOfZone { unit_id, position });
             }
         }
 
-        self.events.extend(events.clone());
-        Ok(events)
+        issues
     }
 
-    /// End the current turn
-    fn end_turn(&mut self) {
-        self.current_turn += 1;
-        self.current_phase = Phase::Movement;
-        self.active_player = self.active_player.opponent();
-
-        // Reset all units
-        for unit in &mut self.units {
-            unit.reset_for_turn();
-        }
+    /// Get the IDs of a player's live units standing in their own deployment zone
+    ///
+    /// Embarked units are left out. Empty if the map marks no zone for the player.
+    pub fn units_in_zone(&self, player: Player) -> Vec<u32> {
+        self.player_units_iter(player)
+            .filter(|u| !u.is_embarked())
+            .filter(|u| {
+                self.map
+                    .get_tile(u.position)
+                    .is_some_and(|tile| tile.deployment_zone == Some(player))
+            })
+            .map(|u| u.id)
+            .collect()
     }
 
-    /// Select a unit
-    pub fn select_unit(&mut self, unit_id: Option<u32>) {
-        self.selected_unit = unit_id;
+    /// Find the nearest live enemy of a unit as (unit ID, hex distance)
+    ///
+    /// Ties are broken by the lowest unit ID.
+    pub fn nearest_enemy(&self, unit_id: u32) -> Option<(u32, u32)> {
+        let unit = self.get_unit(unit_id)?;
+        self.nearest_matching(unit, |other| other.owner != unit.owner)
     }
 
-    /// Get the selected unit
-    pub fn selected_unit(&self) -> Option<&Unit> {
-        self.selected_unit.and_then(|id| self.get_unit(id))
+    /// Find the nearest live friendly unit as (unit ID, hex distance)
+    ///
+    /// Ties are broken by the lowest unit ID.
+    pub fn nearest_ally(&self, unit_id: u32) -> Option<(u32, u32)> {
+        let unit = self.get_unit(unit_id)?;
+        self.nearest_matching(unit, |other| other.owner == unit.owner && other.id != unit.id)
     }
 
-    /// Check if a player has won
-    pub fn check_victory(&mut self) {
-        let p1_alive = self.player_units(Player::Player1).len();
-        let p2_alive = self.player_units(Player::Player2).len();
+    /// Pick the enemy a unit's main weapon can legally hit with the best
+    /// `score_target`, breaking ties by the lowest ID
+    pub fn best_target(&self, attacker_id: u32) -> Option<u32> {
+        self.units
+            .iter()
+            .filter_map(|target| {
+                let (attacker, target, _) = self.check_attack(attacker_id, target.id, 0).ok()?;
+                Some((score_target(self, attacker, target), target.id))
+            })
+            .max_by_key(|&(score, id)| (score, std::cmp::Reverse(id)))
+            .map(|(_, id)| id)
+    }
 
-        if p1_alive == 0 && p2_alive > 0 {
-            self.game_over = true;
-            self.winner = Some(Player::Player2);
-        } else if p2_alive == 0 && p1_alive > 0 {
-            self.game_over = true;
-            self.winner = Some(Player::Player1);
+    /// Check if a unit is locked in melee, adjacent to at least one live enemy
+    ///
+    /// Units riding in a transport are never engaged, and neither are
+    /// enemies riding in one.
+    pub fn is_engaged(&self, unit_id: u32) -> bool {
+        let Some(unit) = self.get_unit(unit_id) else {
+            return false;
+        };
+        if unit.is_destroyed() || unit.is_embarked() {
+            return false;
         }
+
+        self.units.iter().any(|other| {
+            other.owner != unit.owner
+                && !other.is_destroyed()
+                && !other.is_embarked()
+                && self.map.distance(unit.position, other.position) == 1
+        })
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    /// Find the closest live unit accepted by a filter
+    fn nearest_matching(&self, unit: &Unit, filter: impl Fn(&Unit) -> bool) -> Option<(u32, u32)> {
+        self.units
+            .iter()
+            .filter(|other| !other.is_destroyed() && filter(other))
+            .map(|other| (other.id, self.map.distance(unit.position, other.position)))
+            .min_by_key(|&(id, distance)| (distance, id))
+    }
 
-    #[test]
-    fn test_phase_sequence() {
-        assert_eq!(Phase::Deployment.next(), Phase::Movement);
-        assert_eq!(Phase::Movement.next(), Phase::Combat);
-        assert_eq!(Phase::Combat.next(), Phase::End);
-        assert_eq!(Phase::End.next(), Phase::Movement);
+    /// Get all events logged at or after an index
+    ///
+    /// The event log is append-only, so a polling client can pass the number
+    /// of events it has already seen to fetch only the new ones.
+    pub fn events_since(&self, index: usize) -> &[GameEvent] {
+        &self.events[index.min(self.events.len())..]
     }
 
-    #[test]
-    fn test_unit_creation() {
-        let unit = Unit::new(
-            1,
-            UnitType::ReaverTitan,
-            Player::Player1,
-            HexCoord::new(0, 0),
-            Facing::East,
-        );
+    /// Summarize what each player did during a turn
+    ///
+    /// Events carry no turn number, so the log is split at each
+    /// `TurnChanged`; everything before the first belongs to turn 1. A
+    /// bombardment counts as one attack however many units it hits, and a
+    /// destroyed unit is credited to its owner's opponent.
+    pub fn turn_summary(&self, turn: u32) -> TurnSummary {
+        let mut summary = TurnSummary {
+            turn,
+            ..TurnSummary::default()
+        };
+        let owner = |unit_id: u32| self.get_unit(unit_id).map(|u| u.owner);
+
+        let mut event_turn = 1;
+        // Attacker whose bombardment the following hits belong to
+        let mut barrage = None;
+        for event in &self.events {
+            if let GameEvent::TurnChanged { turn } = event {
+                event_turn = *turn;
+            }
+            if event_turn != turn {
+                continue;
+            }
+
+            match event {
+                GameEvent::UnitMoved { unit_id, .. } => {
+                    if let Some(player) = owner(*unit_id) {
+                        summary.for_player_mut(player).units_moved += 1;
+                    }
+                }
+                GameEvent::Bombarded { attacker_id, .. } => {
+                    if let Some(player) = owner(*attacker_id) {
+                        summary.for_player_mut(player).attacks_made += 1;
+                    }
+                    barrage = Some(*attacker_id);
+                    continue;
+                }
+                GameEvent::UnitAttacked {
+                    attacker_id,
+                    shields_lost,
+                    structure_damage,
+                    ..
+                } => {
+                    if let Some(player) = owner(*attacker_id) {
+                        let totals = summary.for_player_mut(player);
+                        if barrage != Some(*attacker_id) {
+                            totals.attacks_made += 1;
+                        }
+                        totals.shields_stripped += shields_lost;
+                        totals.damage_dealt += structure_damage;
+                    }
+                }
+                GameEvent::UnitDestroyed { unit_id } => {
+                    if let Some(player) = owner(*unit_id) {
+                        summary.for_player_mut(player.opponent()).units_destroyed += 1;
+                    }
+                }
+                _ => {}
+            }
+
+            // Hits and their aftermath continue a barrage; anything else ends it
+            if !matches!(
+                event,
+                GameEvent::UnitAttacked { .. }
+                    | GameEvent::UnitDestroyed { .. }
+                    | GameEvent::TerrainChanged { .. }
+            ) {
+                barrage = None;
+            }
+        }
+
+        summary
+    }
+
+    /// Render the event log as a readable battle report, one line per action
+    ///
+    /// Consecutive actions by the same unit share a line, so a move and the
+    /// shot that follows read "Turn 3: Reaver Titan (P1) moved (0, 0) ->
+    /// (2, -1); attacked Shadowsword (P2) for 4 damage (destroyed)."
+    pub fn battle_log_text(&self) -> String {
+        let tag = |player: Player| match player {
+            Player::Player1 => "P1",
+            Player::Player2 => "P2",
+        };
+        let name = |unit_id: u32| match self.get_unit(unit_id) {
+            Some(unit) => format!("{} ({})", unit.unit_type.display_name(), tag(unit.owner)),
+            None => format!("Unit {}", unit_id),
+        };
+        let hex = |coord: HexCoord| format!("({}, {})", coord.q, coord.r);
+
+        // (turn, acting unit, clause); clauses without an actor are whole sentences
+        let mut entries: Vec<(u32, Option<u32>, String)> = Vec::new();
+        let mut turn = 1;
+        let mut barrage = None;
+        let mut events = self.events.iter().peekable();
+        while let Some(event) = events.next() {
+            let (actor, clause) = match event {
+                GameEvent::TurnChanged { turn: next } => {
+                    turn = *next;
+                    continue;
+                }
+                GameEvent::PhaseChanged { .. } => continue,
+                GameEvent::UnitDeployed {
+                    unit_id, position, ..
+                } => (Some(*unit_id), format!("deployed at {}", hex(*position))),
+                GameEvent::UnitArrived { unit_id, position } => {
+                    (Some(*unit_id), format!("arrived at {}", hex(*position)))
+                }
+                GameEvent::DeploymentConfirmed { player } => {
+                    (None, format!("{} confirmed deployment", tag(*player)))
+                }
+                GameEvent::DeploymentRevealed => (None, "Deployments revealed".to_string()),
+                GameEvent::UnitMoved {
+                    unit_id, from, to, ..
+                } => (
+                    Some(*unit_id),
+                    format!("moved {} -> {}", hex(*from), hex(*to)),
+                ),
+                GameEvent::MoveBounced {
+                    unit_id,
+                    destination,
+                } => (
+                    Some(*unit_id),
+                    format!("was blocked short of {}", hex(*destination)),
+                ),
+                GameEvent::UnitEmbarked {
+                    unit_id,
+                    transport_id,
+                } => (Some(*unit_id), format!("boarded {}", name(*transport_id))),
+                GameEvent::UnitDisembarked { unit_id, position } => {
+                    (Some(*unit_id), format!("disembarked at {}", hex(*position)))
+                }
+                GameEvent::UnitAttacked {
+                    attacker_id,
+                    target_id,
+                    hit,
+                    shields_lost,
+                    structure_damage,
+                } => {
+                    let verb = if barrage == Some(*attacker_id) {
+                        "hit"
+                    } else {
+                        "attacked"
+                    };
+                    let mut clause = format!("{} {}", verb, name(*target_id));
+                    if !hit {
+                        clause.push_str(" and missed");
+                    } else if *shields_lost > 0 {
+                        let plural = if *shields_lost == 1 { "" } else { "s" };
+                        clause.push_str(&format!(
+                            ", collapsing {} void shield{}",
+                            shields_lost, plural
+                        ));
+                        if *structure_damage > 0 {
+                            clause.push_str(&format!(" and dealing {} damage", structure_damage));
+                        }
+                    } else {
+                        clause.push_str(&format!(" for {} damage", structure_damage));
+                    }
+                    if events
+                        .next_if(|next| {
+                            **next
+                                == GameEvent::UnitDestroyed {
+                                    unit_id: *target_id,
+                                }
+                        })
+                        .is_some()
+                    {
+                        clause.push_str(" (destroyed)");
+                    }
+                    (Some(*attacker_id), clause)
+                }
+                GameEvent::Bombarded {
+                    attacker_id,
+                    target,
+                } => {
+                    barrage = Some(*attacker_id);
+                    (Some(*attacker_id), format!("bombarded {}", hex(*target)))
+                }
+                GameEvent::UnitRepaired {
+                    engineer_id,
+                    target_id,
+                    amount,
+                } => (
+                    Some(*engineer_id),
+                    format!("repaired {} for {}", name(*target_id), amount),
+                ),
+                GameEvent::StatusApplied { unit_id, effect } => match effect {
+                    StatusEffect::Burning { .. } => {
+                        (None, format!("{} caught fire", name(*unit_id)))
+                    }
+                },
+                GameEvent::StatusDamage { unit_id, damage } => {
+                    let mut clause = format!("burned for {} damage", damage);
+                    if events
+                        .next_if(|next| **next == GameEvent::UnitDestroyed { unit_id: *unit_id })
+                        .is_some()
+                    {
+                        clause.push_str(" (destroyed)");
+                    }
+                    (Some(*unit_id), clause)
+                }
+                GameEvent::StatusExpired { unit_id, effect } => match effect {
+                    StatusEffect::Burning { .. } => (Some(*unit_id), "stopped burning".to_string()),
+                },
+                GameEvent::OrderSet {
+                    unit_id,
+                    destination,
+                } => (Some(*unit_id), format!("ordered to {}", hex(*destination))),
+                GameEvent::OrderCancelled {
+                    unit_id,
+                    destination,
+                } => (
+                    Some(*unit_id),
+                    format!("gave up its order to {}", hex(*destination)),
+                ),
+                GameEvent::UnitRefreshed { unit_id } => {
+                    (Some(*unit_id), "was readied to act again".to_string())
+                }
+                GameEvent::FormationRotated {
+                    unit_ids, pivot, ..
+                } => (
+                    None,
+                    format!("{} units wheeled about {}", unit_ids.len(), hex(*pivot)),
+                ),
+                GameEvent::UnitActivated { unit_id } => (Some(*unit_id), "activated".to_string()),
+                GameEvent::ActivationEnded { unit_id } => {
+                    (Some(*unit_id), "ended its activation".to_string())
+                }
+                GameEvent::UnitDestroyed { unit_id } => {
+                    (None, format!("{} was destroyed", name(*unit_id)))
+                }
+                GameEvent::TerrainChanged { coord, terrain } => {
+                    (None, format!("{} became {:?}", hex(*coord), terrain))
+                }
+                GameEvent::PlayerEliminated { player } => {
+                    (None, format!("{} was eliminated", tag(*player)))
+                }
+                GameEvent::GameOver {
+                    winner: Some(winner),
+                } => (None, format!("{} wins", tag(*winner))),
+                GameEvent::GameOver { winner: None } => (None, "Game drawn".to_string()),
+            };
+            if !matches!(
+                event,
+                GameEvent::Bombarded { .. }
+                    | GameEvent::UnitAttacked { .. }
+                    | GameEvent::StatusApplied { .. }
+            ) {
+                barrage = None;
+            }
+            entries.push((turn, actor, clause));
+        }
+
+        let mut lines: Vec<String> = Vec::new();
+        let mut previous = None;
+        for (turn, actor, clause) in entries {
+            match actor {
+                Some(unit_id) if previous == Some((turn, unit_id)) => {
+                    let line = lines.last_mut().unwrap();
+                    line.pop();
+                    line.push_str(&format!("; {}.", clause));
+                }
+                Some(unit_id) => {
+                    lines.push(format!("Turn {}: {} {}.", turn, name(unit_id), clause));
+                }
+                None => lines.push(format!("Turn {}: {}.", turn, clause)),
+            }
+            previous = actor.map(|unit_id| (turn, unit_id));
+        }
+
+        lines.join("\n")
+    }
+
+    /// Preview a command on a copy of the state
+    ///
+    /// The copy carries its own RNG, so lookahead sees the same rolls the
+    /// real command would make without advancing the live game.
+    pub fn simulate(&self, command: Command) -> Result<(GameState, Vec<GameEvent>), CommandError> {
+        let mut preview = self.clone();
+        let events = preview.process_command(command)?;
+        Ok((preview, events))
+    }
+
+    /// Check whether a command would be accepted, without applying it
+    ///
+    /// Returns exactly the error `process_command` would, so the UI can
+    /// grey out buttons before the player commits.
+    pub fn can_process(&self, command: &Command) -> Result<(), CommandError> {
+        if self.game_over {
+            return Err(CommandError::GameOver);
+        }
+
+        if let Some(unit_id) = command.acting_unit() {
+            if self.alternating_activation && self.activation != Some(unit_id) {
+                return Err(CommandError::NotActivated(unit_id));
+            }
+        }
+
+        match *command {
+            Command::Deploy {
+                unit_id, position, ..
+            } => self.check_deploy(unit_id, position),
+
+            Command::Simultaneous {
+                ref p1_moves,
+                ref p2_moves,
+            } => self.plan_simultaneous(p1_moves, p2_moves).map(|_| ()),
+
+            Command::ConfirmDeployment => {
+                self.check_phase(CommandKind::Deploy)?;
+
+                if !self.hidden_deployment {
+                    return Err(CommandError::DeploymentNotHidden);
+                }
+
+                if self.deployment_confirmed.contains(&self.active_player) {
+                    return Err(CommandError::DeploymentConfirmed(self.active_player));
+                }
+
+                Ok(())
+            }
+
+            Command::Move {
+                unit_id, ref path, ..
+            } => {
+                self.check_phase(CommandKind::Move)?;
+
+                let unit = self
+                    .get_unit(unit_id)
+                    .ok_or(CommandError::UnitNotFound(unit_id))?;
+
+                if unit.owner != self.active_player {
+                    return Err(CommandError::NotOwner(unit_id));
+                }
+
+                if unit.has_moved {
+                    return Err(CommandError::AlreadyMoved(unit_id));
+                }
+
+                if unit.is_embarked() {
+                    return Err(CommandError::UnitEmbarked(unit_id));
+                }
+
+                // Nothing below may walk a path with gaps in it
+                if !self.map.is_contiguous_path(path) {
+                    let gap = path
+                        .windows(2)
+                        .find(|step| self.map.distance(step[0], step[1]) != 1)
+                        .unwrap();
+                    return Err(CommandError::NotAdjacent(gap[1]));
+                }
+
+                validate_path(self, unit, path)?;
+                Ok(())
+            }
+
+            Command::StepMove { unit_id, to } => {
+                self.check_phase(CommandKind::Move)?;
+
+                let unit = self
+                    .get_unit(unit_id)
+                    .ok_or(CommandError::UnitNotFound(unit_id))?;
+
+                if unit.owner != self.active_player {
+                    return Err(CommandError::NotOwner(unit_id));
+                }
+
+                if unit.is_embarked() {
+                    return Err(CommandError::UnitEmbarked(unit_id));
+                }
+
+                validate_path(self, unit, &[unit.position, to])?;
+                Ok(())
+            }
+
+            Command::Embark {
+                unit_id,
+                transport_id,
+            } => {
+                let unit = self.ready_passenger(unit_id)?;
+
+                if unit.is_embarked() {
+                    return Err(CommandError::UnitEmbarked(unit_id));
+                }
+
+                let transport = self
+                    .get_unit(transport_id)
+                    .ok_or(CommandError::UnitNotFound(transport_id))?;
+
+                if transport.owner != unit.owner {
+                    return Err(CommandError::NotOwner(transport_id));
+                }
+
+                if transport.is_destroyed() {
+                    return Err(CommandError::UnitDestroyed(transport_id));
+                }
+
+                if !unit.unit_type.can_embark() || transport.unit_type.transport_capacity() == 0 {
+                    return Err(CommandError::NotATransport(transport_id));
+                }
+
+                if self.map.distance(unit.position, transport.position) > 1 {
+                    return Err(CommandError::NotAdjacent(transport.position));
+                }
+
+                let capacity = transport.unit_type.transport_capacity() as usize;
+                if self.passengers(transport_id).len() >= capacity {
+                    return Err(CommandError::TransportFull(transport_id));
+                }
+
+                Ok(())
+            }
+
+            Command::Disembark { unit_id, position } => {
+                let unit = self.ready_passenger(unit_id)?;
+
+                if !unit.is_embarked() {
+                    return Err(CommandError::NotEmbarked(unit_id));
+                }
+
+                if self.map.distance(unit.position, position) != 1 {
+                    return Err(CommandError::NotAdjacent(position));
+                }
+
+                if !self.map.is_valid(position)
+                    || movement_cost_for(unit, &self.map, unit.position, position).is_none()
+                {
+                    return Err(CommandError::InvalidDestination(position));
+                }
+
+                if !self.has_room(position, unit_id) {
+                    return Err(CommandError::Occupied(position));
+                }
+
+                Ok(())
+            }
+
+            Command::Attack {
+                attacker_id,
+                target_id,
+                weapon_index,
+            } => self
+                .check_attack(attacker_id, target_id, weapon_index)
+                .map(|_| ()),
+
+            Command::Bombard {
+                attacker_id,
+                target,
+            } => {
+                let (attacker, weapon) = self.ready_attacker(attacker_id, 0)?;
+
+                if !self.map.is_valid(target) {
+                    return Err(CommandError::TargetOffMap(target));
+                }
+
+                let distance = self.map.distance(attacker.position, target);
+                let range = weapon.range;
+                if distance > range {
+                    return Err(CommandError::OutOfRange { distance, range });
+                }
+
+                let toward = self.map.unwrap_toward(attacker.position, target);
+                if weapon.arc == WeaponArc::Front
+                    && !attacker.facing.is_in_front_arc(attacker.position, toward)
+                {
+                    return Err(CommandError::HexOutOfArc(target));
+                }
+
+                if !has_line_of_sight(&self.map, attacker.position, target) {
+                    return Err(CommandError::HexNotVisible(target));
+                }
+
+                Ok(())
+            }
+
+            Command::SetOrder {
+                unit_id,
+                destination,
+            } => {
+                let unit = self
+                    .get_unit(unit_id)
+                    .ok_or(CommandError::UnitNotFound(unit_id))?;
+
+                if unit.owner != self.active_player {
+                    return Err(CommandError::NotOwner(unit_id));
+                }
+
+                if unit.is_destroyed() {
+                    return Err(CommandError::UnitDestroyed(unit_id));
+                }
+
+                if !self.map.is_valid(destination) {
+                    return Err(CommandError::InvalidDestination(destination));
+                }
+
+                Ok(())
+            }
+
+            Command::Repair {
+                engineer_id,
+                target_id,
+            } => {
+                self.check_phase(CommandKind::Attack)?;
+
+                let engineer = self
+                    .get_unit(engineer_id)
+                    .ok_or(CommandError::UnitNotFound(engineer_id))?;
+                let target = self
+                    .get_unit(target_id)
+                    .ok_or(CommandError::UnitNotFound(target_id))?;
+
+                if engineer.owner != self.active_player {
+                    return Err(CommandError::NotOwner(engineer_id));
+                }
+
+                if engineer.is_destroyed() {
+                    return Err(CommandError::UnitDestroyed(engineer_id));
+                }
+
+                if engineer.is_embarked() {
+                    return Err(CommandError::UnitEmbarked(engineer_id));
+                }
+
+                if engineer.unit_type.repair_amount() == 0 {
+                    return Err(CommandError::CannotRepair(engineer_id));
+                }
+
+                if engineer.has_attacked {
+                    return Err(CommandError::AlreadyAttacked(engineer_id));
+                }
+
+                if target.owner != engineer.owner {
+                    return Err(CommandError::NotOwner(target_id));
+                }
+
+                if target.is_destroyed() {
+                    return Err(CommandError::TargetDestroyed(target_id));
+                }
+
+                if target.is_embarked() {
+                    return Err(CommandError::UnitEmbarked(target_id));
+                }
+
+                if self.map.distance(engineer.position, target.position) != 1 {
+                    return Err(CommandError::NotAdjacent(target.position));
+                }
+
+                if target.structure >= target.unit_type.base_structure() {
+                    return Err(CommandError::FullStructure(target_id));
+                }
+
+                Ok(())
+            }
+
+            Command::Refresh { unit_id } => {
+                let unit = self
+                    .get_unit(unit_id)
+                    .ok_or(CommandError::UnitNotFound(unit_id))?;
+
+                if unit.owner != self.active_player {
+                    return Err(CommandError::NotOwner(unit_id));
+                }
+
+                Ok(())
+            }
+
+            Command::RotateFormation {
+                ref unit_ids,
+                pivot,
+                steps,
+            } => self.plan_rotation(unit_ids, pivot, steps).map(|_| ()),
+
+            Command::Activate { unit_id } => {
+                let unit = self
+                    .get_unit(unit_id)
+                    .ok_or(CommandError::UnitNotFound(unit_id))?;
+
+                if unit.owner != self.active_player {
+                    return Err(CommandError::NotOwner(unit_id));
+                }
+
+                if unit.is_destroyed() {
+                    return Err(CommandError::UnitDestroyed(unit_id));
+                }
+
+                if let Some(open) = self.activation {
+                    return Err(CommandError::ActivationOpen(open));
+                }
+
+                if unit.activated {
+                    return Err(CommandError::AlreadyActivated(unit_id));
+                }
+
+                Ok(())
+            }
+
+            Command::EndActivation => match self.activation {
+                Some(_) => Ok(()),
+                None => Err(CommandError::NoActivation),
+            },
+
+            Command::EndPhase if self.current_phase == Phase::Deployment => {
+                match [self.active_player, self.active_player.opponent()]
+                    .into_iter()
+                    .find(|p| self.hidden_deployment && !self.deployment_confirmed.contains(p))
+                {
+                    Some(player) => Err(CommandError::DeploymentNotConfirmed(player)),
+                    None => Ok(()),
+                }
+            }
+
+            Command::Concede { .. } | Command::EndPhase | Command::EndTurn => Ok(()),
+        }
+    }
+
+    /// Process a command
+    ///
+    /// Every command is rejected once the game is over. Validation is left
+    /// to `can_process`; once it passes, the effects below can't fail.
+    /// Accepted commands are appended to `command_log`.
+    pub fn process_command(&mut self, command: Command) -> Result<Vec<GameEvent>, CommandError> {
+        self.can_process(&command)?;
+
+        if self.origin.is_none() {
+            self.origin = Some(std::sync::Arc::new(self.clone()));
+        }
+        self.command_log.push(command.clone());
+        if matches!(command, Command::Attack { .. } | Command::Bombard { .. }) {
+            self.last_combat_turn = self.current_turn;
+        }
+
+        let mut events = Vec::new();
+
+        match command {
+            Command::Deploy {
+                unit_id,
+                position,
+                facing,
+            } => {
+                if let Some(index) = self.reserves.iter().position(|u| u.id == unit_id) {
+                    let unit = self.reserves.remove(index);
+                    self.add_unit(unit);
+                }
+                let unit = self.get_unit_mut(unit_id).unwrap();
+                unit.position = position;
+                unit.facing = facing;
+
+                events.push(GameEvent::UnitDeployed {
+                    unit_id,
+                    position,
+                    facing,
+                });
+            }
+
+            Command::Simultaneous { p1_moves, p2_moves } => {
+                let (planned, bounced) = self.plan_simultaneous(&p1_moves, &p2_moves)?;
+                for plan in planned {
+                    let unit = self.get_unit_mut(plan.unit_id).unwrap();
+                    unit.has_moved = true;
+                    unit.movement_remaining = 0;
+
+                    if bounced.contains(&plan.unit_id) {
+                        events.push(GameEvent::MoveBounced {
+                            unit_id: plan.unit_id,
+                            destination: plan.destination,
+                        });
+                        continue;
+                    }
+
+                    unit.position = plan.destination;
+                    unit.facing = plan.facing;
+                    self.carry_passengers(plan.unit_id);
+                    events.push(GameEvent::UnitMoved {
+                        unit_id: plan.unit_id,
+                        from: plan.start,
+                        to: plan.destination,
+                        facing: plan.facing,
+                    });
+                }
+            }
+
+            Command::ConfirmDeployment => {
+                let player = self.active_player;
+                self.deployment_confirmed.insert(player);
+                events.push(GameEvent::DeploymentConfirmed { player });
+
+                // Hand the table to whoever still has to deploy
+                if self.deployment_revealed() {
+                    self.active_player = Player::Player1;
+                    events.push(GameEvent::DeploymentRevealed);
+                } else {
+                    self.active_player = player.opponent();
+                }
+            }
+
+            Command::Move {
+                unit_id,
+                path,
+                final_facing,
+            } => {
+                let start = self.get_unit(unit_id).unwrap().position;
+                let end = *path.last().unwrap();
+
+                // Apply movement; a unit moved by hand drops its standing order
+                let unit = self.get_unit_mut(unit_id).unwrap();
+                unit.position = end;
+                unit.facing = final_facing;
+                unit.has_moved = true;
+                unit.movement_remaining = 0;
+                self.carry_passengers(unit_id);
+                self.orders.remove(&unit_id);
+
+                events.push(GameEvent::UnitMoved {
+                    unit_id,
+                    from: start,
+                    to: end,
+                    facing: final_facing,
+                });
+            }
+
+            Command::StepMove { unit_id, to } => {
+                let unit = self.get_unit(unit_id).unwrap();
+                let from = unit.position;
+                let cost = step_cost(self, unit, from, to).unwrap();
+                let facing = suggest_facing(from, to);
+
+                let unit = self.get_unit_mut(unit_id).unwrap();
+                unit.position = to;
+                unit.facing = facing;
+                unit.has_moved = true;
+                unit.spend_movement(cost);
+                self.carry_passengers(unit_id);
+                self.orders.remove(&unit_id);
+
+                events.push(GameEvent::UnitMoved {
+                    unit_id,
+                    from,
+                    to,
+                    facing,
+                });
+            }
+
+            Command::Embark {
+                unit_id,
+                transport_id,
+            } => {
+                let position = self.get_unit(transport_id).unwrap().position;
+                let unit = self.get_unit_mut(unit_id).unwrap();
+                unit.embarked_on = Some(transport_id);
+                unit.position = position;
+                unit.has_moved = true;
+                unit.movement_remaining = 0;
+
+                events.push(GameEvent::UnitEmbarked {
+                    unit_id,
+                    transport_id,
+                });
+            }
+
+            Command::Disembark { unit_id, position } => {
+                let unit = self.get_unit_mut(unit_id).unwrap();
+                unit.embarked_on = None;
+                unit.position = position;
+                unit.has_moved = true;
+                unit.movement_remaining = 0;
+
+                events.push(GameEvent::UnitDisembarked { unit_id, position });
+            }
+
+            Command::Attack {
+                attacker_id,
+                target_id,
+                weapon_index,
+            } => {
+                let attacker = self.get_unit(attacker_id).unwrap();
+                let target = self.get_unit(target_id).unwrap();
+                let weapon = &attacker.unit_type.weapons()[weapon_index];
+
+                // Partial cover along the line of fire gives a chance to miss,
+                // and cover in the target's own hex adds armor
+                let obstruction = los_obstruction(&self.map, attacker.position, target.position);
+                let armor_bonus = self.map.terrain_at(target.position).armor_bonus();
+                let arc = target.facing.arc_of(target.position, attacker.position);
+                let multiplier = self.stat_modifier(attacker.owner).damage_multiplier;
+                self.spend_attack(attacker_id, weapon_index);
+
+                // Each die is a separate shot, until the target goes down
+                for _ in 0..weapon.dice {
+                    let hit = self.rng.roll(100) as f32 > obstruction * 100.0;
+                    let outcome = if hit {
+                        let target = self.get_unit_mut(target_id).unwrap();
+                        resolve_hit(weapon, target, arc, armor_bonus, multiplier)
+                    } else {
+                        AttackOutcome::default()
+                    };
+                    self.record_damage(attacker_id, target_id, outcome.structure_damage);
+
+                    events.push(GameEvent::UnitAttacked {
+                        attacker_id,
+                        target_id,
+                        hit,
+                        shields_lost: outcome.shields_lost,
+                        structure_damage: outcome.structure_damage,
+                    });
+                    if outcome.structure_damage > 0 {
+                        events.extend(self.inflict_status(target_id, weapon));
+                    }
+
+                    if outcome.destroyed {
+                        events.extend(self.destroy_unit(target_id));
+                        events.extend(self.check_victory());
+                        break;
+                    }
+                }
+            }
+
+            Command::Bombard {
+                attacker_id,
+                target,
+            } => {
+                let attacker = self.get_unit(attacker_id).unwrap();
+                let weapon = attacker.unit_type.main_weapon();
+                let origin = attacker.position;
+                let multiplier = self.stat_modifier(attacker.owner).damage_multiplier;
+                let owner = attacker.owner;
+                let victims: Vec<u32> = self
+                    .units_at(target)
+                    .iter()
+                    .filter(|u| self.friendly_fire || u.owner != owner)
+                    .map(|u| u.id)
+                    .collect();
+                self.spend_attack(attacker_id, 0);
+
+                events.push(GameEvent::Bombarded {
+                    attacker_id,
+                    target,
+                });
+
+                let mut destroyed_any = false;
+                for target_id in victims {
+                    let armor_bonus = self.map.terrain_at(target).armor_bonus();
+                    let target_unit = self.get_unit_mut(target_id).unwrap();
+                    let arc = target_unit.facing.arc_of(target, origin);
+                    let outcome = resolve_hit(weapon, target_unit, arc, armor_bonus, multiplier);
+                    self.record_damage(attacker_id, target_id, outcome.structure_damage);
+                    events.push(GameEvent::UnitAttacked {
+                        attacker_id,
+                        target_id,
+                        hit: true,
+                        shields_lost: outcome.shields_lost,
+                        structure_damage: outcome.structure_damage,
+                    });
+                    if outcome.structure_damage > 0 {
+                        events.extend(self.inflict_status(target_id, weapon));
+                    }
+                    if outcome.destroyed {
+                        events.extend(self.destroy_unit(target_id));
+                        destroyed_any = true;
+                    }
+                }
+
+                if destroyed_any {
+                    events.extend(self.check_victory());
+                }
+            }
+
+            Command::SetOrder {
+                unit_id,
+                destination,
+            } => {
+                self.orders.insert(unit_id, destination);
+                events.push(GameEvent::OrderSet {
+                    unit_id,
+                    destination,
+                });
+            }
+
+            Command::Repair {
+                engineer_id,
+                target_id,
+            } => {
+                let engineer = self.get_unit_mut(engineer_id).unwrap();
+                engineer.has_attacked = true;
+                let repair = engineer.unit_type.repair_amount();
+
+                let target = self.get_unit_mut(target_id).unwrap();
+                let amount = repair.min(target.unit_type.base_structure() - target.structure);
+                target.structure += amount;
+
+                events.push(GameEvent::UnitRepaired {
+                    engineer_id,
+                    target_id,
+                    amount,
+                });
+            }
+
+            Command::Refresh { unit_id } => {
+                let bonus = self.stat_modifier(self.active_player).movement_bonus;
+                let unit = self.get_unit_mut(unit_id).unwrap();
+                unit.movement_remaining =
+                    unit.unit_type.base_movement().saturating_add_signed(bonus);
+                unit.has_moved = false;
+                unit.has_attacked = false;
+                events.push(GameEvent::UnitRefreshed { unit_id });
+            }
+
+            Command::RotateFormation {
+                unit_ids,
+                pivot,
+                steps,
+            } => {
+                for (id, position, facing) in self.plan_rotation(&unit_ids, pivot, steps)? {
+                    let unit = self.get_unit_mut(id).unwrap();
+                    unit.position = position;
+                    unit.facing = facing;
+                    self.carry_passengers(id);
+                }
+                events.push(GameEvent::FormationRotated {
+                    unit_ids,
+                    pivot,
+                    steps,
+                });
+            }
+
+            Command::Activate { unit_id } => {
+                self.get_unit_mut(unit_id).unwrap().activated = true;
+                self.activation = Some(unit_id);
+                events.push(GameEvent::UnitActivated { unit_id });
+            }
+
+            Command::EndActivation => {
+                let unit_id = self.activation.take().unwrap();
+                events.push(GameEvent::ActivationEnded { unit_id });
+            }
+
+            Command::Concede { player } => {
+                let winner = player.opponent();
+                self.game_over = true;
+                self.winner = Some(winner);
+                events.push(GameEvent::GameOver {
+                    winner: Some(winner),
+                });
+            }
+
+            Command::EndPhase => {
+                events.extend(self.advance_phase());
+
+                // Never cascade into the next player's turn
+                while self.auto_skip_empty_phases
+                    && self.current_phase != Phase::Movement
+                    && !self.has_legal_actions()
+                {
+                    events.extend(self.advance_phase());
+                }
+            }
+
+            Command::EndTurn => {
+                let old_phase = self.current_phase;
+                let status_events = self.end_turn();
+
+                events.push(GameEvent::PhaseChanged {
+                    from: old_phase,
+                    to: Phase::Movement,
+                });
+                events.push(GameEvent::TurnChanged {
+                    turn: self.current_turn,
+                });
+                events.extend(status_events);
+                events.extend(self.advance_orders());
+            }
+        }
+
+        self.events.extend(events.clone());
+        Ok(events)
+    }
+
+    /// Rebuild the game as it stood once the given turn was played out
+    ///
+    /// Replays the commands logged after `origin` was taken onto it,
+    /// stopping at the first command issued after that turn, so a finished
+    /// turn includes the command that closed it. The seeded RNG makes the
+    /// replay exact. Saves leave the snapshot out, so a loaded game can only
+    /// be rebuilt back to where it was loaded.
+    pub fn state_at_turn(&self, turn: u32) -> Result<GameState, String> {
+        if turn == 0 || turn > self.current_turn {
+            return Err(format!(
+                "Turn {} is outside the game so far (1-{})",
+                turn, self.current_turn
+            ));
+        }
+
+        let Some(origin) = &self.origin else {
+            return Ok(self.clone());
+        };
+        if turn < origin.current_turn {
+            return Err(format!(
+                "Turn {} was played before this replay begins (turn {})",
+                turn, origin.current_turn
+            ));
+        }
+
+        let mut state = (**origin).clone();
+        let start = origin.command_log.len();
+        for (index, command) in self.command_log.iter().enumerate().skip(start) {
+            if state.current_turn > turn {
+                break;
+            }
+            state
+                .process_command(command.clone())
+                .map_err(|e| format!("Replay failed at command {}: {}", index, e))?;
+        }
+        Ok(state)
+    }
+
+    /// Resolve both players' planned moves at once, for simultaneous play
+    ///
+    /// Processes them as one `Command::Simultaneous`, so the resolution is
+    /// logged and replayed like any other command.
+    pub fn resolve_simultaneous(
+        &mut self,
+        p1_moves: &[Command],
+        p2_moves: &[Command],
+    ) -> Result<Vec<GameEvent>, String> {
+        self.process_command(Command::Simultaneous {
+            p1_moves: p1_moves.to_vec(),
+            p2_moves: p2_moves.to_vec(),
+        })
+        .map_err(|e| e.to_string())
+    }
+
+    /// Validate both players' planned moves and work out which of them bounce
+    ///
+    /// Every move is validated against the board as it stands before any
+    /// unit moves. Units whose destinations collide bounce: when a hex is
+    /// claimed by both players, or by more units than it has room for, every
+    /// unit heading there stays where it is and still spends its move. Enemy
+    /// units whose paths trade hexes on the way would pass through each
+    /// other, so both of them bounce too. Bounces can cascade, since a
+    /// bounced unit keeps its hex.
+    fn plan_simultaneous(
+        &self,
+        p1_moves: &[Command],
+        p2_moves: &[Command],
+    ) -> Result<(Vec<PlannedMove>, HashSet<u32>), CommandError> {
+        self.check_phase(CommandKind::Move)?;
+
+        let mut planned: Vec<PlannedMove> = Vec::new();
+        for (player, moves) in [(Player::Player1, p1_moves), (Player::Player2, p2_moves)] {
+            for command in moves {
+                let Command::Move {
+                    unit_id,
+                    path,
+                    final_facing,
+                } = command
+                else {
+                    return Err(CommandError::NotAMove);
+                };
+                let unit_id = *unit_id;
+
+                let unit = self
+                    .get_unit(unit_id)
+                    .ok_or(CommandError::UnitNotFound(unit_id))?;
+                if unit.owner != player {
+                    return Err(CommandError::NotOwner(unit_id));
+                }
+                if unit.has_moved || planned.iter().any(|m| m.unit_id == unit_id) {
+                    return Err(CommandError::AlreadyMoved(unit_id));
+                }
+                if unit.is_embarked() {
+                    return Err(CommandError::UnitEmbarked(unit_id));
+                }
+                validate_path(self, unit, path)?;
+
+                planned.push(PlannedMove {
+                    unit_id,
+                    owner: player,
+                    start: unit.position,
+                    destination: *path.last().unwrap(),
+                    facing: *final_facing,
+                    path: path.clone(),
+                });
+            }
+        }
+
+        let mut bounced: HashSet<u32> = HashSet::new();
+        loop {
+            let mut changed = false;
+            for plan in &planned {
+                if bounced.contains(&plan.unit_id) {
+                    continue;
+                }
+                let destination = plan.destination;
+
+                // Everyone who would end the phase on this hex: the units
+                // still heading there plus any that aren't leaving it
+                let arriving = planned
+                    .iter()
+                    .filter(|m| m.destination == destination && !bounced.contains(&m.unit_id))
+                    .map(|m| m.owner);
+                let staying = self
+                    .units_at(destination)
+                    .into_iter()
+                    .filter(|u| {
+                        planned
+                            .iter()
+                            .find(|m| m.unit_id == u.id)
+                            .is_none_or(|m| bounced.contains(&m.unit_id))
+                    })
+                    .map(|u| u.owner);
+                let owners: Vec<Player> = arriving.chain(staying).collect();
+
+                if owners.len() > self.max_stack || owners.iter().any(|p| *p != owners[0]) {
+                    for m in &planned {
+                        if m.destination == destination {
+                            bounced.insert(m.unit_id);
+                        }
+                    }
+                    changed = true;
+                }
+
+                let swap = planned.iter().find(|m| {
+                    m.owner != plan.owner && !bounced.contains(&m.unit_id) && plan.crosses(m)
+                });
+                if let Some(other) = swap {
+                    bounced.insert(plan.unit_id);
+                    bounced.insert(other.unit_id);
+                    changed = true;
+                }
+            }
+            if !changed {
+                break;
+            }
+        }
+
+        Ok((planned, bounced))
+    }
+
+    /// Check that a unit can be deployed to a hex
+    fn check_deploy(&self, unit_id: u32, position: HexCoord) -> Result<(), CommandError> {
+        let zoned = self.map.has_deployment_zone(self.active_player);
+        self.check_deploy_in(unit_id, position, zoned)
+    }
+
+    /// Check that a unit can be deployed to a hex, given whether the active
+    /// player has a deployment zone
+    ///
+    /// Finding the zone scans the whole map, so callers checking many hexes
+    /// look it up once.
+    fn check_deploy_in(
+        &self,
+        unit_id: u32,
+        position: HexCoord,
+        zoned: bool,
+    ) -> Result<(), CommandError> {
+        self.check_phase(CommandKind::Deploy)?;
+
+        let reserve = self.reserves.iter().find(|u| u.id == unit_id);
+        let unit = self
+            .get_unit(unit_id)
+            .or(reserve)
+            .ok_or(CommandError::UnitNotFound(unit_id))?;
+
+        if unit.owner != self.active_player {
+            return Err(CommandError::NotOwner(unit_id));
+        }
+
+        if unit.is_destroyed() {
+            return Err(CommandError::UnitDestroyed(unit_id));
+        }
+
+        if unit.is_embarked() {
+            return Err(CommandError::UnitEmbarked(unit_id));
+        }
+
+        let Some(tile) = self.map.get_tile(position) else {
+            return Err(CommandError::InvalidDestination(position));
+        };
+
+        if tile.terrain.movement_cost().is_none() {
+            return Err(CommandError::InvalidDestination(position));
+        }
+
+        if zoned && tile.deployment_zone != Some(unit.owner) {
+            return Err(CommandError::OutsideDeploymentZone(position));
+        }
+
+        // A unit coming out of reserve has no hex of its own yet
+        let moving = reserve.is_some() || position != unit.position;
+        if moving && !self.has_room(position, unit_id) {
+            return Err(CommandError::Occupied(position));
+        }
+
+        Ok(())
+    }
+
+    /// Check that an attacker can fire a weapon on a target, returning both units and the weapon
+    fn check_attack(
+        &self,
+        attacker_id: u32,
+        target_id: u32,
+        weapon_index: usize,
+    ) -> Result<(&Unit, &Unit, &'static Weapon), CommandError> {
+        let (attacker, weapon) = self.ready_attacker(attacker_id, weapon_index)?;
+
+        let target = self
+            .get_unit(target_id)
+            .ok_or(CommandError::UnitNotFound(target_id))?;
+
+        if target.is_destroyed() {
+            return Err(CommandError::TargetDestroyed(target_id));
+        }
+
+        if target.owner == attacker.owner {
+            return Err(CommandError::FriendlyTarget(target_id));
+        }
+
+        if target.is_embarked() {
+            return Err(CommandError::UnitEmbarked(target_id));
+        }
+
+        let distance = self.map.distance(attacker.position, target.position);
+        let range = weapon.range;
+        if distance > range {
+            return Err(CommandError::OutOfRange { distance, range });
+        }
+
+        let toward = self.map.unwrap_toward(attacker.position, target.position);
+        if weapon.arc == WeaponArc::Front
+            && !attacker.facing.is_in_front_arc(attacker.position, toward)
+        {
+            return Err(CommandError::OutOfArc(target_id));
+        }
+
+        if !has_line_of_sight(&self.map, attacker.position, target.position) {
+            return Err(CommandError::NoLineOfSight(target_id));
+        }
+
+        Ok((attacker, target, weapon))
+    }
+
+    /// List a representative set of commands the active player can issue now
+    ///
+    /// Covers deploying to each free zone hex, boarding and leaving
+    /// transports, moving to each reachable hex along its cheapest path, and
+    /// every legal attack and repair, followed by `EndPhase`. Under alternating
+    /// activation only the open activation's unit acts, and the activations
+    /// that can start or end are listed too. Empty once the game is over.
+    pub fn legal_commands(&self) -> Vec<Command> {
+        let mut commands = Vec::new();
+        if self.game_over {
+            return commands;
+        }
+
+        let units = self.player_units(self.active_player);
+        let allows = |kind| self.phase_rules.allows(self.current_phase, kind);
+
+        if allows(CommandKind::Deploy) {
+            let hexes = self.map.all_hexes();
+            let zoned = self.map.has_deployment_zone(self.active_player);
+            for unit in &units {
+                for &position in &hexes {
+                    if position != unit.position
+                        && self.check_deploy_in(unit.id, position, zoned).is_ok()
+                    {
+                        commands.push(Command::Deploy {
+                            unit_id: unit.id,
+                            position,
+                            facing: unit.facing,
+                        });
+                    }
+                }
+            }
+            for unit in self.reserves(self.active_player) {
+                for &position in &hexes {
+                    if self.check_deploy_in(unit.id, position, zoned).is_ok() {
+                        commands.push(Command::Deploy {
+                            unit_id: unit.id,
+                            position,
+                            facing: unit.facing,
+                        });
+                    }
+                }
+            }
+        }
+
+        for unit in units.iter().filter(|u| !u.has_moved) {
+            if allows(CommandKind::Transport) {
+                let candidates: Vec<Command> = if unit.is_embarked() {
+                    self.map
+                        .neighbors(unit.position)
+                        .into_iter()
+                        .map(|position| Command::Disembark {
+                            unit_id: unit.id,
+                            position,
+                        })
+                        .collect()
+                } else {
+                    units
+                        .iter()
+                        .filter(|t| t.id != unit.id)
+                        .map(|t| Command::Embark {
+                            unit_id: unit.id,
+                            transport_id: t.id,
+                        })
+                        .collect()
+                };
+                commands.extend(
+                    candidates
+                        .into_iter()
+                        .filter(|command| self.simulate(command.clone()).is_ok()),
+                );
+            }
+
+            if !allows(CommandKind::Move) || unit.is_embarked() {
+                continue;
+            }
+            for (destination, _) in reachable_sorted(self, unit) {
+                if destination == unit.position {
+                    continue;
+                }
+                if let Some((path, _)) = find_path(self, unit, destination, None) {
+                    let final_facing = suggest_facing(path[path.len() - 2], destination);
+                    commands.push(Command::Move {
+                        unit_id: unit.id,
+                        path,
+                        final_facing,
+                    });
+                }
+            }
+        }
+
+        if allows(CommandKind::Attack) {
+            for unit in &units {
+                for target in &self.units {
+                    for weapon_index in 0..unit.unit_type.weapons().len() {
+                        if self.check_attack(unit.id, target.id, weapon_index).is_ok() {
+                            commands.push(Command::Attack {
+                                attacker_id: unit.id,
+                                target_id: target.id,
+                                weapon_index,
+                            });
+                        }
+                    }
+
+                    let repair = Command::Repair {
+                        engineer_id: unit.id,
+                        target_id: target.id,
+                    };
+                    if unit.unit_type.repair_amount() > 0 && self.can_process(&repair).is_ok() {
+                        commands.push(repair);
+                    }
+                }
+            }
+        }
+
+        if allows(CommandKind::Deploy)
+            && self.hidden_deployment
+            && !self.deployment_confirmed.contains(&self.active_player)
+        {
+            commands.push(Command::ConfirmDeployment);
+        }
+
+        if self.alternating_activation {
+            commands.retain(|c| c.acting_unit().is_none_or(|id| self.activation == Some(id)));
+            for unit in &units {
+                let activate = Command::Activate { unit_id: unit.id };
+                if self.can_process(&activate).is_ok() {
+                    commands.push(activate);
+                }
+            }
+            if self.activation.is_some() {
+                commands.push(Command::EndActivation);
+            }
+        }
+
+        if self.can_process(&Command::EndPhase).is_ok() {
+            commands.push(Command::EndPhase);
+        }
+        commands
+    }
+
+    /// Check that the phase rules allow a kind of command in the current phase
+    fn check_phase(&self, kind: CommandKind) -> Result<(), CommandError> {
+        if self.phase_rules.allows(self.current_phase, kind) {
+            Ok(())
+        } else {
+            Err(CommandError::WrongPhase(self.current_phase))
+        }
+    }
+
+    /// Look up a unit that is able to board or leave a transport this phase
+    fn ready_passenger(&self, unit_id: u32) -> Result<&Unit, CommandError> {
+        self.check_phase(CommandKind::Transport)?;
+
+        let unit = self
+            .get_unit(unit_id)
+            .ok_or(CommandError::UnitNotFound(unit_id))?;
+
+        if unit.owner != self.active_player {
+            return Err(CommandError::NotOwner(unit_id));
+        }
+
+        if unit.is_destroyed() {
+            return Err(CommandError::UnitDestroyed(unit_id));
+        }
+
+        if unit.has_moved {
+            return Err(CommandError::AlreadyMoved(unit_id));
+        }
+
+        Ok(unit)
+    }
+
+    /// Get the live units riding in a transport
+    pub fn passengers(&self, transport_id: u32) -> Vec<&Unit> {
+        self.units
+            .iter()
+            .filter(|u| u.embarked_on == Some(transport_id) && !u.is_destroyed())
+            .collect()
+    }
+
+    /// Move a transport's passengers to wherever it now stands
+    fn carry_passengers(&mut self, transport_id: u32) {
+        let Some(position) = self.get_unit(transport_id).map(|t| t.position) else {
+            return;
+        };
+        for unit in &mut self.units {
+            if unit.embarked_on == Some(transport_id) {
+                unit.position = position;
+            }
+        }
+    }
+
+    /// Look up a unit and weapon that are able to fire this phase
+    ///
+    /// Only the main weapon (index 0) is held back by cooldown.
+    fn ready_attacker(
+        &self,
+        attacker_id: u32,
+        weapon_index: usize,
+    ) -> Result<(&Unit, &'static Weapon), CommandError> {
+        self.check_phase(CommandKind::Attack)?;
+
+        let attacker = self
+            .get_unit(attacker_id)
+            .ok_or(CommandError::UnitNotFound(attacker_id))?;
+
+        if attacker.owner != self.active_player {
+            return Err(CommandError::NotOwner(attacker_id));
+        }
+
+        if attacker.is_destroyed() {
+            return Err(CommandError::UnitDestroyed(attacker_id));
+        }
+
+        if attacker.is_embarked() {
+            return Err(CommandError::UnitEmbarked(attacker_id));
+        }
+
+        if attacker.has_attacked {
+            return Err(CommandError::AlreadyAttacked(attacker_id));
+        }
+
+        let weapon =
+            attacker
+                .unit_type
+                .weapons()
+                .get(weapon_index)
+                .ok_or(CommandError::NoSuchWeapon {
+                    unit_id: attacker_id,
+                    weapon_index,
+                })?;
+
+        if weapon_index == 0 && attacker.main_weapon_cooldown > 0 {
+            return Err(CommandError::WeaponCoolingDown {
+                unit_id: attacker_id,
+                turns: attacker.main_weapon_cooldown,
+            });
+        }
+
+        Ok((attacker, weapon))
+    }
+
+    /// Record a unit's destruction along with any passengers, leaving
+    /// wreckage on its hex if it is a titan
+    fn destroy_unit(&mut self, unit_id: u32) -> Vec<GameEvent> {
+        let mut events = vec![GameEvent::UnitDestroyed { unit_id }];
+
+        // Passengers go down with their transport
+        let passengers: Vec<u32> = self.passengers(unit_id).iter().map(|u| u.id).collect();
+        for passenger_id in passengers {
+            if let Some(passenger) = self.get_unit_mut(passenger_id) {
+                passenger.structure = 0;
+                events.push(GameEvent::UnitDestroyed {
+                    unit_id: passenger_id,
+                });
+            }
+        }
+
+        let Some(unit) = self.get_unit(unit_id) else {
+            return events;
+        };
+        let coord = unit.position;
+        if unit.unit_type.leaves_wreckage() {
+            if let Some(tile) = self.map.tiles.get_mut(&(coord.q, coord.r)) {
+                tile.terrain = TerrainType::Wreckage;
+                events.push(GameEvent::TerrainChanged {
+                    coord,
+                    terrain: TerrainType::Wreckage,
+                });
+            }
+        }
+
+        events
+    }
+
+    /// Leave a weapon's lingering effect on a unit it damaged, if it survived
+    fn inflict_status(&mut self, unit_id: u32, weapon: &Weapon) -> Option<GameEvent> {
+        let effect = weapon.inflicts?;
+        let unit = self.get_unit_mut(unit_id).filter(|u| !u.is_destroyed())?;
+        unit.status_effects.push(effect);
+        Some(GameEvent::StatusApplied { unit_id, effect })
+    }
+
+    /// Mark a unit as having fired, starting its weapon cooldown
+    fn spend_attack(&mut self, attacker_id: u32, weapon_index: usize) {
+        if let Some(attacker) = self.get_unit_mut(attacker_id) {
+            attacker.has_attacked = true;
+            if weapon_index == 0 {
+                attacker.main_weapon_cooldown = attacker.unit_type.weapon_cooldown();
+            }
+        }
+    }
+
+    /// Add structure damage to both units' combat tallies
+    fn record_damage(&mut self, attacker_id: u32, target_id: u32, damage: u32) {
+        if let Some(attacker) = self.get_unit_mut(attacker_id) {
+            attacker.damage_dealt += damage;
+        }
+        if let Some(target) = self.get_unit_mut(target_id) {
+            target.damage_taken += damage;
+        }
+    }
+
+    /// Move to the next phase, rolling over to a new turn after combat
+    fn advance_phase(&mut self) -> Vec<GameEvent> {
+        let mut events = Vec::new();
+        let old_phase = self.current_phase;
+        self.current_phase = self.current_phase.next();
+
+        if self.current_phase == Phase::End {
+            // End of turn, reset and go to next turn
+            let status_events = self.end_turn();
+            events.push(GameEvent::TurnChanged {
+                turn: self.current_turn,
+            });
+            events.extend(status_events);
+        }
+
+        events.push(GameEvent::PhaseChanged {
+            from: old_phase,
+            to: self.current_phase,
+        });
+
+        if self.current_phase == Phase::Movement {
+            events.extend(self.advance_orders());
+        }
+
+        events
+    }
+
+    /// Check whether the active player can still act in the current phase
+    pub fn has_legal_actions(&self) -> bool {
+        if matches!(self.current_phase, Phase::Deployment | Phase::End) {
+            return true;
+        }
+
+        let allows = |kind| self.phase_rules.allows(self.current_phase, kind);
+        self.player_units_iter(self.active_player).any(|u| {
+            (allows(CommandKind::Move) && !u.has_moved && u.movement_remaining > 0)
+                || (allows(CommandKind::Attack)
+                    && !u.is_embarked()
+                    && !u.has_attacked
+                    && self.has_target(u))
+        })
+    }
+
+    /// Check whether any live enemy is in range and sight of a unit
+    fn has_target(&self, unit: &Unit) -> bool {
+        // A cooling main weapon can't fire, but the others still can
+        let ready: Vec<&Weapon> = unit
+            .unit_type
+            .weapons()
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| *index > 0 || unit.main_weapon_cooldown == 0)
+            .map(|(_, weapon)| weapon)
+            .collect();
+        self.units.iter().any(|other| {
+            let distance = self.map.distance(unit.position, other.position);
+            let toward = self.map.unwrap_toward(unit.position, other.position);
+            other.owner != unit.owner
+                && !other.is_destroyed()
+                && !other.is_embarked()
+                && ready.iter().any(|w| {
+                    distance <= w.range
+                        && (w.arc == WeaponArc::Turret
+                            || unit.facing.is_in_front_arc(unit.position, toward))
+                })
+                && has_line_of_sight(&self.map, unit.position, other.position)
+        })
+    }
+
+    /// Get every hex a unit could fire on from where it stands
+    ///
+    /// A hex counts if any of the unit's weapons reaches it, bears on it
+    /// (fixed weapons only cover the front arc) and has line of sight to it.
+    /// Weapon cooldowns are ignored, so this shows the unit's full threat.
+    pub fn attackable_hexes(&self, unit_id: u32) -> Vec<HexCoord> {
+        let Some(unit) = self.get_unit(unit_id) else {
+            return Vec::new();
+        };
+        if unit.is_destroyed() || unit.is_embarked() {
+            return Vec::new();
+        }
+
+        let weapons = unit.unit_type.weapons();
+        self.map
+            .all_hexes()
+            .into_iter()
+            .filter(|&hex| {
+                let distance = self.map.distance(unit.position, hex);
+                let toward = self.map.unwrap_toward(unit.position, hex);
+                hex != unit.position
+                    && weapons.iter().any(|w| {
+                        distance <= w.range
+                            && (w.arc == WeaponArc::Turret
+                                || unit.facing.is_in_front_arc(unit.position, toward))
+                    })
+                    && has_line_of_sight(&self.map, unit.position, hex)
+            })
+            .collect()
+    }
+
+    /// Get where a unit can move this turn and what it could shoot at after
+    ///
+    /// Returns the `find_reachable` hexes with their remaining movement points
+    /// plus every hex within weapon range of any of those hexes, other than the
+    /// firing hex itself. Arcs and line of sight are left out since the unit may
+    /// turn and the view changes as it moves. A unit that has already attacked
+    /// threatens nothing.
+    pub fn movement_and_threat_overlay(
+        &self,
+        unit_id: u32,
+    ) -> (HashMap<HexCoord, u32>, HashSet<HexCoord>) {
+        let Some(unit) = self.get_unit(unit_id) else {
+            return (HashMap::new(), HashSet::new());
+        };
+        if unit.is_destroyed() || unit.is_embarked() {
+            return (HashMap::new(), HashSet::new());
+        }
+
+        let reachable = find_reachable(self, unit);
+        let range = if unit.has_attacked {
+            0
+        } else {
+            unit.unit_type.weapon_range()
+        };
+
+        let mut threat = HashSet::new();
+        for &from in reachable.keys() {
+            for radius in 1..=range {
+                threat.extend(
+                    from.ring(radius)
+                        .into_iter()
+                        .map(|hex| self.map.normalize(hex))
+                        .filter(|hex| self.map.is_valid(*hex)),
+                );
+            }
+        }
+
+        (reachable, threat)
+    }
+
+    /// Advance the active player's units along their standing orders
+    ///
+    /// Each ordered unit follows the cheapest path toward its destination as
+    /// far as its movement allows, spending its move for the turn. While the
+    /// destination is occupied or cut off the unit gets as close as it can,
+    /// and the order is cancelled once it can get no closer. Orders also
+    /// clear on arrival or when the unit is destroyed.
+    fn advance_orders(&mut self) -> Vec<GameEvent> {
+        let mut events = Vec::new();
+
+        let mut ordered: Vec<(u32, HexCoord)> = self
+            .orders
+            .iter()
+            .map(|(&unit_id, &destination)| (unit_id, destination))
+            .collect();
+        ordered.sort_by_key(|&(unit_id, _)| unit_id);
+
+        for (unit_id, destination) in ordered {
+            let Some(unit) = self.get_unit(unit_id) else {
+                self.orders.remove(&unit_id);
+                continue;
+            };
+            if unit.is_destroyed() || unit.position == destination {
+                self.orders.remove(&unit_id);
+                continue;
+            }
+            if unit.owner != self.active_player || unit.has_moved || unit.is_embarked() {
+                continue;
+            }
+
+            // Plan the whole route, then take as much of it as this turn allows
+            let (path, routed) = match find_path(self, unit, destination, Some(u32::MAX)) {
+                Some((path, _)) => (path, true),
+                None => match best_approach(self, unit, destination) {
+                    Some((path, _)) => (path, false),
+                    None => (vec![unit.position], false),
+                },
+            };
+
+            let budget = unit.effective_movement();
+            let mut spent = 0;
+            let mut stop = 0;
+            for (index, step) in path.windows(2).enumerate() {
+                let Some(cost) = step_cost(self, unit, step[0], step[1]) else {
+                    break;
+                };
+                spent += cost;
+                if spent > budget {
+                    break;
+                }
+                if !is_blocked(self, step[1], unit_id) {
+                    stop = index + 1;
+                }
+            }
+
+            if stop == 0 {
+                if !routed {
+                    self.orders.remove(&unit_id);
+                    events.push(GameEvent::OrderCancelled {
+                        unit_id,
+                        destination,
+                    });
+                }
+                continue;
+            }
+
+            let start = unit.position;
+            let end = path[stop];
+            let facing = suggest_facing(path[stop - 1], end);
+
+            let unit = self.get_unit_mut(unit_id).unwrap();
+            unit.position = end;
+            unit.facing = facing;
+            unit.has_moved = true;
+            unit.movement_remaining = 0;
+            self.carry_passengers(unit_id);
+
+            events.push(GameEvent::UnitMoved {
+                unit_id,
+                from: start,
+                to: end,
+                facing,
+            });
+
+            if end == destination {
+                self.orders.remove(&unit_id);
+            }
+        }
+
+        events
+    }
+
+    /// End the current turn
+    ///
+    /// Returns the events raised as the new turn starts: status effects
+    /// ticking, reinforcements arriving, a victory and a stalemate draw.
+    fn end_turn(&mut self) -> Vec<GameEvent> {
+        self.current_turn += 1;
+        self.activation = None;
+        self.current_phase = Phase::Movement;
+        self.active_player = match self.initiative_mode {
+            InitiativeMode::Alternating => self.active_player.opponent(),
+            InitiativeMode::Rolled if self.rng.roll(2) == 1 => Player::Player1,
+            InitiativeMode::Rolled => Player::Player2,
+        };
+
+        // Reset all units
+        let mut ticks = Vec::new();
+        for unit in &mut self.units {
+            let tick = unit.reset_for_turn();
+            if let Some(modifier) = self.stat_modifiers.get(&unit.owner) {
+                unit.movement_remaining = unit
+                    .movement_remaining
+                    .saturating_add_signed(modifier.movement_bonus);
+            }
+            ticks.push((unit.id, unit.is_destroyed(), tick));
+        }
+
+        let mut events = Vec::new();
+        let mut destroyed_any = false;
+        for (unit_id, destroyed, tick) in ticks {
+            if tick.damage > 0 {
+                events.push(GameEvent::StatusDamage {
+                    unit_id,
+                    damage: tick.damage,
+                });
+            }
+            for effect in tick.expired {
+                events.push(GameEvent::StatusExpired { unit_id, effect });
+            }
+            if destroyed && tick.damage > 0 {
+                events.extend(self.destroy_unit(unit_id));
+                destroyed_any = true;
+            }
+        }
+        if destroyed_any {
+            events.extend(self.check_victory());
+        }
+        events.extend(self.arrive_reinforcements());
+        events.extend(self.check_victory());
+        events.extend(self.check_stalemate());
+
+        events
+    }
+
+    /// Refresh a unit's actions mid-turn so it can act again
+    ///
+    /// Used by abilities that grant a unit a second activation. Goes
+    /// through `Command::Refresh` so the refresh is logged for replays.
+    pub fn refresh_unit(&mut self, unit_id: u32) -> Result<(), CommandError> {
+        self.process_command(Command::Refresh { unit_id })
+            .map(|_| ())
+    }
+
+    /// Put a unit on a hex directly, ignoring movement rules
+    ///
+    /// For scenario scripting and test setup rather than play: no event is
+    /// logged and the unit's actions aren't spent. The hex must be on the
+    /// map, passable, and have room under the stacking rules. A passenger
+    /// placed this way leaves its transport; a transport takes its
+    /// passengers along.
+    pub fn place_unit(
+        &mut self,
+        unit_id: u32,
+        position: HexCoord,
+        facing: Facing,
+    ) -> Result<(), String> {
+        if self.get_unit(unit_id).is_none() {
+            return Err(CommandError::UnitNotFound(unit_id).to_string());
+        }
+
+        if self
+            .map
+            .get_tile(position)
+            .is_none_or(|t| t.terrain.movement_cost().is_none())
+        {
+            return Err(CommandError::InvalidDestination(position).to_string());
+        }
+
+        if !self.has_room(position, unit_id) {
+            return Err(CommandError::Occupied(position).to_string());
+        }
+
+        let unit = self.get_unit_mut(unit_id).unwrap();
+        unit.position = position;
+        unit.facing = facing;
+        unit.embarked_on = None;
+        self.carry_passengers(unit_id);
+        Ok(())
+    }
+
+    /// Rotate a group of units about a pivot by 60° steps, keeping its shape
+    ///
+    /// Each unit's facing turns with it. Every destination is checked before
+    /// anything moves, so on error no unit has been repositioned. Goes
+    /// through `Command::RotateFormation` so the rotation is logged for
+    /// replays.
+    pub fn rotate_formation(
+        &mut self,
+        unit_ids: &[u32],
+        pivot: HexCoord,
+        steps: i32,
+    ) -> Result<(), String> {
+        let command = Command::RotateFormation {
+            unit_ids: unit_ids.to_vec(),
+            pivot,
+            steps,
+        };
+        self.process_command(command)
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    }
+
+    /// Work out where each unit of a rotating formation ends up and which
+    /// way it faces, as (unit ID, position, facing)
+    fn plan_rotation(
+        &self,
+        unit_ids: &[u32],
+        pivot: HexCoord,
+        steps: i32,
+    ) -> Result<Vec<(u32, HexCoord, Facing)>, CommandError> {
+        let mut moves = Vec::with_capacity(unit_ids.len());
+        for &id in unit_ids {
+            let unit = self.get_unit(id).ok_or(CommandError::UnitNotFound(id))?;
+            if unit.is_destroyed() {
+                return Err(CommandError::UnitDestroyed(id));
+            }
+            if unit.is_embarked() {
+                return Err(CommandError::UnitEmbarked(id));
+            }
+
+            let position = unit.position.rotate_around(pivot, steps);
+            if self
+                .map
+                .get_tile(position)
+                .is_none_or(|t| t.terrain.movement_cost().is_none())
+            {
+                return Err(CommandError::InvalidDestination(position));
+            }
+            moves.push((id, unit.owner, position, unit.facing.rotate_cw(steps)));
+        }
+
+        // Formation members are all leaving, so only the others stay put
+        for &(_, owner, position, _) in &moves {
+            let staying: Vec<&Unit> = self
+                .units_at(position)
+                .into_iter()
+                .filter(|u| !unit_ids.contains(&u.id))
+                .collect();
+            let arriving: Vec<Player> = moves
+                .iter()
+                .filter(|m| m.2 == position)
+                .map(|m| m.1)
+                .collect();
+            if staying.len() + arriving.len() > self.max_stack
+                || staying.iter().any(|u| u.owner != owner)
+                || arriving.iter().any(|p| *p != owner)
+            {
+                return Err(CommandError::Occupied(position));
+            }
+        }
+
+        Ok(moves
+            .into_iter()
+            .map(|(id, _, position, facing)| (id, position, facing))
+            .collect())
+    }
+
+    /// Move a group of units the same number of hexes in one direction
+    ///
+    /// Each unit makes a normal Move ending facing `direction`. The unit
+    /// furthest ahead goes first so nobody ends on a hex a squadmate is
+    /// still standing in. If any move is illegal none of them happen.
+    pub fn advance_formation(
+        &mut self,
+        unit_ids: &[u32],
+        direction: Facing,
+        hexes: u32,
+    ) -> Result<Vec<GameEvent>, String> {
+        let mut order = Vec::with_capacity(unit_ids.len());
+        for &id in unit_ids {
+            let unit = self
+                .get_unit(id)
+                .ok_or(CommandError::UnitNotFound(id).to_string())?;
+            order.push((id, unit.position));
+        }
+
+        // Project each position onto the direction of travel, leaders first
+        let step = HexCoord::from(direction.offset()).to_cube();
+        order.sort_by_key(|&(id, position)| {
+            let cube = position.to_cube();
+            (
+                std::cmp::Reverse(cube.x * step.x + cube.y * step.y + cube.z * step.z),
+                id,
+            )
+        });
+
+        let mut advanced = self.clone();
+        let mut events = Vec::new();
+        for (id, start) in order {
+            let mut path = vec![start];
+            for _ in 0..hexes {
+                let last = *path.last().unwrap();
+                path.push(self.map.neighbors(last)[direction.index() as usize]);
+            }
+            let command = Command::Move {
+                unit_id: id,
+                path,
+                final_facing: direction,
+            };
+            events.extend(
+                advanced
+                    .process_command(command)
+                    .map_err(|e| format!("Unit {}: {}", id, e))?,
+            );
+        }
+
+        *self = advanced;
+        Ok(events)
+    }
+
+    /// Select a unit
+    pub fn select_unit(&mut self, unit_id: Option<u32>) {
+        self.selected_unit = unit_id;
+    }
+
+    /// Get the selected unit
+    pub fn selected_unit(&self) -> Option<&Unit> {
+        self.selected_unit.and_then(|id| self.get_unit(id))
+    }
+
+    /// Get the selection, turn and phase the UI restores after a reload
+    pub fn ui_context(&self) -> UiContext {
+        UiContext {
+            selected_unit: self.selected_unit,
+            active_player: self.active_player,
+            phase: self.current_phase,
+            turn: self.current_turn,
+        }
+    }
+
+    /// Check if a player has won, returning the elimination and game-over events
+    ///
+    /// The first of `victory_conditions` that names a winner decides the game.
+    pub fn check_victory(&mut self) -> Vec<GameEvent> {
+        let mut events = Vec::new();
+        if self.game_over {
+            return events;
+        }
+
+        let Some((condition, winner)) = self
+            .victory_conditions
+            .iter()
+            .find_map(|condition| Some((*condition, self.condition_winner(condition)?)))
+        else {
+            return events;
+        };
+
+        self.game_over = true;
+        self.winner = Some(winner);
+        if condition == VictoryCondition::Annihilation {
+            events.push(GameEvent::PlayerEliminated {
+                player: winner.opponent(),
+            });
+        }
+        events.push(GameEvent::GameOver {
+            winner: Some(winner),
+        });
+        events
+    }
+
+    /// Get the player a victory condition currently hands the game to, if any
+    pub fn condition_winner(&self, condition: &VictoryCondition) -> Option<Player> {
+        match *condition {
+            VictoryCondition::Annihilation => {
+                // A side that has yet to lose anything may just not be on the board yet
+                let wiped_out = |player: Player| {
+                    self.player_units_iter(player).next().is_none()
+                        && self
+                            .units
+                            .iter()
+                            .any(|u| u.owner == player && u.is_destroyed())
+                };
+                let p1_alive = self.player_units_iter(Player::Player1).count();
+                let p2_alive = self.player_units_iter(Player::Player2).count();
+
+                if wiped_out(Player::Player1) && p2_alive > 0 {
+                    Some(Player::Player2)
+                } else if wiped_out(Player::Player2) && p1_alive > 0 {
+                    Some(Player::Player1)
+                } else {
+                    None
+                }
+            }
+            VictoryCondition::VictoryPoints(target) => [Player::Player1, Player::Player2]
+                .into_iter()
+                .find(|player| self.victory_points(*player) >= target),
+            VictoryCondition::SurviveTurns(turns) => {
+                let standing = self.player_units_iter(Player::Player2).next().is_some();
+                (self.current_turn > turns && standing).then_some(Player::Player2)
+            }
+            VictoryCondition::ExitUnits { edge, count } => {
+                let exited = self
+                    .player_units_iter(Player::Player1)
+                    .filter(|u| !u.is_embarked() && self.map.is_on_edge(u.position, edge))
+                    .count();
+                (exited >= count as usize).then_some(Player::Player1)
+            }
+        }
+    }
+
+    /// Get a player's victory points: the starting structure of every enemy
+    /// unit they have destroyed
+    pub fn victory_points(&self, player: Player) -> u32 {
+        self.units
+            .iter()
+            .filter(|u| u.owner != player && u.is_destroyed())
+            .map(|u| u.unit_type.base_structure())
+            .sum()
+    }
+
+    /// Get how many whole turns have passed since the last attack
+    pub fn turns_without_combat(&self) -> u32 {
+        self.current_turn
+            .saturating_sub(1)
+            .saturating_sub(self.last_combat_turn)
+    }
+
+    /// Draw the game once `stalemate_limit` turns in a row pass with no attack
+    pub fn check_stalemate(&mut self) -> Vec<GameEvent> {
+        let mut events = Vec::new();
+        if self.game_over
+            || self.stalemate_limit == 0
+            || self.turns_without_combat() < self.stalemate_limit
+        {
+            return events;
+        }
+
+        self.game_over = true;
+        self.winner = None;
+        events.push(GameEvent::GameOver { winner: None });
+        events
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_phase_sequence() {
+        assert_eq!(Phase::Deployment.next(), Phase::Movement);
+        assert_eq!(Phase::Movement.next(), Phase::Combat);
+        assert_eq!(Phase::Combat.next(), Phase::End);
+        assert_eq!(Phase::End.next(), Phase::Movement);
+    }
+
+    #[test]
+    fn test_unit_creation() {
+        let unit = Unit::new(
+            1,
+            UnitType::ReaverTitan,
+            Player::Player1,
+            HexCoord::new(0, 0),
+            Facing::East,
+        );
         assert_eq!(unit.armor, 12);
         assert_eq!(unit.structure, 10);
         assert_eq!(unit.void_shields, 2);
@@ -557,21 +4399,3096 @@ mod tests {
     }
 
     #[test]
-    fn test_game_state() {
-        let map = GameMap::new(10, 10);
-        let mut state = GameState::new(map);
+    fn test_player_units_iter_matches_vec() {
+        let mut state = GameState::new(GameMap::new(10, 10));
+        let roster = [
+            (UnitType::ReaverTitan, Player::Player1, HexCoord::new(0, 0)),
+            (UnitType::Shadowsword, Player::Player2, HexCoord::new(5, 5)),
+            (UnitType::KriegSquad, Player::Player1, HexCoord::new(2, 2)),
+            (UnitType::WarlordTitan, Player::Player1, HexCoord::new(3, 3)),
+        ];
+        for (id, (unit_type, owner, position)) in (1..).zip(roster) {
+            state.add_unit(Unit::new(id, unit_type, owner, position, Facing::East));
+        }
+        state.get_unit_mut(3).unwrap().structure = 0;
+
+        for player in [Player::Player1, Player::Player2] {
+            let listed: Vec<u32> = state.player_units(player).iter().map(|u| u.id).collect();
+            let iterated: Vec<u32> = state.player_units_iter(player).map(|u| u.id).collect();
+            assert_eq!(listed, iterated);
+        }
+        assert_eq!(state.player_units_iter(Player::Player1).count(), 2);
+    }
+
+    #[test]
+    fn test_new_at_starts_mid_game() {
+        let mut state = GameState::new_at(GameMap::new(10, 10), 3, Phase::Combat, Player::Player2);
+        state.add_unit(Unit::new(
+            1,
+            UnitType::ReaverTitan,
+            Player::Player1,
+            HexCoord::new(0, 0),
+            Facing::East,
+        ));
+        state.add_unit(Unit::new(
+            2,
+            UnitType::WarlordTitan,
+            Player::Player2,
+            HexCoord::new(4, 0),
+            Facing::West,
+        ));
+        assert_eq!(state.current_turn, 3);
+
+        let result = state.process_command(Command::Move {
+            unit_id: 2,
+            path: vec![HexCoord::new(4, 0), HexCoord::new(3, 0)],
+            final_facing: Facing::West,
+        });
+        assert_eq!(result.unwrap_err(), CommandError::WrongPhase(Phase::Combat));
+
+        state
+            .process_command(Command::Attack {
+                attacker_id: 2,
+                target_id: 1,
+                weapon_index: 0,
+            })
+            .unwrap();
+        assert!(state.get_unit(2).unwrap().has_attacked);
+    }
+
+    #[test]
+    fn test_game_state() {
+        let map = GameMap::new(10, 10);
+        let mut state = GameState::new(map);
+
+        let unit = Unit::new(
+            1,
+            UnitType::Shadowsword,
+            Player::Player1,
+            HexCoord::new(0, 0),
+            Facing::East,
+        );
+        state.add_unit(unit);
+
+        assert_eq!(state.units.len(), 1);
+        assert!(state.get_unit(1).is_some());
+        assert_eq!(state.units_at(HexCoord::new(0, 0)).len(), 1);
+    }
+
+    #[test]
+    fn test_elevation_grid() {
+        let mut map = GameMap::new(6, 4);
+        map.tiles.get_mut(&(2, 1)).unwrap().elevation = 3;
+        map.tiles.get_mut(&(0, 3)).unwrap().elevation = -1;
+
+        let grid = map.elevation_grid();
+        assert_eq!(grid.len(), map.tiles.len());
+        let coords: HashSet<HexCoord> = grid.iter().map(|(coord, _)| *coord).collect();
+        assert_eq!(coords.len(), grid.len());
+        assert!(grid.contains(&(HexCoord::new(2, 1), 3)));
+        assert!(grid.contains(&(HexCoord::new(0, 3), -1)));
+
+        assert_eq!(map.elevation_range(), (-1, 3));
+        assert_eq!(GameMap::new(6, 4).elevation_range(), (0, 0));
+    }
+
+    #[test]
+    fn test_horizontal_wrap_distance() {
+        let flat = GameMap::new(10, 10);
+        let wrapped = GameMap::new_wrapped(10, 10, Wrap::Horizontal).unwrap();
+
+        // Column 0 and column 9 of the same row sit either side of the seam
+        let west = HexCoord::new(0, 0);
+        let east = HexCoord::new(9, 0);
+        assert_eq!(flat.distance(west, east), 9);
+        assert_eq!(wrapped.distance(west, east), 1);
+        assert!(wrapped.neighbors(east).contains(&west));
+        assert!(wrapped.valid_neighbors(east).contains(&west));
+        assert!(!flat.valid_neighbors(east).contains(&west));
+        assert!(wrapped.is_contiguous_path(&[east, west]));
+        assert!(!flat.is_contiguous_path(&[east, west]));
+
+        // Rows with an offset still wrap onto the matching column
+        let west_odd = HexCoord::new(-1, 3);
+        let east_odd = HexCoord::new(8, 3);
+        assert!(wrapped.is_valid(west_odd) && wrapped.is_valid(east_odd));
+        assert_eq!(wrapped.distance(west_odd, east_odd), 1);
+
+        // Wrapping never changes the north/south edges
+        assert_eq!(wrapped.distance(HexCoord::new(0, 0), HexCoord::new(-4, 9)), 9);
+    }
+
+    #[test]
+    fn test_toroidal_wrap_distance() {
+        let map = GameMap::new_wrapped(10, 10, Wrap::Toroidal).unwrap();
+        let top = HexCoord::new(0, 0);
+        let bottom = HexCoord::new(-4, 9);
+        assert!(map.is_valid(bottom));
+        assert_eq!(map.distance(top, bottom), 1);
+        assert!(map.valid_neighbors(top).contains(&bottom));
+    }
+
+    #[test]
+    fn test_toroidal_wrap_needs_even_height() {
+        assert!(GameMap::new_wrapped(10, 9, Wrap::Toroidal).is_err());
+        assert!(GameMap::new_wrapped(10, 9, Wrap::Horizontal).is_ok());
+
+        let json = r#"{"width":10,"height":9,"wrap":"Toroidal"}"#;
+        assert!(serde_json::from_str::<GameMap>(json).is_err());
+    }
+
+    #[test]
+    fn test_attack_on_destroyed_target_is_rejected() {
+        let map = GameMap::new(10, 10);
+        let mut state = GameState::new(map);
+        state.current_phase = Phase::Combat;
+        state.add_unit(Unit::new(
+            1,
+            UnitType::ReaverTitan,
+            Player::Player1,
+            HexCoord::new(0, 0),
+            Facing::East,
+        ));
+        state.add_unit(Unit::new(
+            2,
+            UnitType::Shadowsword,
+            Player::Player1,
+            HexCoord::new(0, 1),
+            Facing::East,
+        ));
+        let mut target = Unit::new(
+            3,
+            UnitType::Shadowsword,
+            Player::Player2,
+            HexCoord::new(3, 0),
+            Facing::West,
+        );
+        target.structure = 1;
+        state.add_unit(target);
+        state.add_unit(Unit::new(
+            4,
+            UnitType::KriegSquad,
+            Player::Player2,
+            HexCoord::new(9, 9),
+            Facing::West,
+        ));
+
+        let events = state
+            .process_command(Command::Attack {
+                attacker_id: 1,
+                target_id: 3,
+                weapon_index: 0,
+            })
+            .unwrap();
+        assert!(events
+            .iter()
+            .any(|e| matches!(e, GameEvent::UnitDestroyed { unit_id: 3 })));
+        assert!(state.get_unit(3).unwrap().is_destroyed());
+
+        let result = state.process_command(Command::Attack {
+            attacker_id: 2,
+            target_id: 3,
+            weapon_index: 0,
+        });
+        assert_eq!(result.unwrap_err(), CommandError::TargetDestroyed(3));
+        assert!(!state.get_unit(2).unwrap().has_attacked);
+        assert_eq!(state.get_unit(3).unwrap().structure, 0);
+    }
+
+    fn command_error_state() -> GameState {
+        let map = GameMap::new(10, 10);
+        let mut state = GameState::new(map);
+        state.add_unit(Unit::new(
+            1,
+            UnitType::ReaverTitan,
+            Player::Player1,
+            HexCoord::new(0, 0),
+            Facing::East,
+        ));
+        state.add_unit(Unit::new(
+            2,
+            UnitType::Shadowsword,
+            Player::Player1,
+            HexCoord::new(1, 0),
+            Facing::East,
+        ));
+        state.add_unit(Unit::new(
+            3,
+            UnitType::Shadowsword,
+            Player::Player2,
+            HexCoord::new(8, 0),
+            Facing::West,
+        ));
+        // Held back so destroying unit 3 doesn't end the game
+        state.add_unit(Unit::new(
+            4,
+            UnitType::KriegSquad,
+            Player::Player2,
+            HexCoord::new(5, 9),
+            Facing::West,
+        ));
+        state
+    }
+
+    fn move_to(unit_id: u32, path: Vec<HexCoord>) -> Command {
+        Command::Move {
+            unit_id,
+            path,
+            final_facing: Facing::East,
+        }
+    }
+
+    #[test]
+    fn test_move_command_errors() {
+        let mut state = command_error_state();
+        let step = vec![HexCoord::new(0, 0), HexCoord::new(0, 1)];
+
+        assert_eq!(
+            state.process_command(move_to(1, step.clone())),
+            Err(CommandError::WrongPhase(Phase::Deployment))
+        );
+
+        state.current_phase = Phase::Movement;
+        assert_eq!(
+            state.process_command(move_to(99, step.clone())),
+            Err(CommandError::UnitNotFound(99))
+        );
+        assert_eq!(
+            state.process_command(move_to(3, vec![HexCoord::new(8, 0), HexCoord::new(7, 0)])),
+            Err(CommandError::NotOwner(3))
+        );
+        assert_eq!(
+            state.process_command(move_to(1, vec![])),
+            Err(CommandError::EmptyPath)
+        );
+        assert_eq!(
+            state.process_command(move_to(1, vec![HexCoord::new(0, 0), HexCoord::new(-1, 0)])),
+            Err(CommandError::InvalidDestination(HexCoord::new(-1, 0)))
+        );
+        assert_eq!(
+            state.process_command(move_to(1, vec![HexCoord::new(0, 0), HexCoord::new(1, 0)])),
+            Err(CommandError::Occupied(HexCoord::new(1, 0)))
+        );
+
+        assert_eq!(
+            state.process_command(move_to(1, vec![HexCoord::new(0, 1), HexCoord::new(0, 2)])),
+            Err(CommandError::PathStartMismatch(HexCoord::new(0, 1)))
+        );
+        assert_eq!(
+            state.process_command(move_to(1, vec![HexCoord::new(0, 0), HexCoord::new(0, 2)])),
+            Err(CommandError::NotAdjacent(HexCoord::new(0, 2)))
+        );
+        state.map.tiles.get_mut(&(0, 1)).unwrap().terrain = TerrainType::Impassable;
+        assert_eq!(
+            state.process_command(move_to(
+                1,
+                vec![
+                    HexCoord::new(0, 0),
+                    HexCoord::new(0, 1),
+                    HexCoord::new(0, 2)
+                ]
+            )),
+            Err(CommandError::PathBlocked(HexCoord::new(0, 1)))
+        );
+        state.map.tiles.get_mut(&(0, 1)).unwrap().terrain = TerrainType::Clear;
+        let long_path: Vec<HexCoord> = (0..=7).map(|r| HexCoord::new(0, r)).collect();
+        assert_eq!(
+            state.process_command(move_to(1, long_path)),
+            Err(CommandError::InsufficientMovement {
+                needed: 7,
+                available: 6
+            })
+        );
+
+        state.process_command(move_to(1, step)).unwrap();
+        assert_eq!(
+            state.process_command(move_to(1, vec![HexCoord::new(0, 1), HexCoord::new(0, 2)])),
+            Err(CommandError::AlreadyMoved(1))
+        );
+    }
+
+    #[test]
+    fn test_move_loops_back_to_start() {
+        let mut state = command_error_state();
+        state.current_phase = Phase::Movement;
+        let (start, out) = (HexCoord::new(1, 0), HexCoord::new(2, 0));
+
+        // Out and back is a legal move that costs the MP of both steps
+        let preview = crate::movement::path_with_costs(
+            &state,
+            state.get_unit(2).unwrap(),
+            &[start, out, start],
+        );
+        assert_eq!(preview.unwrap().last(), Some(&(start, 3)));
+
+        // Bouncing past the budget is refused even though nothing moves net
+        let mut long_loop = vec![start];
+        for _ in 0..3 {
+            long_loop.extend([out, start]);
+        }
+        assert_eq!(
+            state.can_process(&move_to(2, long_loop)),
+            Err(CommandError::InsufficientMovement {
+                needed: 6,
+                available: 5
+            })
+        );
+
+        // An enemy on the loop blocks it even though the end hex is free
+        let mut blocked = state.clone();
+        blocked.add_unit(Unit::new(
+            5,
+            UnitType::KriegSquad,
+            Player::Player2,
+            out,
+            Facing::West,
+        ));
+        assert_eq!(
+            blocked.can_process(&move_to(2, vec![start, out, start])),
+            Err(CommandError::PathBlocked(out))
+        );
+
+        let events = state
+            .process_command(move_to(2, vec![start, out, start]))
+            .unwrap();
+        assert!(matches!(
+            events[0],
+            GameEvent::UnitMoved { from, to, .. } if from == start && to == start
+        ));
+        assert_eq!(state.get_unit(2).unwrap().position, start);
+        assert!(state.get_unit(2).unwrap().has_moved);
+    }
+
+    #[test]
+    fn test_move_in_place_on_crowded_hex() {
+        let mut state = command_error_state();
+        state.current_phase = Phase::Movement;
+        let (start, out) = (HexCoord::new(1, 0), HexCoord::new(2, 0));
+
+        // A second tank crammed onto the hex puts it over the stack limit
+        state.add_unit(Unit::new(
+            5,
+            UnitType::Shadowsword,
+            Player::Player1,
+            start,
+            Facing::East,
+        ));
+
+        // Walking out and back in is a real move onto a full hex
+        assert_eq!(
+            state.can_process(&move_to(2, vec![start, out, start])),
+            Err(CommandError::Occupied(start))
+        );
+
+        // Holding position never leaves, so there is nothing to re-enter
+        let events = state.process_command(move_to(2, vec![start])).unwrap();
+        assert!(matches!(
+            events[0],
+            GameEvent::UnitMoved { from, to, .. } if from == start && to == start
+        ));
+        assert!(state.get_unit(2).unwrap().has_moved);
+    }
+
+    #[test]
+    fn test_step_moves_match_bulk_move() {
+        let mut stepped = command_error_state();
+        stepped.current_phase = Phase::Movement;
+        stepped.map.tiles.get_mut(&(2, 1)).unwrap().terrain = TerrainType::Rough;
+        let mut bulk = stepped.clone();
+        let path = vec![
+            HexCoord::new(1, 0),
+            HexCoord::new(1, 1),
+            HexCoord::new(2, 1),
+            HexCoord::new(3, 1),
+        ];
+
+        bulk.process_command(Command::Move {
+            unit_id: 2,
+            path: path.clone(),
+            final_facing: Facing::East,
+        })
+        .unwrap();
+        for &to in &path[1..] {
+            stepped
+                .process_command(Command::StepMove { unit_id: 2, to })
+                .unwrap();
+        }
+
+        let (stepped_unit, bulk_unit) = (stepped.get_unit(2).unwrap(), bulk.get_unit(2).unwrap());
+        assert_eq!(stepped_unit.position, bulk_unit.position);
+        assert_eq!(stepped_unit.facing, Facing::East);
+        // Clear, Rough, Clear: four of the tank's five MP
+        assert_eq!(stepped_unit.movement_remaining, 1);
+
+        // A step must go to an adjacent hex the unit can still afford
+        let step = |q, r| Command::StepMove {
+            unit_id: 2,
+            to: HexCoord::new(q, r),
+        };
+        assert_eq!(
+            stepped.can_process(&step(5, 1)),
+            Err(CommandError::NotAdjacent(HexCoord::new(5, 1)))
+        );
+        stepped.map.tiles.get_mut(&(4, 1)).unwrap().terrain = TerrainType::Rough;
+        assert_eq!(
+            stepped.can_process(&step(4, 1)),
+            Err(CommandError::InsufficientMovement {
+                needed: 2,
+                available: 1,
+            })
+        );
+    }
+
+    #[test]
+    fn test_attack_command_errors() {
+        let mut state = command_error_state();
+        let attack = |attacker_id, target_id| Command::Attack {
+            attacker_id,
+            target_id,
+            weapon_index: 0,
+        };
+
+        assert_eq!(
+            state.process_command(attack(1, 3)),
+            Err(CommandError::WrongPhase(Phase::Deployment))
+        );
+
+        state.current_phase = Phase::Combat;
+        assert_eq!(
+            state.process_command(attack(99, 3)),
+            Err(CommandError::UnitNotFound(99))
+        );
+        assert_eq!(
+            state.process_command(attack(3, 1)),
+            Err(CommandError::NotOwner(3))
+        );
+        assert_eq!(
+            state.process_command(attack(1, 99)),
+            Err(CommandError::UnitNotFound(99))
+        );
+        assert_eq!(
+            state.process_command(attack(1, 2)),
+            Err(CommandError::FriendlyTarget(2))
+        );
+        assert_eq!(
+            state.process_command(attack(1, 3)),
+            Err(CommandError::OutOfRange {
+                distance: 8,
+                range: 6
+            })
+        );
+
+        state.get_unit_mut(3).unwrap().position = HexCoord::new(4, 0);
+        state.process_command(attack(1, 3)).unwrap();
+        assert_eq!(
+            state.process_command(attack(1, 3)),
+            Err(CommandError::AlreadyAttacked(1))
+        );
+
+        state.get_unit_mut(2).unwrap().structure = 0;
+        assert_eq!(
+            state.process_command(attack(2, 3)),
+            Err(CommandError::UnitDestroyed(2))
+        );
+    }
+
+    #[test]
+    fn test_can_process_matches_process_command() {
+        let mut state = command_error_state();
+        state.current_phase = Phase::Movement;
+        let illegal = [
+            move_to(99, vec![HexCoord::new(0, 0)]),
+            move_to(3, vec![HexCoord::new(8, 0), HexCoord::new(7, 0)]),
+            move_to(1, vec![]),
+            move_to(1, vec![HexCoord::new(0, 0), HexCoord::new(1, 0)]),
+            move_to(1, vec![HexCoord::new(0, 0), HexCoord::new(0, 2)]),
+            Command::Attack {
+                attacker_id: 1,
+                target_id: 3,
+                weapon_index: 0,
+            },
+            Command::Deploy {
+                unit_id: 1,
+                position: HexCoord::new(2, 2),
+                facing: Facing::East,
+            },
+            Command::Embark {
+                unit_id: 1,
+                transport_id: 2,
+            },
+            Command::Disembark {
+                unit_id: 1,
+                position: HexCoord::new(0, 1),
+            },
+            Command::SetOrder {
+                unit_id: 1,
+                destination: HexCoord::new(-5, 0),
+            },
+        ];
+
+        for command in illegal {
+            let expected = state.can_process(&command).unwrap_err();
+            assert_eq!(state.process_command(command), Err(expected));
+        }
+
+        state.current_phase = Phase::Combat;
+        let bombard = Command::Bombard {
+            attacker_id: 1,
+            target: HexCoord::new(0, 9),
+        };
+        let expected = state.can_process(&bombard).unwrap_err();
+        assert!(matches!(expected, CommandError::OutOfRange { .. }));
+        assert_eq!(state.process_command(bombard), Err(expected));
+
+        assert_eq!(state.can_process(&Command::EndPhase), Ok(()));
+        state.game_over = true;
+        assert_eq!(
+            state.can_process(&Command::EndPhase),
+            Err(CommandError::GameOver)
+        );
+    }
+
+    #[test]
+    fn test_handicap_weakens_attacks() {
+        let damage_from = |modifier: StatModifier| {
+            let mut state = command_error_state();
+            state.set_stat_modifier(Player::Player1, modifier);
+            state.current_phase = Phase::Combat;
+            state.get_unit_mut(3).unwrap().position = HexCoord::new(4, 0);
+            state
+                .process_command(Command::Attack {
+                    attacker_id: 1,
+                    target_id: 3,
+                    weapon_index: 0,
+                })
+                .unwrap();
+            state.get_unit(1).unwrap().damage_dealt
+        };
+
+        let full = damage_from(StatModifier::default());
+        let weakened = damage_from(StatModifier {
+            damage_multiplier: 0.8,
+            movement_bonus: 0,
+        });
+        assert_eq!(full, 6);
+        assert_eq!(weakened, 5);
+
+        let mut state = command_error_state();
+        let base = UnitType::ReaverTitan.base_movement();
+        state.set_stat_modifier(
+            Player::Player1,
+            StatModifier {
+                damage_multiplier: 1.0,
+                movement_bonus: 2,
+            },
+        );
+        assert_eq!(state.get_unit(1).unwrap().movement_remaining, base + 2);
+        assert_eq!(
+            state.get_unit(3).unwrap().movement_remaining,
+            UnitType::Shadowsword.base_movement()
+        );
+        state.end_turn();
+        state.end_turn();
+        assert_eq!(state.get_unit(1).unwrap().movement_remaining, base + 2);
+    }
+
+    #[test]
+    fn test_reserves_deploy_onto_board() {
+        let mut state = command_error_state();
+        state.add_reserve(Unit::new(
+            5,
+            UnitType::KriegSquad,
+            Player::Player1,
+            HexCoord::new(0, 0),
+            Facing::East,
+        ));
+        // Sits next to the Reaver, where it would block it if on the board
+        state.add_reserve(Unit::new(
+            6,
+            UnitType::Shadowsword,
+            Player::Player2,
+            HexCoord::new(0, 1),
+            Facing::West,
+        ));
+
+        let reserve_ids = |state: &GameState, player| -> Vec<u32> {
+            state.reserves(player).iter().map(|u| u.id).collect()
+        };
+        assert_eq!(reserve_ids(&state, Player::Player1), vec![5]);
+        assert_eq!(reserve_ids(&state, Player::Player2), vec![6]);
+        assert!(state.get_unit(6).is_none());
+        assert!(state.units_at(HexCoord::new(0, 1)).is_empty());
+        let reaver = state.get_unit(1).unwrap();
+        assert!(crate::movement::find_reachable(&state, reaver).contains_key(&HexCoord::new(0, 1)));
+
+        let deploy = Command::Deploy {
+            unit_id: 5,
+            position: HexCoord::new(3, 3),
+            facing: Facing::East,
+        };
+        assert!(state.legal_commands().contains(&deploy));
+        state.process_command(deploy).unwrap();
+
+        assert!(reserve_ids(&state, Player::Player1).is_empty());
+        assert_eq!(state.get_unit(5).unwrap().position, HexCoord::new(3, 3));
+        assert_eq!(state.units_at(HexCoord::new(3, 3)).len(), 1);
+
+        // The opponent's reserves are still theirs to deploy
+        assert_eq!(
+            state.can_process(&Command::Deploy {
+                unit_id: 6,
+                position: HexCoord::new(6, 6),
+                facing: Facing::West,
+            }),
+            Err(CommandError::NotOwner(6))
+        );
+    }
+
+    #[test]
+    fn test_hidden_deployment() {
+        let mut state = command_error_state();
+        state.hidden_deployment = true;
+        let deploy = |unit_id, q, r| Command::Deploy {
+            unit_id,
+            position: HexCoord::new(q, r),
+            facing: Facing::East,
+        };
+        let seen_by = |state: &GameState, viewer| -> Vec<u32> {
+            state.visible_units(viewer).iter().map(|u| u.id).collect()
+        };
+
+        state.process_command(deploy(1, 2, 2)).unwrap();
+        assert_eq!(seen_by(&state, Player::Player1), vec![1, 2]);
+        assert_eq!(seen_by(&state, Player::Player2), vec![3, 4]);
+
+        // Looking a unit up by ID doesn't give the hidden ones away
+        assert!(state.visible_unit(3).is_none());
+        assert!(state.visible_unit(2).is_some());
+        assert_eq!(
+            state.simulate(Command::EndPhase).unwrap_err(),
+            CommandError::DeploymentNotConfirmed(Player::Player1)
+        );
+        assert!(state.legal_commands().contains(&Command::ConfirmDeployment));
+        assert!(!state.legal_commands().contains(&Command::EndPhase));
+
+        state.process_command(Command::ConfirmDeployment).unwrap();
+        assert_eq!(state.active_player, Player::Player2);
+        state.process_command(deploy(3, 6, 6)).unwrap();
+        assert_eq!(seen_by(&state, Player::Player1), vec![1, 2]);
+        assert_eq!(
+            state.simulate(Command::EndPhase).unwrap_err(),
+            CommandError::DeploymentNotConfirmed(Player::Player2)
+        );
+
+        let events = state.process_command(Command::ConfirmDeployment).unwrap();
+        assert_eq!(events.last(), Some(&GameEvent::DeploymentRevealed));
+        assert!(state.deployment_revealed());
+        assert_eq!(seen_by(&state, Player::Player1), vec![1, 2, 3, 4]);
+        assert_eq!(seen_by(&state, Player::Player2), vec![1, 2, 3, 4]);
+        assert_eq!(state.active_player, Player::Player1);
+        assert_eq!(
+            state.simulate(Command::ConfirmDeployment).unwrap_err(),
+            CommandError::DeploymentConfirmed(Player::Player1)
+        );
+        state.process_command(Command::EndPhase).unwrap();
+        assert_eq!(state.current_phase, Phase::Movement);
+    }
+
+    #[test]
+    fn test_blind_deployment_cannot_overlap() {
+        let mut state = command_error_state();
+        state.hidden_deployment = true;
+        let deploy = |unit_id, q, r| Command::Deploy {
+            unit_id,
+            position: HexCoord::new(q, r),
+            facing: Facing::East,
+        };
+
+        state.process_command(deploy(1, 4, 4)).unwrap();
+        state.process_command(Command::ConfirmDeployment).unwrap();
+
+        // The rules still see the first player's hidden Reaver
+        assert!(state.visible_unit(1).is_none());
+        assert_eq!(
+            state.process_command(deploy(3, 4, 4)),
+            Err(CommandError::Occupied(HexCoord::new(4, 4)))
+        );
+        assert!(!state.legal_commands().contains(&deploy(3, 4, 4)));
+
+        // A clash slipped in around the commands is reported before the reveal
+        state.get_unit_mut(3).unwrap().position = HexCoord::new(4, 4);
+        assert!(!state.deployment_revealed());
+        let issues = state.validate_deployment();
+        assert!(issues.contains(&DeploymentIssue::Occupied {
+            unit_id: 1,
+            position: HexCoord::new(4, 4)
+        }));
+        assert!(issues.contains(&DeploymentIssue::Occupied {
+            unit_id: 3,
+            position: HexCoord::new(4, 4)
+        }));
+    }
+
+    #[test]
+    fn test_confirm_deployment_needs_hidden_deployment() {
+        let mut state = command_error_state();
+        assert_eq!(
+            state.process_command(Command::ConfirmDeployment),
+            Err(CommandError::DeploymentNotHidden)
+        );
+        assert_eq!(state.active_player, Player::Player1);
+        assert!(state.deployment_confirmed.is_empty());
+        assert!(!state.legal_commands().contains(&Command::ConfirmDeployment));
+    }
+
+    #[test]
+    fn test_attackable_hexes() {
+        let mut state = GameState::new(GameMap::new(20, 20));
+        let position = HexCoord::new(4, 4);
+        state.add_unit(Unit::new(
+            1,
+            UnitType::Shadowsword,
+            Player::Player1,
+            position,
+            Facing::East,
+        ));
+        state.map.tiles.get_mut(&(6, 4)).unwrap().terrain = TerrainType::Impassable;
+
+        let hexes = state.attackable_hexes(1);
+        assert!(!hexes.contains(&position));
+
+        // Beyond the turret sponsons only the fixed cannon reaches, so only its arc counts
+        let sponson_range = UnitType::Shadowsword.weapons()[1].range;
+        assert!(hexes
+            .iter()
+            .filter(|h| h.distance_to(position) > sponson_range)
+            .all(|h| Facing::East.is_in_front_arc(position, *h)));
+        assert!(hexes.contains(&HexCoord::new(4, 9)));
+        assert!(!hexes.contains(&HexCoord::new(-1, 4)));
+        assert!(hexes.contains(&HexCoord::new(2, 4)));
+
+        // The impassable hex itself is visible, but it hides what lies behind
+        assert!(hexes.contains(&HexCoord::new(6, 4)));
+        assert!(!hexes.contains(&HexCoord::new(8, 4)));
+
+        assert!(state.attackable_hexes(99).is_empty());
+    }
+
+    #[test]
+    fn test_rolled_initiative() {
+        let initiative = |mode, seed| -> Vec<Player> {
+            let mut state = command_error_state();
+            state.initiative_mode = mode;
+            state.rng = GameRng::new(seed);
+            (0..12)
+                .map(|_| {
+                    state.end_turn();
+                    state.active_player
+                })
+                .collect()
+        };
+
+        let rolled = initiative(InitiativeMode::Rolled, 7);
+        assert_eq!(rolled, initiative(InitiativeMode::Rolled, 7));
+        assert!(rolled.contains(&Player::Player1) && rolled.contains(&Player::Player2));
+        assert!(rolled.windows(2).any(|pair| pair[0] == pair[1]));
+
+        let alternating = initiative(InitiativeMode::Alternating, 7);
+        assert!(alternating.windows(2).all(|pair| pair[0] != pair[1]));
+        assert_eq!(alternating[0], Player::Player2);
+    }
+
+    #[test]
+    fn test_battle_log_text() {
+        let mut state = command_error_state();
+        state.get_unit_mut(3).unwrap().position = HexCoord::new(4, 0);
+        state.process_command(Command::EndPhase).unwrap();
+        state
+            .process_command(move_to(1, vec![HexCoord::new(0, 0), HexCoord::new(0, 1)]))
+            .unwrap();
+        state.process_command(Command::EndPhase).unwrap();
+        state
+            .process_command(Command::Attack {
+                attacker_id: 1,
+                target_id: 3,
+                weapon_index: 0,
+            })
+            .unwrap();
+        state.process_command(Command::EndTurn).unwrap();
+        state
+            .process_command(move_to(4, vec![HexCoord::new(5, 9), HexCoord::new(5, 8)]))
+            .unwrap();
+
+        assert_eq!(
+            state.battle_log_text(),
+            "Turn 1: Reaver Titan (P1) moved (0, 0) -> (0, 1); \
+             attacked Shadowsword (P2) for 6 damage (destroyed).\n\
+             Turn 2: Krieg Infantry Squad (P2) moved (5, 9) -> (5, 8)."
+        );
+        assert_eq!(GameState::new(GameMap::new(4, 4)).battle_log_text(), "");
+
+        // A volley can both drop shields and get through to the structure
+        let mut state = command_error_state();
+        let volley = |shields_lost, structure_damage| GameEvent::UnitAttacked {
+            attacker_id: 1,
+            target_id: 3,
+            hit: true,
+            shields_lost,
+            structure_damage,
+        };
+        state.events = vec![volley(1, 0), volley(2, 3)];
+        assert_eq!(
+            state.battle_log_text(),
+            "Turn 1: Reaver Titan (P1) attacked Shadowsword (P2), collapsing 1 void shield; \
+             attacked Shadowsword (P2), collapsing 2 void shields and dealing 3 damage."
+        );
+    }
+
+    #[test]
+    fn test_simultaneous_head_on_collision_bounces() {
+        let mut state = command_error_state();
+        state.current_phase = Phase::Movement;
+        state.get_unit_mut(3).unwrap().position = HexCoord::new(4, 2);
+        state.get_unit_mut(1).unwrap().position = HexCoord::new(2, 2);
+        let clash = HexCoord::new(3, 2);
+
+        let p1 = [
+            move_to(1, vec![HexCoord::new(2, 2), clash]),
+            move_to(2, vec![HexCoord::new(1, 0), HexCoord::new(1, 1)]),
+        ];
+        let p2 = [Command::Move {
+            unit_id: 3,
+            path: vec![HexCoord::new(4, 2), clash],
+            final_facing: Facing::West,
+        }];
+
+        // A unit planned by the wrong side rejects the whole batch
+        assert_eq!(
+            state.resolve_simultaneous(&p2, &p1),
+            Err(CommandError::NotOwner(3).to_string())
+        );
+        assert!(!state.get_unit(2).unwrap().has_moved);
+
+        let events = state.resolve_simultaneous(&p1, &p2).unwrap();
+        assert_eq!(
+            events,
+            vec![
+                GameEvent::MoveBounced {
+                    unit_id: 1,
+                    destination: clash
+                },
+                GameEvent::UnitMoved {
+                    unit_id: 2,
+                    from: HexCoord::new(1, 0),
+                    to: HexCoord::new(1, 1),
+                    facing: Facing::East
+                },
+                GameEvent::MoveBounced {
+                    unit_id: 3,
+                    destination: clash
+                },
+            ]
+        );
+        assert_eq!(state.get_unit(1).unwrap().position, HexCoord::new(2, 2));
+        assert_eq!(state.get_unit(3).unwrap().position, HexCoord::new(4, 2));
+        assert!(state.get_unit(1).unwrap().has_moved);
+        assert!(state.get_unit(3).unwrap().has_moved);
+        assert!(state.units_at(clash).is_empty());
+        assert_eq!(
+            state.command_log,
+            vec![Command::Simultaneous {
+                p1_moves: p1.to_vec(),
+                p2_moves: p2.to_vec(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_simultaneous_crossing_paths_bounce() {
+        let mut state = command_error_state();
+        state.current_phase = Phase::Movement;
+        let hex = |q| HexCoord::new(q, 2);
+        state.get_unit_mut(1).unwrap().position = hex(1);
+        state.get_unit_mut(3).unwrap().position = hex(4);
+
+        // The two ends differ, but the units would trade hexes on the way
+        let events = state
+            .resolve_simultaneous(
+                &[move_to(1, vec![hex(1), hex(2), hex(3)])],
+                &[move_to(3, vec![hex(4), hex(3), hex(2)])],
+            )
+            .unwrap();
+        assert_eq!(events.len(), 2);
+        assert!(events
+            .iter()
+            .all(|e| matches!(e, GameEvent::MoveBounced { .. })));
+        assert_eq!(state.get_unit(1).unwrap().position, hex(1));
+        assert_eq!(state.get_unit(3).unwrap().position, hex(4));
+    }
+
+    #[test]
+    fn test_attack_needs_line_of_sight() {
+        let mut state = command_error_state();
+        state.current_phase = Phase::Combat;
+        state.get_unit_mut(3).unwrap().position = HexCoord::new(4, 0);
+        state.map.tiles.get_mut(&(2, 0)).unwrap().terrain = TerrainType::Impassable;
+
+        assert_eq!(
+            state.process_command(Command::Attack {
+                attacker_id: 1,
+                target_id: 3,
+                weapon_index: 0,
+            }),
+            Err(CommandError::NoLineOfSight(3))
+        );
+    }
+
+    #[test]
+    fn test_cover_gives_chance_to_miss() {
+        let mut hits = 0;
+        for seed in 0..200 {
+            let mut state = command_error_state();
+            state.current_phase = Phase::Combat;
+            state.rng = GameRng::new(seed);
+            state.get_unit_mut(3).unwrap().position = HexCoord::new(4, 0);
+            state.map.tiles.get_mut(&(2, 0)).unwrap().terrain = TerrainType::Ruins;
+
+            let events = state
+                .process_command(Command::Attack {
+                    attacker_id: 1,
+                    target_id: 3,
+                    weapon_index: 0,
+                })
+                .unwrap();
+            assert_ne!(state.rng, GameRng::new(seed));
+            if let GameEvent::UnitAttacked { hit: true, .. } = events[0] {
+                hits += 1;
+            }
+        }
+
+        // Ruins obstruct 35% of the line, so roughly 65% of attacks land
+        assert!((100..160).contains(&hits), "hits = {}", hits);
+    }
+
+    #[test]
+    fn test_shot_through_woods() {
+        let mut hits = 0;
+        for seed in 0..200 {
+            let mut state = command_error_state();
+            state.current_phase = Phase::Combat;
+            state.rng = GameRng::new(seed);
+            state.get_unit_mut(3).unwrap().position = HexCoord::new(4, 0);
+            state.map.tiles.get_mut(&(2, 0)).unwrap().terrain = TerrainType::Woods;
+
+            let events = state
+                .process_command(Command::Attack {
+                    attacker_id: 1,
+                    target_id: 3,
+                    weapon_index: 0,
+                })
+                .unwrap();
+            if let GameEvent::UnitAttacked { hit: true, .. } = events[0] {
+                hits += 1;
+            }
+        }
+
+        // Woods hide half the target, so about half the shots land
+        assert!((70..130).contains(&hits), "hits = {}", hits);
+    }
+
+    #[test]
+    fn test_command_error_display() {
+        assert_eq!(
+            CommandError::Occupied(HexCoord::new(2, -1)).to_string(),
+            "Destination (2, -1) occupied"
+        );
+        assert_eq!(
+            CommandError::TargetDestroyed(3).to_string(),
+            "Target 3 already destroyed"
+        );
+    }
+
+    #[test]
+    fn test_movement_and_threat_overlay() {
+        let mut state = command_error_state();
+        state.current_phase = Phase::Movement;
+        let (reachable, threat) = state.movement_and_threat_overlay(2);
+        assert_eq!(
+            reachable,
+            crate::movement::find_reachable(&state, state.get_unit(2).unwrap())
+        );
+
+        // Every threatened hex is in gun range of somewhere the tank can stop
+        let range = UnitType::Shadowsword.weapon_range();
+        assert!(!threat.is_empty());
+        assert!(threat.contains(&HexCoord::new(8, 0)));
+        assert!(threat.iter().all(|hex| reachable
+            .keys()
+            .any(|from| state.map.distance(*from, *hex) <= range)));
+
+        // With its attack spent the tank has no reach left to show
+        state.get_unit_mut(2).unwrap().has_attacked = true;
+        let (reachable, threat) = state.movement_and_threat_overlay(2);
+        assert!(!reachable.is_empty());
+        assert!(threat.is_empty());
+        assert_eq!(state.movement_and_threat_overlay(99).0.len(), 0);
+    }
+
+    #[test]
+    fn test_nearest_enemy_and_ally() {
+        let map = GameMap::new(10, 10);
+        let mut state = GameState::new(map);
+        let place = |id, owner, q, r| {
+            Unit::new(id, UnitType::Shadowsword, owner, HexCoord::new(q, r), Facing::East)
+        };
+        state.add_unit(place(1, Player::Player1, 0, 0));
+        state.add_unit(place(2, Player::Player1, 2, 0));
+        state.add_unit(place(10, Player::Player2, 5, 0));
+        state.add_unit(place(11, Player::Player2, 3, 0));
+        state.add_unit(place(12, Player::Player2, 7, 0));
+
+        assert_eq!(state.nearest_enemy(1), Some((11, 3)));
+        assert_eq!(state.nearest_ally(1), Some((2, 2)));
+        assert_eq!(state.nearest_ally(10), Some((11, 2)));
+
+        // Equidistant enemies resolve to the lowest ID
+        state.get_unit_mut(10).unwrap().position = HexCoord::new(0, 3);
+        assert_eq!(state.nearest_enemy(1), Some((10, 3)));
+
+        // Destroyed units are ignored
+        state.get_unit_mut(10).unwrap().structure = 0;
+        state.get_unit_mut(11).unwrap().structure = 0;
+        assert_eq!(state.nearest_enemy(1), Some((12, 7)));
+        assert_eq!(state.nearest_enemy(99), None);
+    }
+
+    #[test]
+    fn test_activation_gates_acting_units() {
+        let mut state = command_error_state();
+        state.current_phase = Phase::Movement;
+        state.alternating_activation = true;
+        let step = |unit_id, from: (i32, i32), to: (i32, i32)| Command::Move {
+            unit_id,
+            path: vec![HexCoord::new(from.0, from.1), HexCoord::new(to.0, to.1)],
+            final_facing: Facing::East,
+        };
+
+        assert_eq!(
+            state.process_command(step(2, (1, 0), (2, 0))),
+            Err(CommandError::NotActivated(2))
+        );
+        assert_eq!(
+            state.process_command(Command::Activate { unit_id: 1 }),
+            Ok(vec![GameEvent::UnitActivated { unit_id: 1 }])
+        );
+
+        // One activation at a time, and only that unit acts
+        assert_eq!(
+            state.process_command(Command::Activate { unit_id: 2 }),
+            Err(CommandError::ActivationOpen(1))
+        );
+        assert_eq!(
+            state.process_command(step(2, (1, 0), (2, 0))),
+            Err(CommandError::NotActivated(2))
+        );
+        assert!(!state
+            .legal_commands()
+            .iter()
+            .any(|c| c.acting_unit() == Some(2)));
+        state.process_command(step(1, (0, 0), (0, 1))).unwrap();
+        assert_eq!(
+            state.process_command(Command::EndActivation),
+            Ok(vec![GameEvent::ActivationEnded { unit_id: 1 }])
+        );
+
+        // The first unit is spent for the turn; the second may now go
+        assert_eq!(
+            state.process_command(Command::Activate { unit_id: 1 }),
+            Err(CommandError::AlreadyActivated(1))
+        );
+        state
+            .process_command(Command::Activate { unit_id: 2 })
+            .unwrap();
+        state.process_command(step(2, (1, 0), (2, 0))).unwrap();
+        state.process_command(Command::EndActivation).unwrap();
+        assert_eq!(
+            state.process_command(Command::EndActivation),
+            Err(CommandError::NoActivation)
+        );
+
+        state.process_command(Command::EndTurn).unwrap();
+        assert!(!state.get_unit(1).unwrap().activated);
+        assert!(!state.get_unit(2).unwrap().activated);
+    }
+
+    #[test]
+    fn test_is_engaged() {
+        let map = GameMap::new(10, 10);
+        let mut state = GameState::new_at(map, 1, Phase::Movement, Player::Player1);
+        let place = |id, owner, q, r| {
+            Unit::new(
+                id,
+                UnitType::Shadowsword,
+                owner,
+                HexCoord::new(q, r),
+                Facing::East,
+            )
+        };
+        state.add_unit(place(1, Player::Player1, 2, 0));
+        state.add_unit(place(2, Player::Player1, 2, 1));
+        state.add_unit(place(10, Player::Player2, 3, 0));
+
+        assert!(state.is_engaged(1));
+        assert!(state.is_engaged(10));
+        assert!(!state.is_engaged(99));
+
+        // Step clear of the enemy; the friendly tank alongside doesn't count
+        state
+            .process_command(Command::Move {
+                unit_id: 1,
+                path: vec![HexCoord::new(2, 0), HexCoord::new(1, 0)],
+                final_facing: Facing::East,
+            })
+            .unwrap();
+        assert!(!state.is_engaged(1));
+        assert!(state.is_engaged(2));
+
+        state.get_unit_mut(10).unwrap().structure = 0;
+        assert!(!state.is_engaged(2));
+    }
+
+    #[test]
+    fn test_events_since() {
+        let map = GameMap::new(10, 10);
+        let mut state = GameState::new(map);
+        assert!(state.events_since(0).is_empty());
+
+        state.process_command(Command::EndPhase).unwrap();
+        let seen = state.events.len();
+        state.process_command(Command::EndTurn).unwrap();
+
+        assert_eq!(state.events_since(0).len(), state.events.len());
+        let tail = state.events_since(seen);
+        assert_eq!(tail.len(), state.events.len() - seen);
+        assert!(matches!(tail[0], GameEvent::PhaseChanged { .. }));
+        assert!(matches!(tail[1], GameEvent::TurnChanged { turn: 2 }));
+        assert!(state.events_since(state.events.len() + 5).is_empty());
+    }
+
+    #[test]
+    fn test_generate_random_is_deterministic() {
+        let weights = TerrainWeights::default();
+        let a = GameMap::generate_random(12, 10, 1234, &weights);
+        let b = GameMap::generate_random(12, 10, 1234, &weights);
+        let c = GameMap::generate_random(12, 10, 4321, &weights);
+
+        assert_eq!(a.tiles.len(), 120);
+        assert_eq!(a.tiles, b.tiles);
+        assert_ne!(a.tiles, c.tiles);
+        assert!(a.all_hexes().iter().any(|hex| a.terrain_at(*hex) != TerrainType::Clear));
+    }
+
+    #[test]
+    fn test_generate_random_mirrored() {
+        let map = GameMap::generate_random(12, 10, 99, &TerrainWeights::default());
+        for hex in map.all_hexes() {
+            let mirror = map.mirror(hex);
+            assert!(map.is_valid(mirror));
+            assert_eq!(map.get_tile(hex), map.get_tile(mirror));
+        }
+
+        let weights = TerrainWeights {
+            clear: 0,
+            rough: 0,
+            woods: 1,
+            water: 0,
+            ruins: 0,
+            mirrored: false,
+        };
+        let woods = GameMap::generate_random(6, 6, 5, &weights);
+        assert!(woods
+            .all_hexes()
+            .iter()
+            .all(|hex| woods.terrain_at(*hex) == TerrainType::Woods));
+    }
+
+    #[test]
+    fn test_standing_order_advances_each_turn() {
+        let map = GameMap::new(20, 10);
+        let mut state = GameState::new(map);
+        state.add_unit(Unit::new(
+            1,
+            UnitType::Shadowsword,
+            Player::Player1,
+            HexCoord::new(0, 0),
+            Facing::East,
+        ));
+        let destination = HexCoord::new(14, 0);
+
+        state
+            .process_command(Command::SetOrder {
+                unit_id: 1,
+                destination,
+            })
+            .unwrap();
+        assert_eq!(state.orders.get(&1), Some(&destination));
+
+        // Entering Player1's movement phase moves the unit a full turn's worth
+        let events = state.process_command(Command::EndPhase).unwrap();
+        assert!(events.contains(&GameEvent::UnitMoved {
+            unit_id: 1,
+            from: HexCoord::new(0, 0),
+            to: HexCoord::new(5, 0),
+            facing: Facing::East,
+        }));
+        assert!(state.get_unit(1).unwrap().has_moved);
+
+        // Player2's turn leaves the unit alone
+        state.process_command(Command::EndTurn).unwrap();
+        assert_eq!(state.get_unit(1).unwrap().position, HexCoord::new(5, 0));
+
+        state.process_command(Command::EndTurn).unwrap();
+        assert_eq!(state.get_unit(1).unwrap().position, HexCoord::new(10, 0));
+        assert!(state.orders.contains_key(&1));
+
+        state.process_command(Command::EndTurn).unwrap();
+        state.process_command(Command::EndTurn).unwrap();
+        assert_eq!(state.get_unit(1).unwrap().position, destination);
+        assert!(!state.orders.contains_key(&1));
+    }
+
+    #[test]
+    fn test_standing_order_to_occupied_hex() {
+        let mut state = GameState::new(GameMap::new(20, 10));
+        state.add_unit(Unit::new(
+            1,
+            UnitType::Shadowsword,
+            Player::Player1,
+            HexCoord::new(0, 0),
+            Facing::East,
+        ));
+        state.add_unit(Unit::new(
+            2,
+            UnitType::KriegSquad,
+            Player::Player1,
+            HexCoord::new(8, 0),
+            Facing::East,
+        ));
+        let destination = HexCoord::new(8, 0);
+        state
+            .process_command(Command::SetOrder {
+                unit_id: 1,
+                destination,
+            })
+            .unwrap();
+
+        // The squad holds the hex, so the tank closes in as far as it can
+        state.process_command(Command::EndPhase).unwrap();
+        assert_eq!(state.get_unit(1).unwrap().position, HexCoord::new(5, 0));
+        state.process_command(Command::EndTurn).unwrap();
+        state.process_command(Command::EndTurn).unwrap();
+        assert_eq!(state.get_unit(1).unwrap().position, HexCoord::new(7, 0));
+
+        // Once it can get no closer the order is dropped
+        state.process_command(Command::EndTurn).unwrap();
+        let events = state.process_command(Command::EndTurn).unwrap();
+        assert!(events.contains(&GameEvent::OrderCancelled {
+            unit_id: 1,
+            destination
+        }));
+        assert!(state.orders.is_empty());
+        assert_eq!(state.get_unit(1).unwrap().position, HexCoord::new(7, 0));
+    }
+
+    #[test]
+    fn test_manual_move_clears_standing_order() {
+        let mut state = command_error_state();
+        state.current_phase = Phase::Movement;
+        state
+            .process_command(Command::SetOrder {
+                unit_id: 2,
+                destination: HexCoord::new(5, 5),
+            })
+            .unwrap();
+
+        state
+            .process_command(move_to(2, vec![HexCoord::new(1, 0), HexCoord::new(2, 0)]))
+            .unwrap();
+        assert!(state.orders.is_empty());
+
+        // Next turn the tank stays where it was sent
+        state.process_command(Command::EndTurn).unwrap();
+        state.process_command(Command::EndTurn).unwrap();
+        assert_eq!(state.get_unit(2).unwrap().position, HexCoord::new(2, 0));
+    }
+
+    #[test]
+    fn test_refresh_unit() {
+        let map = GameMap::new(10, 10);
+        let mut state = GameState::new(map);
+        state.current_phase = Phase::Movement;
+        state.add_unit(Unit::new(
+            1,
+            UnitType::Shadowsword,
+            Player::Player1,
+            HexCoord::new(0, 0),
+            Facing::East,
+        ));
+        state.add_unit(Unit::new(
+            2,
+            UnitType::Shadowsword,
+            Player::Player2,
+            HexCoord::new(5, 5),
+            Facing::West,
+        ));
+
+        let first_move = Command::Move {
+            unit_id: 1,
+            path: vec![HexCoord::new(0, 0), HexCoord::new(1, 0)],
+            final_facing: Facing::East,
+        };
+        assert!(state.process_command(first_move).is_ok());
+
+        let second_move = Command::Move {
+            unit_id: 1,
+            path: vec![HexCoord::new(1, 0), HexCoord::new(2, 0)],
+            final_facing: Facing::East,
+        };
+        assert!(state.process_command(second_move.clone()).is_err());
+
+        state.refresh_unit(1).unwrap();
+        let unit = state.get_unit(1).unwrap();
+        assert!(!unit.has_moved);
+        assert_eq!(unit.movement_remaining, unit.unit_type.base_movement());
+        assert!(state.process_command(second_move).is_ok());
+        assert_eq!(state.get_unit(1).unwrap().position, HexCoord::new(2, 0));
+
+        assert_eq!(state.refresh_unit(2), Err(CommandError::NotOwner(2)));
+        assert_eq!(state.refresh_unit(99), Err(CommandError::UnitNotFound(99)));
+    }
+
+    #[test]
+    fn test_auto_skip_empty_combat_phase() {
+        let map = GameMap::new(12, 12);
+        let mut state = GameState::new(map);
+        state.current_phase = Phase::Movement;
+        state.add_unit(Unit::new(
+            1,
+            UnitType::ReaverTitan,
+            Player::Player1,
+            HexCoord::new(0, 0),
+            Facing::East,
+        ));
+        state.add_unit(Unit::new(
+            2,
+            UnitType::ReaverTitan,
+            Player::Player2,
+            HexCoord::new(5, 10),
+            Facing::West,
+        ));
+
+        // Without the flag, combat still waits for a manual EndPhase
+        let mut manual = state.clone();
+        manual.process_command(Command::EndPhase).unwrap();
+        assert_eq!(manual.current_phase, Phase::Combat);
+
+        // Nobody is in range, so combat is skipped straight through End
+        state.auto_skip_empty_phases = true;
+        let events = state.process_command(Command::EndPhase).unwrap();
+        assert_eq!(
+            events,
+            vec![
+                GameEvent::PhaseChanged {
+                    from: Phase::Movement,
+                    to: Phase::Combat,
+                },
+                GameEvent::TurnChanged { turn: 2 },
+                GameEvent::PhaseChanged {
+                    from: Phase::Combat,
+                    to: Phase::Movement,
+                },
+            ]
+        );
+        assert_eq!(state.active_player, Player::Player2);
+        assert_eq!(state.current_phase, Phase::Movement);
+
+        // A target in range keeps the combat phase open
+        state.get_unit_mut(1).unwrap().position = HexCoord::new(3, 8);
+        state.process_command(Command::EndPhase).unwrap();
+        assert_eq!(state.current_phase, Phase::Combat);
+    }
+
+    #[test]
+    fn test_main_weapon_cooldown() {
+        let map = GameMap::new(10, 10);
+        let mut state = GameState::new(map);
+        state.current_phase = Phase::Combat;
+        state.add_unit(Unit::new(
+            1,
+            UnitType::WarlordTitan,
+            Player::Player1,
+            HexCoord::new(0, 0),
+            Facing::East,
+        ));
+        state.add_unit(Unit::new(
+            2,
+            UnitType::ReaverTitan,
+            Player::Player2,
+            HexCoord::new(3, 0),
+            Facing::West,
+        ));
+        let fire = Command::Attack {
+            attacker_id: 1,
+            target_id: 2,
+            weapon_index: 0,
+        };
+        let combat_turn = |state: &mut GameState| {
+            state.process_command(Command::EndTurn).unwrap();
+            state.process_command(Command::EndTurn).unwrap();
+            state.current_phase = Phase::Combat;
+        };
+
+        state.process_command(fire.clone()).unwrap();
+        assert_eq!(state.get_unit(1).unwrap().main_weapon_cooldown, 3);
+
+        // Player1's next turn: still cooling
+        combat_turn(&mut state);
+        assert_eq!(
+            state.process_command(fire.clone()),
+            Err(CommandError::WeaponCoolingDown {
+                unit_id: 1,
+                turns: 1
+            })
+        );
+
+        // The turn after, the gun is ready again
+        combat_turn(&mut state);
+        assert_eq!(state.get_unit(1).unwrap().main_weapon_cooldown, 0);
+        assert!(state.process_command(fire).is_ok());
+    }
+
+    #[test]
+    fn test_tank_main_gun_fires_every_turn() {
+        let mut state = command_error_state();
+        state.current_phase = Phase::Combat;
+        state.get_unit_mut(3).unwrap().position = HexCoord::new(4, 0);
+        state.get_unit_mut(3).unwrap().structure = 50;
+        let fire = Command::Attack {
+            attacker_id: 2,
+            target_id: 3,
+            weapon_index: 0,
+        };
+
+        state.process_command(fire.clone()).unwrap();
+        assert_eq!(state.get_unit(2).unwrap().main_weapon_cooldown, 0);
+
+        // Player1's next combat phase, and the Volcano fires again
+        state.process_command(Command::EndTurn).unwrap();
+        state.process_command(Command::EndTurn).unwrap();
+        state.current_phase = Phase::Combat;
+        assert!(state.process_command(fire).is_ok());
+    }
+
+    #[test]
+    fn test_pixel_bounds_enclose_every_corner() {
+        let map = GameMap::new(3, 3);
+        let size = 10.0;
+        let (min_x, min_y, max_x, max_y) = map.pixel_bounds(size);
+        let eps = 1e-9;
+
+        let mut corners = Vec::new();
+        for hex in map.all_hexes() {
+            let (x, y) = hex.to_pixel(size);
+            corners.extend(hex_corners(x, y, size));
+        }
+        assert_eq!(corners.len(), 54);
+        for &(x, y) in &corners {
+            assert!(x >= min_x - eps && x <= max_x + eps);
+            assert!(y >= min_y - eps && y <= max_y + eps);
+        }
+
+        // The box is tight: some corner touches each edge
+        assert!(corners.iter().any(|c| (c.0 - min_x).abs() < eps));
+        assert!(corners.iter().any(|c| (c.1 - min_y).abs() < eps));
+        assert!(corners.iter().any(|c| (c.0 - max_x).abs() < eps));
+        assert!(corners.iter().any(|c| (c.1 - max_y).abs() < eps));
+
+        // Odd rows shift half a hex right: 3.5 hex widths across, 5 sizes tall
+        let width = 3.0_f64.sqrt() * size;
+        assert!((max_x - min_x - 3.5 * width).abs() < eps);
+        assert!((max_y - min_y - 5.0 * size).abs() < eps);
+    }
+
+    #[test]
+    fn test_destroying_last_unit_ends_game() {
+        let map = GameMap::new(10, 10);
+        let mut state = GameState::new(map);
+        state.current_phase = Phase::Combat;
+        state.add_unit(Unit::new(
+            1,
+            UnitType::WarlordTitan,
+            Player::Player1,
+            HexCoord::new(0, 0),
+            Facing::East,
+        ));
+        let mut last = Unit::new(
+            2,
+            UnitType::KriegSquad,
+            Player::Player2,
+            HexCoord::new(2, 0),
+            Facing::West,
+        );
+        last.structure = 1;
+        state.add_unit(last);
+
+        let events = state
+            .process_command(Command::Attack {
+                attacker_id: 1,
+                target_id: 2,
+                weapon_index: 0,
+            })
+            .unwrap();
+
+        assert_eq!(
+            &events[1..],
+            &[
+                GameEvent::UnitDestroyed { unit_id: 2 },
+                GameEvent::PlayerEliminated {
+                    player: Player::Player2,
+                },
+                GameEvent::GameOver {
+                    winner: Some(Player::Player1),
+                },
+            ]
+        );
+        assert!(state.game_over);
+        assert_eq!(state.winner, Some(Player::Player1));
+
+        // Victory is only announced once
+        assert!(state.check_victory().is_empty());
+    }
+
+    #[test]
+    fn test_annihilation_is_the_default_victory() {
+        let mut state = command_error_state();
+        assert_eq!(
+            state.victory_conditions,
+            vec![VictoryCondition::Annihilation]
+        );
+
+        // Without annihilation among the conditions, wiping a side out wins nothing
+        state.victory_conditions = vec![VictoryCondition::SurviveTurns(5)];
+        state.get_unit_mut(3).unwrap().structure = 0;
+        state.get_unit_mut(4).unwrap().structure = 0;
+        assert!(state.check_victory().is_empty());
+
+        state.victory_conditions = vec![VictoryCondition::Annihilation];
+        assert_eq!(
+            state.check_victory(),
+            vec![
+                GameEvent::PlayerEliminated {
+                    player: Player::Player2,
+                },
+                GameEvent::GameOver {
+                    winner: Some(Player::Player1),
+                },
+            ]
+        );
+        assert_eq!(state.winner, Some(Player::Player1));
+    }
+
+    #[test]
+    fn test_defender_wins_by_surviving() {
+        let mut state = command_error_state();
+        state.current_phase = Phase::Movement;
+        state.victory_conditions = vec![
+            VictoryCondition::Annihilation,
+            VictoryCondition::SurviveTurns(3),
+        ];
+
+        for _ in 0..2 {
+            let events = state.process_command(Command::EndTurn).unwrap();
+            assert!(!events
+                .iter()
+                .any(|e| matches!(e, GameEvent::GameOver { .. })));
+        }
+        assert_eq!(state.current_turn, 3);
+
+        // Turn 3 ends with the defender still on the board
+        let events = state.process_command(Command::EndTurn).unwrap();
+        assert_eq!(
+            events.last(),
+            Some(&GameEvent::GameOver {
+                winner: Some(Player::Player2)
+            })
+        );
+        assert!(!events
+            .iter()
+            .any(|e| matches!(e, GameEvent::PlayerEliminated { .. })));
+        assert!(state.game_over);
+        assert_eq!(state.winner, Some(Player::Player2));
+    }
+
+    #[test]
+    fn test_points_and_exit_victories() {
+        let mut state = command_error_state();
+        let krieg = UnitType::KriegSquad.base_structure();
+        state.get_unit_mut(4).unwrap().structure = 0;
+        assert_eq!(state.victory_points(Player::Player1), krieg);
+        assert_eq!(state.victory_points(Player::Player2), 0);
+        assert_eq!(
+            state.condition_winner(&VictoryCondition::VictoryPoints(krieg + 1)),
+            None
+        );
+        assert_eq!(
+            state.condition_winner(&VictoryCondition::VictoryPoints(krieg)),
+            Some(Player::Player1)
+        );
+
+        // Both attacking units start on the north row but only one on the west edge
+        let north = VictoryCondition::ExitUnits {
+            edge: MapEdge::North,
+            count: 2,
+        };
+        let west = VictoryCondition::ExitUnits {
+            edge: MapEdge::West,
+            count: 2,
+        };
+        assert_eq!(state.condition_winner(&north), Some(Player::Player1));
+        assert_eq!(state.condition_winner(&west), None);
+        assert!(state
+            .map
+            .is_on_edge(HexCoord::from_offset(9, 5), MapEdge::East));
+        assert!(!state
+            .map
+            .is_on_edge(HexCoord::from_offset(8, 5), MapEdge::East));
+        assert!(state
+            .map
+            .is_on_edge(HexCoord::from_offset(4, 9), MapEdge::South));
+
+        state.victory_conditions = vec![west, north];
+        assert_eq!(
+            state.check_victory(),
+            vec![GameEvent::GameOver {
+                winner: Some(Player::Player1)
+            }]
+        );
+    }
+
+    #[test]
+    fn test_reinforcements_arrive_on_schedule() {
+        let mut state = command_error_state();
+        state.current_phase = Phase::Movement;
+        let arrival = HexCoord::new(0, 5);
+        let reinforcement = |id| {
+            Unit::new(
+                id,
+                UnitType::KriegSquad,
+                Player::Player1,
+                HexCoord::new(0, 0),
+                Facing::East,
+            )
+        };
+        state.add_reinforcement(3, reinforcement(5), arrival);
+        state.add_reinforcement(3, reinforcement(6), HexCoord::new(5, 9));
+
+        state.process_command(Command::EndTurn).unwrap();
+        assert_eq!(state.current_turn, 2);
+        assert!(state.get_unit(5).is_none());
+
+        let events = state.process_command(Command::EndTurn).unwrap();
+        assert_eq!(state.current_turn, 3);
+        assert!(events.contains(&GameEvent::UnitArrived {
+            unit_id: 5,
+            position: arrival,
+        }));
+        assert_eq!(state.get_unit(5).unwrap().position, arrival);
+
+        // The enemy squad holds the other hex, so that unit waits a turn
+        assert!(state.get_unit(6).is_none());
+        assert_eq!(state.reinforcements.len(), 1);
+        state.get_unit_mut(4).unwrap().position = HexCoord::new(6, 9);
+        state.process_command(Command::EndTurn).unwrap();
+        assert_eq!(state.get_unit(6).unwrap().position, HexCoord::new(5, 9));
+        assert!(state.reinforcements.is_empty());
+    }
+
+    #[test]
+    fn test_quiet_turns_end_in_a_draw() {
+        let mut state = command_error_state();
+        state.current_phase = Phase::Movement;
+        state.stalemate_limit = 2;
+
+        state.process_command(Command::EndTurn).unwrap();
+        assert_eq!(state.turns_without_combat(), 1);
+
+        // Any attack, hit or miss, restarts the count
+        state.current_phase = Phase::Combat;
+        state.active_player = Player::Player1;
+        state
+            .process_command(Command::Attack {
+                attacker_id: 1,
+                target_id: 3,
+                weapon_index: 1,
+            })
+            .unwrap();
+        let events = state.process_command(Command::EndTurn).unwrap();
+        assert_eq!(state.turns_without_combat(), 0);
+        assert!(!events
+            .iter()
+            .any(|e| matches!(e, GameEvent::GameOver { .. })));
+
+        state.process_command(Command::EndTurn).unwrap();
+        assert!(!state.game_over);
+        let events = state.process_command(Command::EndTurn).unwrap();
+        assert_eq!(events.last(), Some(&GameEvent::GameOver { winner: None }));
+        assert!(state.game_over);
+        assert_eq!(state.winner, None);
+        assert!(state.battle_log_text().ends_with("Game drawn."));
+    }
+
+    #[test]
+    fn test_commands_rejected_after_victory() {
+        let mut state = command_error_state();
+        state.current_phase = Phase::Combat;
+        state.get_unit_mut(4).unwrap().structure = 0;
+        state.get_unit_mut(3).unwrap().position = HexCoord::new(4, 0);
+        state.get_unit_mut(3).unwrap().structure = 1;
+
+        while !state.game_over {
+            state.get_unit_mut(1).unwrap().has_attacked = false;
+            state.get_unit_mut(1).unwrap().main_weapon_cooldown = 0;
+            state
+                .process_command(Command::Attack {
+                    attacker_id: 1,
+                    target_id: 3,
+                    weapon_index: 0,
+                })
+                .unwrap();
+        }
+        assert_eq!(state.winner, Some(Player::Player1));
+        let turn = state.current_turn;
+        let position = state.get_unit(2).unwrap().position;
+
+        state.current_phase = Phase::Movement;
+        let moved = state.process_command(move_to(2, vec![position, HexCoord::new(1, 1)]));
+        assert_eq!(moved, Err(CommandError::GameOver));
+        assert_eq!(
+            moved.unwrap_err().to_string(),
+            "The game is over; no further commands are accepted"
+        );
+        assert_eq!(
+            state.process_command(Command::EndTurn),
+            Err(CommandError::GameOver)
+        );
+        assert_eq!(state.current_turn, turn);
+        assert_eq!(state.get_unit(2).unwrap().position, position);
+    }
+
+    #[test]
+    fn test_targetable_neighbors_respect_weapon_arc() {
+        let position = HexCoord::new(4, 4);
+        let tank = Unit::new(
+            1,
+            UnitType::Shadowsword,
+            Player::Player1,
+            position,
+            Facing::East,
+        );
+        assert_eq!(UnitType::Shadowsword.weapon_arc(), WeaponArc::Front);
+
+        let targets = tank.targetable_neighbors();
+        assert_eq!(targets.len(), 3);
+        assert!(targets.contains(&HexCoord::new(5, 4)));
+        assert!(targets.contains(&HexCoord::new(5, 3)));
+        assert!(targets.contains(&HexCoord::new(4, 5)));
+        assert!(targets
+            .iter()
+            .all(|hex| Facing::East.is_in_front_arc(position, *hex)));
+
+        let titan = Unit::new(
+            2,
+            UnitType::ReaverTitan,
+            Player::Player1,
+            position,
+            Facing::East,
+        );
+        assert_eq!(titan.targetable_neighbors().len(), 6);
+    }
+
+    #[test]
+    fn test_simulate_leaves_rng_untouched() {
+        let mut state = command_error_state();
+        state.current_phase = Phase::Combat;
+        state.get_unit_mut(3).unwrap().position = HexCoord::new(4, 0);
+        let attack = Command::Attack {
+            attacker_id: 1,
+            target_id: 3,
+            weapon_index: 0,
+        };
+        let seed = state.rng.state();
+
+        let (preview, first) = state.simulate(attack.clone()).unwrap();
+        assert_ne!(preview.rng.state(), seed);
+        for _ in 0..3 {
+            let (_, events) = state.simulate(attack.clone()).unwrap();
+            assert_eq!(events, first);
+        }
+        assert_eq!(state.rng.state(), seed);
+        assert!(!state.get_unit(1).unwrap().has_attacked);
+
+        let real = state.process_command(attack).unwrap();
+        assert_eq!(real, first);
+        assert_ne!(state.rng.state(), seed);
+    }
+
+    #[test]
+    fn test_max_stack_allows_friendly_sharing() {
+        let map = GameMap::new(10, 10);
+        let mut state = GameState::new(map);
+        state.current_phase = Phase::Movement;
+        state.max_stack = 2;
+        for (id, q) in [(1, 0), (2, 1), (3, 2)] {
+            state.add_unit(Unit::new(
+                id,
+                UnitType::KriegSquad,
+                Player::Player1,
+                HexCoord::new(q, 0),
+                Facing::East,
+            ));
+        }
+        state.add_unit(Unit::new(
+            4,
+            UnitType::KriegSquad,
+            Player::Player2,
+            HexCoord::new(1, 1),
+            Facing::West,
+        ));
+        let shared = HexCoord::new(1, 0);
+
+        state
+            .process_command(move_to(1, vec![HexCoord::new(0, 0), shared]))
+            .unwrap();
+        assert_eq!(state.units_at(shared).len(), 2);
+
+        assert_eq!(
+            state.process_command(move_to(3, vec![HexCoord::new(2, 0), shared])),
+            Err(CommandError::Occupied(shared))
+        );
+
+        // Enemies never share, whatever the stacking limit
+        state.get_unit_mut(1).unwrap().position = HexCoord::new(0, 0);
+        assert!(!state.has_room(shared, 4));
+        assert!(state.has_room(shared, 3));
+    }
+
+    #[test]
+    fn test_units_in_zone() {
+        let mut state = command_error_state();
+        for q in 0..3 {
+            state.map.tiles.get_mut(&(q, 0)).unwrap().deployment_zone = Some(Player::Player1);
+        }
+        state.map.tiles.get_mut(&(8, 0)).unwrap().deployment_zone = Some(Player::Player2);
+
+        assert_eq!(state.units_in_zone(Player::Player1), vec![1, 2]);
+        assert_eq!(state.units_in_zone(Player::Player2), vec![3]);
+
+        // Pulling out of the zone drops the unit
+        state.get_unit_mut(2).unwrap().position = HexCoord::new(4, 2);
+        assert_eq!(state.units_in_zone(Player::Player1), vec![1]);
+        state.get_unit_mut(3).unwrap().structure = 0;
+        assert!(state.units_in_zone(Player::Player2).is_empty());
+    }
+
+    #[test]
+    fn test_validate_deployment_reports_every_issue() {
+        let mut state = command_error_state();
+        state.get_unit_mut(2).unwrap().position = HexCoord::new(0, 0);
+        state.get_unit_mut(3).unwrap().position = HexCoord::new(-5, 0);
+
+        assert_eq!(
+            state.validate_deployment(),
+            vec![
+                DeploymentIssue::Occupied {
+                    unit_id: 1,
+                    position: HexCoord::new(0, 0),
+                },
+                DeploymentIssue::Occupied {
+                    unit_id: 2,
+                    position: HexCoord::new(0, 0),
+                },
+                DeploymentIssue::OffMap {
+                    unit_id: 3,
+                    position: HexCoord::new(-5, 0),
+                },
+            ]
+        );
+
+        // Once Player1 has a zone, units outside it are flagged too
+        state.get_unit_mut(2).unwrap().position = HexCoord::new(1, 0);
+        state.get_unit_mut(3).unwrap().position = HexCoord::new(8, 0);
+        state.map.tiles.get_mut(&(0, 0)).unwrap().deployment_zone = Some(Player::Player1);
+        assert_eq!(
+            state.validate_deployment(),
+            vec![DeploymentIssue::OutOfZone {
+                unit_id: 2,
+                position: HexCoord::new(1, 0),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_bombard_occupied_and_empty_hexes() {
+        let mut state = command_error_state();
+        state.current_phase = Phase::Combat;
+        state.get_unit_mut(3).unwrap().position = HexCoord::new(4, 0);
+        let shields = state.get_unit(3).unwrap().void_shields;
+
+        let events = state
+            .process_command(Command::Bombard {
+                attacker_id: 1,
+                target: HexCoord::new(4, 0),
+            })
+            .unwrap();
+        assert_eq!(
+            events[0],
+            GameEvent::Bombarded {
+                attacker_id: 1,
+                target: HexCoord::new(4, 0),
+            }
+        );
+        assert!(matches!(
+            events[1],
+            GameEvent::UnitAttacked {
+                target_id: 3,
+                hit: true,
+                ..
+            }
+        ));
+        let target = state.get_unit(3).unwrap();
+        assert!(
+            target.void_shields < shields || target.structure < target.unit_type.base_structure()
+        );
+
+        let events = state
+            .process_command(Command::Bombard {
+                attacker_id: 2,
+                target: HexCoord::new(3, 2),
+            })
+            .unwrap();
+        assert_eq!(
+            events,
+            vec![GameEvent::Bombarded {
+                attacker_id: 2,
+                target: HexCoord::new(3, 2),
+            }]
+        );
+        assert!(state.get_unit(2).unwrap().has_attacked);
+
+        state.get_unit_mut(1).unwrap().has_attacked = false;
+        state.get_unit_mut(1).unwrap().main_weapon_cooldown = 0;
+        assert_eq!(
+            state.process_command(Command::Bombard {
+                attacker_id: 1,
+                target: HexCoord::new(-3, 0),
+            }),
+            Err(CommandError::TargetOffMap(HexCoord::new(-3, 0)))
+        );
+    }
+
+    #[test]
+    fn test_bombard_respects_fixed_arc() {
+        let mut state = command_error_state();
+        state.current_phase = Phase::Combat;
+        state.get_unit_mut(2).unwrap().position = HexCoord::new(5, 5);
+        let bombard = |q, r| Command::Bombard {
+            attacker_id: 2,
+            target: HexCoord::new(q, r),
+        };
+
+        // The hull-mounted Volcano can't shell a hex behind the tank
+        assert_eq!(
+            state.process_command(bombard(3, 5)),
+            Err(CommandError::HexOutOfArc(HexCoord::new(3, 5)))
+        );
+        assert!(!state.get_unit(2).unwrap().has_attacked);
+
+        state.process_command(bombard(7, 5)).unwrap();
+        assert!(state.get_unit(2).unwrap().has_attacked);
+    }
+
+    #[test]
+    fn test_repair_restores_structure_up_to_max() {
+        let mut state = command_error_state();
+        state.current_phase = Phase::Combat;
+        state.add_unit(Unit::new(
+            5,
+            UnitType::KriegSquad,
+            Player::Player1,
+            HexCoord::new(2, 0),
+            Facing::East,
+        ));
+        let repair = |engineer_id| Command::Repair {
+            engineer_id,
+            target_id: 2,
+        };
+
+        assert_eq!(
+            state.process_command(repair(5)),
+            Err(CommandError::FullStructure(2))
+        );
+        assert_eq!(
+            state.process_command(repair(1)),
+            Err(CommandError::CannotRepair(1))
+        );
+
+        state.get_unit_mut(2).unwrap().structure = 3;
+        assert_eq!(
+            state.process_command(repair(5)),
+            Ok(vec![GameEvent::UnitRepaired {
+                engineer_id: 5,
+                target_id: 2,
+                amount: 2,
+            }])
+        );
+        assert_eq!(state.get_unit(2).unwrap().structure, 5);
+        assert!(state.get_unit(5).unwrap().has_attacked);
+        assert_eq!(
+            state.process_command(repair(5)),
+            Err(CommandError::AlreadyAttacked(5))
+        );
+
+        // The last point tops the tank off without going over
+        state.get_unit_mut(5).unwrap().has_attacked = false;
+        state.process_command(repair(5)).unwrap();
+        let tank = state.get_unit(2).unwrap();
+        assert_eq!(tank.structure, tank.unit_type.base_structure());
+    }
+
+    #[test]
+    fn test_bombard_spares_friends_without_friendly_fire() {
+        let mut state = command_error_state();
+        state.current_phase = Phase::Combat;
+        state.max_stack = 2;
+        state.get_unit_mut(2).unwrap().position = HexCoord::new(4, 0);
+        state.get_unit_mut(3).unwrap().position = HexCoord::new(4, 0);
+        let mut spared = state.clone();
+        spared.friendly_fire = false;
+
+        let bombard = Command::Bombard {
+            attacker_id: 1,
+            target: HexCoord::new(4, 0),
+        };
+        let hit_ids = |events: Vec<GameEvent>| {
+            events
+                .iter()
+                .filter_map(|event| match event {
+                    GameEvent::UnitAttacked { target_id, .. } => Some(*target_id),
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            hit_ids(state.process_command(bombard.clone()).unwrap()),
+            vec![2, 3]
+        );
+        assert_eq!(hit_ids(spared.process_command(bombard).unwrap()), vec![3]);
+        let tank = spared.get_unit(2).unwrap();
+        assert_eq!(tank.structure, tank.unit_type.base_structure());
+        assert_eq!(tank.void_shields, 0);
+        let enemy = spared.get_unit(3).unwrap();
+        assert!(enemy.structure < enemy.unit_type.base_structure());
+    }
+
+    #[test]
+    fn test_units_within_radius() {
+        let mut state = command_error_state();
+        let center = HexCoord::new(4, 0);
+        state.get_unit_mut(1).unwrap().position = HexCoord::new(2, 0);
+        state.get_unit_mut(2).unwrap().position = HexCoord::new(1, 0);
+        state.get_unit_mut(3).unwrap().position = HexCoord::new(4, 2);
+
+        let ids = |units: Vec<&Unit>| units.iter().map(|u| u.id).collect::<Vec<_>>();
+        assert_eq!(ids(state.units_within(center, 2)), vec![1, 3]);
+        assert_eq!(ids(state.units_within(center, 3)), vec![1, 2, 3]);
+        assert!(state.units_within(center, 0).is_empty());
+
+        state.get_unit_mut(3).unwrap().structure = 0;
+        assert_eq!(ids(state.units_within(center, 2)), vec![1]);
+    }
+
+    #[test]
+    fn test_occupancy_matches_scans() {
+        let mut state = command_error_state();
+        state.map = GameMap::new_wrapped(10, 10, Wrap::Horizontal).unwrap();
+        let spots = [(3, 2), (3, 2), (9, 4), (0, 4), (6, 7), (2, 9), (7, 1)];
+        for (i, &(col, row)) in spots.iter().enumerate() {
+            state.add_unit(Unit::new(
+                10 + i as u32,
+                UnitType::KriegSquad,
+                Player::Player1,
+                HexCoord::from_offset(col, row),
+                Facing::East,
+            ));
+        }
+        state.get_unit_mut(12).unwrap().structure = 0;
+        state.get_unit_mut(13).unwrap().embarked_on = Some(2);
+
+        let index = state.occupancy();
+        assert_eq!(index.at(HexCoord::from_offset(3, 2)), &[10, 11]);
+        assert!(index.at(HexCoord::from_offset(9, 4)).is_empty());
+
+        for (col, row) in [(0, 0), (3, 3), (9, 4), (5, 9)] {
+            let center = HexCoord::from_offset(col, row);
+            for radius in 0..=6 {
+                let mut scanned: Vec<u32> = state
+                    .units_within(center, radius)
+                    .iter()
+                    .map(|u| u.id)
+                    .collect();
+                scanned.sort_unstable();
+                assert_eq!(index.units_in_range(&state.map, center, radius), scanned);
+            }
+        }
+
+        // Unit 4 sits at (5, 9) and unit 16 at (7, 1)
+        assert_eq!(index.units_in_rect((2, 1), (7, 2)), vec![10, 11, 16]);
+        assert_eq!(index.units_in_rect((0, 8), (9, 9)), vec![4, 15]);
+        assert!(index.units_in_rect((4, 3), (5, 5)).is_empty());
+    }
+
+    #[test]
+    fn test_concede_ends_game() {
+        let mut state = command_error_state();
+        state.current_phase = Phase::Movement;
+
+        let events = state
+            .process_command(Command::Concede {
+                player: Player::Player1,
+            })
+            .unwrap();
+        assert_eq!(
+            events,
+            vec![GameEvent::GameOver {
+                winner: Some(Player::Player2),
+            }]
+        );
+        assert!(state.game_over);
+        assert_eq!(state.winner, Some(Player::Player2));
+
+        assert_eq!(
+            state.process_command(move_to(1, vec![HexCoord::new(0, 0), HexCoord::new(0, 1)])),
+            Err(CommandError::GameOver)
+        );
+        assert_eq!(
+            state.process_command(Command::EndPhase),
+            Err(CommandError::GameOver)
+        );
+        assert_eq!(
+            state.process_command(Command::Concede {
+                player: Player::Player2,
+            }),
+            Err(CommandError::GameOver)
+        );
+    }
+
+    #[test]
+    fn test_destroyed_titan_leaves_wreckage() {
+        let mut state = command_error_state();
+        state.current_phase = Phase::Combat;
+        let wreck = HexCoord::new(3, 0);
+        let mut titan = Unit::new(
+            5,
+            UnitType::ReaverTitan,
+            Player::Player2,
+            wreck,
+            Facing::West,
+        );
+        titan.void_shields = 0;
+        titan.structure = 1;
+        state.add_unit(titan);
+
+        let events = state
+            .process_command(Command::Bombard {
+                attacker_id: 2,
+                target: wreck,
+            })
+            .unwrap();
+        assert!(events.contains(&GameEvent::UnitDestroyed { unit_id: 5 }));
+        assert!(events.contains(&GameEvent::TerrainChanged {
+            coord: wreck,
+            terrain: TerrainType::Wreckage,
+        }));
+        assert_eq!(state.map.terrain_at(wreck), TerrainType::Wreckage);
+
+        let mover = state.get_unit(1).unwrap();
+        assert!(is_blocked(&state, wreck, mover.id));
+        assert!(find_path(&state, mover, wreck, None).is_none());
+        let (path, _) = find_path(&state, mover, HexCoord::new(4, 0), None).unwrap();
+        assert!(!path.contains(&wreck));
+
+        // Smaller units leave nothing behind
+        state.get_unit_mut(3).unwrap().position = HexCoord::new(5, 0);
+        state.get_unit_mut(3).unwrap().structure = 1;
+        state.get_unit_mut(1).unwrap().has_attacked = false;
+        state
+            .process_command(Command::Bombard {
+                attacker_id: 1,
+                target: HexCoord::new(5, 0),
+            })
+            .unwrap();
+        assert!(state.get_unit(3).unwrap().is_destroyed());
+        assert_eq!(
+            state.map.terrain_at(HexCoord::new(5, 0)),
+            TerrainType::Clear
+        );
+    }
+
+    #[test]
+    fn test_legal_commands() {
+        let mut state = command_error_state();
+        for q in 0..3 {
+            state.map.tiles.get_mut(&(q, 0)).unwrap().deployment_zone = Some(Player::Player1);
+        }
+
+        // Deployment: only the free zone hexes, for Player1's units
+        let commands = state.legal_commands();
+        assert_eq!(commands.last(), Some(&Command::EndPhase));
+        let deploys: Vec<(u32, HexCoord)> = commands
+            .iter()
+            .filter_map(|c| match c {
+                Command::Deploy {
+                    unit_id, position, ..
+                } => Some((*unit_id, *position)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            deploys,
+            vec![(1, HexCoord::new(2, 0)), (2, HexCoord::new(2, 0))]
+        );
+        for command in &commands {
+            assert!(state.simulate(command.clone()).is_ok());
+        }
+
+        // Every listed move and attack is accepted
+        state.current_phase = Phase::Movement;
+        let moves = state.legal_commands();
+        assert!(moves.len() > 1);
+        for command in &moves {
+            assert!(state.simulate(command.clone()).is_ok());
+        }
+
+        // A wiped-out side can only end the phase
+        for id in [1, 2] {
+            state.get_unit_mut(id).unwrap().structure = 0;
+        }
+        assert_eq!(state.legal_commands(), vec![Command::EndPhase]);
+        state.current_phase = Phase::Combat;
+        assert_eq!(state.legal_commands(), vec![Command::EndPhase]);
+    }
+
+    #[test]
+    fn test_deploy_command() {
+        let mut state = command_error_state();
+        state.map.tiles.get_mut(&(0, 1)).unwrap().deployment_zone = Some(Player::Player1);
+        state.map.tiles.get_mut(&(0, 2)).unwrap().deployment_zone = Some(Player::Player1);
+        let deploy = |unit_id, q, r| Command::Deploy {
+            unit_id,
+            position: HexCoord::new(q, r),
+            facing: Facing::Southeast,
+        };
+
+        assert_eq!(
+            state.process_command(deploy(1, 0, 1)).unwrap(),
+            vec![GameEvent::UnitDeployed {
+                unit_id: 1,
+                position: HexCoord::new(0, 1),
+                facing: Facing::Southeast,
+            }]
+        );
+        assert_eq!(state.get_unit(1).unwrap().facing, Facing::Southeast);
+        assert_eq!(
+            state.process_command(deploy(2, 0, 1)),
+            Err(CommandError::Occupied(HexCoord::new(0, 1)))
+        );
+        assert_eq!(
+            state.process_command(deploy(2, 3, 0)),
+            Err(CommandError::OutsideDeploymentZone(HexCoord::new(3, 0)))
+        );
+        assert_eq!(
+            state.process_command(deploy(3, 0, 2)),
+            Err(CommandError::NotOwner(3))
+        );
+
+        state.current_phase = Phase::Movement;
+        assert_eq!(
+            state.process_command(deploy(2, 0, 2)),
+            Err(CommandError::WrongPhase(Phase::Movement))
+        );
+    }
+
+    #[test]
+    fn test_embark_and_disembark() {
+        let mut state = command_error_state();
+        state.current_phase = Phase::Movement;
+        for (id, q, r) in [(5, 0, 1), (6, 1, 1), (7, 2, 0)] {
+            state.add_unit(Unit::new(
+                id,
+                UnitType::KriegSquad,
+                Player::Player1,
+                HexCoord::new(q, r),
+                Facing::East,
+            ));
+        }
+        let embark = |unit_id, transport_id| Command::Embark {
+            unit_id,
+            transport_id,
+        };
+
+        // A Reaver carries no one
+        assert_eq!(
+            state.process_command(embark(5, 1)),
+            Err(CommandError::NotATransport(1))
+        );
+
+        // The Shadowsword at (1, 0) carries two squads
+        assert_eq!(
+            state.process_command(embark(5, 2)).unwrap(),
+            vec![GameEvent::UnitEmbarked {
+                unit_id: 5,
+                transport_id: 2
+            }]
+        );
+        state.process_command(embark(6, 2)).unwrap();
+        assert_eq!(
+            state.process_command(embark(7, 2)),
+            Err(CommandError::TransportFull(2))
+        );
+        assert_eq!(state.passengers(2).len(), 2);
+        assert!(state.units_at(HexCoord::new(0, 1)).is_empty());
+        assert_eq!(state.units_at(HexCoord::new(1, 0)).len(), 1);
+
+        // Passengers ride along with the transport
+        state
+            .process_command(move_to(2, vec![HexCoord::new(1, 0), HexCoord::new(1, 1)]))
+            .unwrap();
+        assert_eq!(state.get_unit(5).unwrap().position, HexCoord::new(1, 1));
+
+        state.end_turn();
+        state.end_turn();
+        let disembark = |unit_id, q, r| Command::Disembark {
+            unit_id,
+            position: HexCoord::new(q, r),
+        };
+        assert_eq!(
+            state.process_command(disembark(5, 2, 0)),
+            Err(CommandError::Occupied(HexCoord::new(2, 0)))
+        );
+        assert_eq!(
+            state.process_command(disembark(5, 2, 1)).unwrap(),
+            vec![GameEvent::UnitDisembarked {
+                unit_id: 5,
+                position: HexCoord::new(2, 1)
+            }]
+        );
+        assert!(!state.get_unit(5).unwrap().is_embarked());
+        assert_eq!(
+            state.process_command(disembark(5, 0, 1)),
+            Err(CommandError::AlreadyMoved(5))
+        );
+    }
+
+    #[test]
+    fn test_turn_summary() {
+        let mut state = command_error_state();
+        state.current_phase = Phase::Movement;
+        state.get_unit_mut(3).unwrap().position = HexCoord::new(4, 0);
+
+        state
+            .process_command(move_to(1, vec![HexCoord::new(0, 0), HexCoord::new(0, 1)]))
+            .unwrap();
+        state.process_command(Command::EndPhase).unwrap();
+        state
+            .process_command(Command::Attack {
+                attacker_id: 2,
+                target_id: 3,
+                weapon_index: 0,
+            })
+            .unwrap();
+        assert!(state.get_unit(3).unwrap().is_destroyed());
+        state.process_command(Command::EndTurn).unwrap();
+        state
+            .process_command(move_to(4, vec![HexCoord::new(5, 9), HexCoord::new(4, 9)]))
+            .unwrap();
+
+        let summary = state.turn_summary(1);
+        assert_eq!(summary.turn, 1);
+        assert_eq!(
+            summary.player1,
+            PlayerTurnSummary {
+                units_moved: 1,
+                attacks_made: 1,
+                shields_stripped: 0,
+                damage_dealt: 6,
+                units_destroyed: 1,
+            }
+        );
+        assert_eq!(summary.player2, PlayerTurnSummary::default());
+
+        let summary = state.turn_summary(2);
+        assert_eq!(summary.player1, PlayerTurnSummary::default());
+        assert_eq!(summary.for_player(Player::Player2).units_moved, 1);
+        assert_eq!(
+            state.turn_summary(3),
+            TurnSummary {
+                turn: 3,
+                ..TurnSummary::default()
+            }
+        );
+    }
+
+    #[test]
+    fn test_map_serializes_only_painted_tiles() {
+        let mut map = GameMap::new_wrapped(40, 40, Wrap::Horizontal).unwrap();
+        map.tiles.get_mut(&(3, 4)).unwrap().terrain = TerrainType::Woods;
+        map.tiles.get_mut(&(10, 2)).unwrap().elevation = 2;
+        map.tiles.get_mut(&(0, 0)).unwrap().deployment_zone = Some(Player::Player1);
+        map.tiles.remove(&(5, 5));
+
+        let json = serde_json::to_string(&map).unwrap();
+        let every_tile: Vec<&Tile> = map.tiles.values().collect();
+        let full = serde_json::to_string(&every_tile).unwrap();
+        assert!(
+            json.len() * 50 < full.len(),
+            "{} vs {}",
+            json.len(),
+            full.len()
+        );
+
+        let restored: GameMap = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored, map);
+        assert!(!restored.is_valid(HexCoord::new(5, 5)));
+    }
+
+    #[test]
+    fn test_hexagonal_map() {
+        let mut map = GameMap::new_hexagonal(2);
+        assert_eq!(map.all_hexes().len(), 19);
+        assert!(map
+            .all_hexes()
+            .iter()
+            .all(|h| h.distance_to(HexCoord::origin()) <= 2));
+        assert!(map.is_valid(HexCoord::new(-2, 0)));
+        assert!(!map.is_valid(HexCoord::new(2, 1)));
+        assert_eq!(map.valid_neighbors(HexCoord::new(2, 0)).len(), 3);
+        assert_eq!(map.mirror(HexCoord::new(2, -1)), HexCoord::new(-2, 1));
+
+        map.tiles.get_mut(&(1, 1)).unwrap().terrain = TerrainType::Ruins;
+        let json = serde_json::to_string(&map).unwrap();
+        let restored: GameMap = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored, map);
+    }
+
+    #[test]
+    fn test_phase_rules_allow_move_and_shoot() {
+        let mut state = command_error_state();
+        state.current_phase = Phase::Movement;
+        state.get_unit_mut(3).unwrap().position = HexCoord::new(4, 0);
+        let attack = Command::Attack {
+            attacker_id: 2,
+            target_id: 3,
+            weapon_index: 0,
+        };
+
+        assert_eq!(
+            state.simulate(attack.clone()).unwrap_err(),
+            CommandError::WrongPhase(Phase::Movement)
+        );
+        assert!(!state.legal_commands().contains(&attack));
+
+        state.phase_rules = PhaseRules::default().allow(Phase::Movement, CommandKind::Attack);
+        state
+            .process_command(move_to(1, vec![HexCoord::new(0, 0), HexCoord::new(0, 1)]))
+            .unwrap();
+        assert!(state.legal_commands().contains(&attack));
+        state.process_command(attack).unwrap();
+        assert!(state.get_unit(3).unwrap().is_destroyed());
+    }
+
+    #[test]
+    fn test_place_unit() {
+        let mut state = command_error_state();
+        let events = state.events.len();
+
+        state
+            .place_unit(1, HexCoord::new(6, 5), Facing::West)
+            .unwrap();
+        let unit = state.get_unit(1).unwrap();
+        assert_eq!(unit.position, HexCoord::new(6, 5));
+        assert_eq!(unit.facing, Facing::West);
+        assert!(!unit.has_moved);
+        assert_eq!(state.events.len(), events);
+
+        assert_eq!(
+            state.place_unit(2, HexCoord::new(8, 0), Facing::East),
+            Err(CommandError::Occupied(HexCoord::new(8, 0)).to_string())
+        );
+        assert_eq!(
+            state.place_unit(2, HexCoord::new(-5, 0), Facing::East),
+            Err(CommandError::InvalidDestination(HexCoord::new(-5, 0)).to_string())
+        );
+        assert_eq!(state.get_unit(2).unwrap().position, HexCoord::new(1, 0));
+    }
+
+    #[test]
+    fn test_advance_formation() {
+        let mut state = command_error_state();
+        state.current_phase = Phase::Movement;
+        state.add_unit(Unit::new(
+            5,
+            UnitType::KriegSquad,
+            Player::Player1,
+            HexCoord::new(2, 0),
+            Facing::East,
+        ));
+        let column = [1, 2, 5];
+
+        // An enemy on the leader's destination stops the whole column
+        state.get_unit_mut(3).unwrap().position = HexCoord::new(4, 0);
+        assert!(state.advance_formation(&column, Facing::East, 2).is_err());
+        assert_eq!(state.get_unit(5).unwrap().position, HexCoord::new(2, 0));
+        assert!(!state.get_unit(1).unwrap().has_moved);
+
+        state.get_unit_mut(3).unwrap().position = HexCoord::new(8, 0);
+        let events = state.advance_formation(&column, Facing::East, 2).unwrap();
+        assert_eq!(events.len(), 3);
+        let positions: Vec<HexCoord> = column
+            .iter()
+            .map(|id| state.get_unit(*id).unwrap().position)
+            .collect();
+        assert_eq!(
+            positions,
+            vec![
+                HexCoord::new(2, 0),
+                HexCoord::new(3, 0),
+                HexCoord::new(4, 0)
+            ]
+        );
+        assert_eq!(positions[0].distance_to(positions[1]), 1);
+        assert_eq!(positions[1].distance_to(positions[2]), 1);
+        assert!(column
+            .iter()
+            .all(|id| state.get_unit(*id).unwrap().has_moved));
+    }
+
+    #[test]
+    fn test_rotate_formation() {
+        let mut state = command_error_state();
+        state.add_unit(Unit::new(
+            5,
+            UnitType::KriegSquad,
+            Player::Player1,
+            HexCoord::new(5, 4),
+            Facing::East,
+        ));
+        state.get_unit_mut(1).unwrap().position = HexCoord::new(3, 4);
+        state.get_unit_mut(2).unwrap().position = HexCoord::new(4, 4);
+        let line = [1, 2, 5];
+        let pivot = HexCoord::new(4, 4);
+
+        // An enemy in the way stops the whole formation
+        state.get_unit_mut(3).unwrap().position = HexCoord::new(4, 5);
+        assert_eq!(
+            state.rotate_formation(&line, pivot, 1),
+            Err(CommandError::Occupied(HexCoord::new(4, 5)).to_string())
+        );
+        assert_eq!(state.get_unit(1).unwrap().position, HexCoord::new(3, 4));
+        assert_eq!(state.get_unit(5).unwrap().position, HexCoord::new(5, 4));
+
+        state.get_unit_mut(3).unwrap().position = HexCoord::new(8, 0);
+        state.rotate_formation(&line, pivot, 1).unwrap();
+        let positions: Vec<HexCoord> = line
+            .iter()
+            .map(|id| state.get_unit(*id).unwrap().position)
+            .collect();
+        assert_eq!(
+            positions,
+            vec![HexCoord::new(4, 3), pivot, HexCoord::new(4, 5)]
+        );
+        assert_eq!(positions[0].distance_to(positions[1]), 1);
+        assert_eq!(positions[1].distance_to(positions[2]), 1);
+        assert_eq!(positions[0].distance_to(positions[2]), 2);
+        assert_eq!(state.get_unit(5).unwrap().facing, Facing::Southeast);
+    }
+
+    #[test]
+    fn test_damage_tallies() {
+        let mut state = command_error_state();
+        state.current_phase = Phase::Combat;
+        state.get_unit_mut(3).unwrap().position = HexCoord::new(5, 0);
+        let dealt = |events: &[GameEvent]| -> u32 {
+            events
+                .iter()
+                .map(|e| match e {
+                    GameEvent::UnitAttacked {
+                        structure_damage, ..
+                    } => *structure_damage,
+                    _ => 0,
+                })
+                .sum()
+        };
+
+        // The missile launcher leaves the tank alive to fire back
+        let first = state
+            .process_command(Command::Attack {
+                attacker_id: 1,
+                target_id: 3,
+                weapon_index: 1,
+            })
+            .unwrap();
+        state.active_player = Player::Player2;
+        let second = state
+            .process_command(Command::Attack {
+                attacker_id: 3,
+                target_id: 1,
+                weapon_index: 0,
+            })
+            .unwrap();
+
+        let (out, back) = (dealt(&first), dealt(&second));
+        assert!(out > 0 && back > 0);
+        let reaver = state.get_unit(1).unwrap();
+        let tank = state.get_unit(3).unwrap();
+        assert_eq!((reaver.damage_dealt, reaver.damage_taken), (out, back));
+        assert_eq!((tank.damage_dealt, tank.damage_taken), (back, out));
+        assert_eq!(state.get_unit(2).unwrap().damage_dealt, 0);
+
+        let json = serde_json::to_string(&state).unwrap();
+        let restored: GameState = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.get_unit(1).unwrap().damage_taken, back);
+    }
+
+    #[test]
+    fn test_footprint_pixel_corners() {
+        let place = |unit_type| {
+            Unit::new(
+                1,
+                unit_type,
+                Player::Player1,
+                HexCoord::new(3, 2),
+                Facing::East,
+            )
+        };
+        let corners_at = |hex: HexCoord| {
+            let (x, y) = hex.to_pixel(20.0);
+            hex_corners(x, y, 20.0).to_vec()
+        };
+
+        let warlord = place(UnitType::WarlordTitan);
+        let footprint = warlord.footprint();
+        assert_eq!(footprint, vec![HexCoord::new(3, 2), HexCoord::new(2, 2)]);
+        let corners = warlord.footprint_pixel_corners(20.0);
+        assert_eq!(corners.len(), 12);
+        for (hex, set) in footprint.iter().zip(corners.chunks(6)) {
+            assert_eq!(set, corners_at(*hex).as_slice());
+        }
 
+        let tank = place(UnitType::Shadowsword);
+        assert_eq!(
+            tank.footprint_pixel_corners(20.0),
+            corners_at(tank.position)
+        );
+    }
+
+    #[test]
+    fn test_facing_toward() {
         let unit = Unit::new(
             1,
-            UnitType::Shadowsword,
+            UnitType::ReaverTitan,
+            Player::Player1,
+            HexCoord::new(3, 3),
+            Facing::East,
+        );
+
+        let target = HexCoord::new(3, 0);
+        let facing = unit.facing_toward(target);
+        assert_eq!(facing, Facing::Northwest);
+        assert_eq!(unit.facing.steps_to(facing), 2);
+        assert_eq!(unit.facing_toward(HexCoord::new(3, 3)), Facing::East);
+    }
+
+    #[test]
+    fn test_shield_forecast() {
+        let mut titan = Unit::new(
+            1,
+            UnitType::WarlordTitan,
             Player::Player1,
             HexCoord::new(0, 0),
             Facing::East,
         );
-        state.add_unit(unit);
+        assert_eq!(titan.shield_forecast(), (4, 4, 4));
 
-        assert_eq!(state.units.len(), 1);
-        assert!(state.get_unit(1).is_some());
-        assert!(state.unit_at(HexCoord::new(0, 0)).is_some());
+        // A stripped Warlord stays stripped, and a new turn brings nothing back
+        titan.void_shields = 0;
+        assert_eq!(titan.shield_forecast(), (0, 4, 0));
+        titan.void_shields = 3;
+        assert_eq!(titan.shield_forecast(), (3, 4, 3));
+        titan.reset_for_turn();
+        assert_eq!(titan.shield_forecast(), (3, 4, 3));
+
+        titan.structure = 0;
+        titan.void_shields = 1;
+        assert_eq!(titan.shield_forecast(), (1, 4, 1));
+
+        let tank = Unit::new(
+            2,
+            UnitType::Shadowsword,
+            Player::Player1,
+            HexCoord::new(1, 0),
+            Facing::East,
+        );
+        assert_eq!(tank.shield_forecast(), (0, 0, 0));
+    }
+
+    #[test]
+    fn test_burning_ticks_then_stops() {
+        let mut state = command_error_state();
+        state.current_phase = Phase::Combat;
+        let tank = state.get_unit_mut(3).unwrap();
+        tank.position = HexCoord::new(5, 0);
+        tank.structure = 50;
+
+        // The Melta Cannon sets what it damages ablaze
+        let events = state
+            .process_command(Command::Attack {
+                attacker_id: 1,
+                target_id: 3,
+                weapon_index: 0,
+            })
+            .unwrap();
+        let burning = StatusEffect::Burning {
+            damage: 1,
+            turns: 2,
+        };
+        assert!(events.contains(&GameEvent::StatusApplied {
+            unit_id: 3,
+            effect: burning,
+        }));
+        assert_eq!(state.get_unit(3).unwrap().status_effects, vec![burning]);
+
+        let before = state.get_unit(3).unwrap().structure;
+        let first = state.end_turn();
+        assert_eq!(
+            first,
+            vec![GameEvent::StatusDamage {
+                unit_id: 3,
+                damage: 1
+            }]
+        );
+        assert_eq!(state.get_unit(3).unwrap().structure, before - 1);
+
+        let second = state.end_turn();
+        assert!(second.contains(&GameEvent::StatusExpired {
+            unit_id: 3,
+            effect: StatusEffect::Burning {
+                damage: 1,
+                turns: 0,
+            },
+        }));
+        let tank = state.get_unit(3).unwrap();
+        assert_eq!(tank.structure, before - 2);
+        assert!(tank.status_effects.is_empty());
+
+        assert!(state.end_turn().is_empty());
+        assert_eq!(state.get_unit(3).unwrap().structure, before - 2);
+    }
+
+    #[test]
+    fn test_burning_can_kill() {
+        let mut state = command_error_state();
+        let tank = state.get_unit_mut(3).unwrap();
+        tank.structure = 1;
+        tank.status_effects.push(StatusEffect::Burning {
+            damage: 3,
+            turns: 2,
+        });
+
+        let events = state.end_turn();
+        assert_eq!(
+            &events[..2],
+            &[
+                GameEvent::StatusDamage {
+                    unit_id: 3,
+                    damage: 1
+                },
+                GameEvent::UnitDestroyed { unit_id: 3 },
+            ]
+        );
+        assert!(state.get_unit(3).unwrap().is_destroyed());
+        assert!(!state.game_over);
+
+        // The wreck stops burning
+        assert!(state.end_turn().is_empty());
+    }
+
+    #[test]
+    fn test_ui_context_survives_save_and_load() {
+        let mut state = command_error_state();
+        state.current_phase = Phase::Movement;
+        state.process_command(Command::EndTurn).unwrap();
+        state.process_command(Command::EndPhase).unwrap();
+        state.select_unit(Some(3));
+
+        let json = serde_json::to_string(&state).unwrap();
+        let restored: GameState = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(
+            restored.ui_context(),
+            UiContext {
+                selected_unit: Some(3),
+                active_player: Player::Player2,
+                phase: Phase::Combat,
+                turn: 2,
+            }
+        );
+        assert_eq!(restored.ui_context(), state.ui_context());
+    }
+
+    #[test]
+    fn test_state_at_turn_replays_log() {
+        let mut state = command_error_state();
+        state.current_phase = Phase::Movement;
+        state.get_unit_mut(3).unwrap().position = HexCoord::new(5, 0);
+        // Cover on the line of fire brings the seeded RNG into play
+        state.map.tiles.get_mut(&(3, 0)).unwrap().terrain = TerrainType::Ruins;
+
+        let commands = [
+            Command::Move {
+                unit_id: 2,
+                path: vec![HexCoord::new(1, 0), HexCoord::new(1, 1)],
+                final_facing: Facing::East,
+            },
+            Command::EndPhase,
+            Command::Attack {
+                attacker_id: 1,
+                target_id: 3,
+                weapon_index: 1,
+            },
+            Command::EndTurn,
+            Command::EndPhase,
+            Command::Attack {
+                attacker_id: 3,
+                target_id: 1,
+                weapon_index: 0,
+            },
+            Command::EndTurn,
+        ];
+        for command in commands {
+            state.process_command(command).unwrap();
+        }
+        assert_eq!(state.current_turn, 3);
+        assert_eq!(state.command_log.len(), 7);
+
+        let live = serde_json::to_value(&state).unwrap();
+        let replayed = state.state_at_turn(3).unwrap();
+        assert_eq!(serde_json::to_value(&replayed).unwrap(), live);
+
+        // Turn 1 includes P1's shot and the EndTurn, but not P2's reply
+        let first = state.state_at_turn(1).unwrap();
+        assert_eq!(first.current_turn, 2);
+        assert_eq!(first.command_log.len(), 4);
+        assert_eq!(first.get_unit(2).unwrap().position, HexCoord::new(1, 1));
+        assert_eq!(
+            first.get_unit(1).unwrap().damage_dealt,
+            state.get_unit(1).unwrap().damage_dealt
+        );
+        assert_eq!(first.get_unit(1).unwrap().damage_taken, 0);
+
+        assert!(state.state_at_turn(0).is_err());
+        assert!(state.state_at_turn(4).is_err());
+    }
+
+    #[test]
+    fn test_state_at_turn_replays_simultaneous_moves() {
+        let mut state = command_error_state();
+        state.current_phase = Phase::Movement;
+        state
+            .resolve_simultaneous(
+                &[move_to(2, vec![HexCoord::new(1, 0), HexCoord::new(1, 1)])],
+                &[move_to(4, vec![HexCoord::new(5, 9), HexCoord::new(5, 8)])],
+            )
+            .unwrap();
+        state.process_command(Command::EndTurn).unwrap();
+        assert!(matches!(state.command_log[0], Command::Simultaneous { .. }));
+
+        let replayed = state.state_at_turn(2).unwrap();
+        assert_eq!(
+            serde_json::to_value(&replayed).unwrap(),
+            serde_json::to_value(&state).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_state_at_turn_replays_refreshes_and_rotations() {
+        let mut state = command_error_state();
+        state.current_phase = Phase::Movement;
+        state
+            .process_command(move_to(2, vec![HexCoord::new(1, 0), HexCoord::new(1, 1)]))
+            .unwrap();
+        state.refresh_unit(2).unwrap();
+        state
+            .process_command(move_to(2, vec![HexCoord::new(1, 1), HexCoord::new(1, 2)]))
+            .unwrap();
+        state
+            .rotate_formation(&[1], HexCoord::new(0, 0), 1)
+            .unwrap();
+        state.process_command(Command::EndTurn).unwrap();
+        assert_eq!(state.command_log[1], Command::Refresh { unit_id: 2 });
+        assert!(matches!(
+            state.command_log[3],
+            Command::RotateFormation { .. }
+        ));
+
+        let replayed = state.state_at_turn(2).unwrap();
+        assert_eq!(
+            serde_json::to_value(&replayed).unwrap(),
+            serde_json::to_value(&state).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_saves_leave_out_replay_origin() {
+        let mut state = command_error_state();
+        state.current_phase = Phase::Movement;
+        state
+            .process_command(move_to(2, vec![HexCoord::new(1, 0), HexCoord::new(1, 1)]))
+            .unwrap();
+        state.process_command(Command::EndTurn).unwrap();
+
+        let json = serde_json::to_string(&state).unwrap();
+        assert!(!json.contains("origin"));
+        let mut restored: GameState = serde_json::from_str(&json).unwrap();
+        assert!(restored.origin.is_none());
+
+        // Replays pick up from where the game was loaded
+        restored
+            .process_command(move_to(4, vec![HexCoord::new(5, 9), HexCoord::new(5, 8)]))
+            .unwrap();
+        restored.process_command(Command::EndTurn).unwrap();
+        assert!(restored.state_at_turn(1).is_err());
+        assert_eq!(
+            serde_json::to_value(restored.state_at_turn(3).unwrap()).unwrap(),
+            serde_json::to_value(&restored).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_attack_with_chosen_weapon() {
+        let mut state = command_error_state();
+        state.current_phase = Phase::Combat;
+        state.get_unit_mut(1).unwrap().unit_type = UnitType::WarlordTitan;
+        state.get_unit_mut(3).unwrap().position = HexCoord::new(9, 0);
+        let fire = |weapon_index| Command::Attack {
+            attacker_id: 1,
+            target_id: 3,
+            weapon_index,
+        };
+
+        let weapons = UnitType::WarlordTitan.weapons();
+        assert_eq!(weapons[1].name, "Gatling Blaster");
+        assert_eq!(
+            state.simulate(fire(1)).unwrap_err(),
+            CommandError::OutOfRange {
+                distance: 9,
+                range: 4
+            }
+        );
+        assert_eq!(
+            state.simulate(fire(3)).unwrap_err(),
+            CommandError::NoSuchWeapon {
+                unit_id: 1,
+                weapon_index: 3
+            }
+        );
+
+        // The missile launcher reaches, and firing it leaves the main weapon ready
+        let events = state.process_command(fire(2)).unwrap();
+        assert!(matches!(
+            events[0],
+            GameEvent::UnitAttacked {
+                attacker_id: 1,
+                target_id: 3,
+                ..
+            }
+        ));
+        assert_eq!(state.get_unit(1).unwrap().main_weapon_cooldown, 0);
+
+        // Ranges quoted for the unit are still the main weapon's
+        assert_eq!(UnitType::WarlordTitan.weapon_range(), 8);
+        assert_eq!(UnitType::WarlordTitan.max_weapon_range(), 12);
+    }
+
+    #[test]
+    fn test_fixed_weapon_arc_and_dice() {
+        let mut state = command_error_state();
+        state.current_phase = Phase::Combat;
+        state.get_unit_mut(1).unwrap().position = HexCoord::new(5, 5);
+        let target = state.get_unit_mut(3).unwrap();
+        target.position = HexCoord::new(0, 0);
+        target.void_shields = 5;
+        let fire = |weapon_index| Command::Attack {
+            attacker_id: 2,
+            target_id: 3,
+            weapon_index,
+        };
+
+        // The hull-mounted Volcano can't bear on a target behind the tank
+        assert_eq!(
+            state.simulate(fire(0)).unwrap_err(),
+            CommandError::OutOfArc(3)
+        );
+        assert!(!state.legal_commands().iter().any(|c| *c == fire(0)));
+
+        // The sponsons traverse and fire a shot for each of their dice
+        let events = state.process_command(fire(1)).unwrap();
+        let shots = events
+            .iter()
+            .filter(|e| matches!(e, GameEvent::UnitAttacked { hit: true, .. }))
+            .count();
+        assert_eq!(shots, UnitType::Shadowsword.weapons()[1].dice as usize);
+        assert_eq!(state.get_unit(3).unwrap().void_shields, 3);
+    }
+
+    #[test]
+    fn test_effective_stats() {
+        let mut state = command_error_state();
+        state.map.tiles.get_mut(&(8, 0)).unwrap().terrain = TerrainType::Ruins;
+        state.get_unit_mut(3).unwrap().structure = 2;
+
+        let fresh = state.get_unit(2).unwrap().effective_stats(&state);
+        assert_eq!(
+            fresh,
+            EffectiveStats {
+                movement: 5,
+                armor: 8,
+                void_shields: 0,
+                range: 6,
+                arcs: vec![WeaponArc::Front, WeaponArc::Turret],
+            }
+        );
+
+        // Same type, but crippled and dug into ruins
+        let damaged = state.get_unit(3).unwrap().effective_stats(&state);
+        assert_eq!(damaged.movement, 3);
+        assert_eq!(damaged.armor, 10);
+        assert!(damaged.movement < fresh.movement);
+        assert!(damaged.armor > fresh.armor);
+
+        // A Reaver's missiles outrange its main weapon
+        let reaver = state.get_unit(1).unwrap();
+        assert_eq!(reaver.unit_type.weapon_range(), 6);
+        assert_eq!(reaver.effective_stats(&state).range, 10);
     }
 }
@@ -3,8 +3,13 @@
 //! Exposes game functions to the browser via wasm-bindgen.
 
 use crate::hex::{Facing, HexCoord};
-use crate::movement::{find_path, find_reachable};
-use crate::rules::{Command, GameMap, GameState, Phase, Player, Unit, UnitType};
+use crate::movement::{
+    best_approach, find_path, find_reachable, measure, path_with_costs, reachable_sorted,
+};
+use crate::rules::{
+    Command, CommandError, GameMap, GameState, Phase, Player, StatModifier, StatusEffect,
+    TerrainWeights, Unit, UnitType, Weapon,
+};
 use serde::{Deserialize, Serialize};
 use wasm_bindgen::prelude::*;
 
@@ -24,14 +29,110 @@ pub struct TitanHuntEngine {
 #[wasm_bindgen]
 impl TitanHuntEngine {
     /// Create a new game with the specified map dimensions
+    ///
+    /// Pass shape "hexagon" for a hexagon-shaped board `width` hexes
+    /// across, centered on the origin; height is then ignored.
     #[wasm_bindgen(constructor)]
-    pub fn new(width: i32, height: i32) -> TitanHuntEngine {
-        let map = GameMap::new(width, height);
+    pub fn new(width: i32, height: i32, shape: Option<String>) -> Result<TitanHuntEngine, JsValue> {
+        let map = match shape.as_deref() {
+            None | Some("rectangle") => GameMap::new(width, height),
+            Some("hexagon") => GameMap::new_hexagonal(width / 2),
+            Some(other) => {
+                return Err(JsValue::from_str(&format!("Unknown map shape: {}", other)));
+            }
+        };
+        Ok(TitanHuntEngine {
+            state: GameState::new(map),
+        })
+    }
+
+    /// Create a new game on a randomly generated, mirrored map
+    #[wasm_bindgen(js_name = generateRandomMap)]
+    pub fn generate_random_map(width: i32, height: i32, seed: u32) -> TitanHuntEngine {
+        let map = GameMap::generate_random(width, height, seed, &TerrainWeights::default());
         TitanHuntEngine {
             state: GameState::new(map),
         }
     }
 
+    /// Start over on the same map, keeping its terrain and the rule settings
+    ///
+    /// Units, events, orders and the game-over flag are cleared and play
+    /// returns to turn 1 in the Deployment phase.
+    #[wasm_bindgen]
+    pub fn reset(&mut self) {
+        self.state = self.fresh_state(1, Phase::Deployment, Player::Player1);
+    }
+
+    /// Start a mid-game scenario on the same map, keeping the rule settings
+    ///
+    /// Clears everything `reset` does, then begins at the given turn, phase
+    /// ("deployment", "movement", "combat" or "end") and active player (1 or
+    /// 2) with the units from an `addUnits` array. Returns the entries that
+    /// couldn't be added, as `addUnits` does.
+    #[wasm_bindgen(js_name = loadScenario)]
+    pub fn load_scenario(
+        &mut self,
+        turn: u32,
+        phase: &str,
+        player: u32,
+        units_json: JsValue,
+    ) -> Result<JsValue, JsValue> {
+        let phase = match phase {
+            "deployment" => Phase::Deployment,
+            "movement" => Phase::Movement,
+            "combat" => Phase::Combat,
+            "end" => Phase::End,
+            _ => return Err(JsValue::from_str(&format!("Unknown phase: {}", phase))),
+        };
+        let player = parse_player(player).map_err(|e| JsValue::from_str(&e))?;
+        if turn == 0 {
+            return Err(JsValue::from_str("Turns start at 1"));
+        }
+        let units: Vec<UnitSpecJson> = serde_wasm_bindgen::from_value(units_json)
+            .map_err(|e| JsValue::from_str(&e.to_string()))?;
+
+        self.state = self.fresh_state(turn, phase, player);
+        let errors = add_unit_batch(&mut self.state, units);
+        serde_wasm_bindgen::to_value(&errors).map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+
+    /// Get base stats for every unit type so the frontend needn't duplicate them
+    #[wasm_bindgen(js_name = unitCatalog)]
+    pub fn unit_catalog() -> Result<JsValue, JsValue> {
+        serde_wasm_bindgen::to_value(&unit_catalog())
+            .map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+
+    /// Get the pixel extent of the whole map so the renderer can size its canvas
+    #[wasm_bindgen(js_name = pixelBounds)]
+    pub fn pixel_bounds(&self, hex_size: f64) -> Result<JsValue, JsValue> {
+        let (min_x, min_y, max_x, max_y) = self.state.map.pixel_bounds(hex_size);
+        let bounds = PixelBounds {
+            min_x,
+            min_y,
+            max_x,
+            max_y,
+        };
+        serde_wasm_bindgen::to_value(&bounds).map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+
+    /// Get the pixel corners of each hex a unit's model covers, six per hex
+    #[wasm_bindgen(js_name = footprintCorners)]
+    pub fn footprint_corners(&self, unit_id: u32, hex_size: f64) -> Result<JsValue, JsValue> {
+        let unit = self
+            .state
+            .visible_unit(unit_id)
+            .ok_or_else(|| JsValue::from_str("Unit not found"))?;
+        let corners: Vec<PixelPos> = unit
+            .footprint_pixel_corners(hex_size)
+            .into_iter()
+            .map(|(x, y)| PixelPos { x, y })
+            .collect();
+
+        serde_wasm_bindgen::to_value(&corners).map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+
     /// Get the current game state as JSON
     #[wasm_bindgen(js_name = getState)]
     pub fn get_state(&self) -> Result<JsValue, JsValue> {
@@ -50,36 +151,73 @@ impl TitanHuntEngine {
         r: i32,
         facing: u8,
     ) -> Result<(), JsValue> {
-        let unit_type = match unit_type {
-            "Reaver_Titan" => UnitType::ReaverTitan,
-            "Warlord_Titan" => UnitType::WarlordTitan,
-            "shadowsword" => UnitType::Shadowsword,
-            "shadowsword2" => UnitType::Shadowsword2,
-            "shadowsword3" => UnitType::Shadowsword3,
-            "krieg" => UnitType::KriegSquad,
-            _ => return Err(JsValue::from_str(&format!("Unknown unit type: {}", unit_type))),
-        };
+        let unit =
+            build_unit(id, unit_type, player, q, r, facing).map_err(|e| JsValue::from_str(&e))?;
+        self.setup_state()?.add_unit(unit);
+        Ok(())
+    }
 
-        let owner = match player {
-            1 => Player::Player1,
-            2 => Player::Player2,
-            _ => return Err(JsValue::from_str("Invalid player (must be 1 or 2)")),
-        };
+    /// Add a unit to the reserves, off the board until deployed
+    #[wasm_bindgen(js_name = addReserve)]
+    pub fn add_reserve(
+        &mut self,
+        id: u32,
+        unit_type: &str,
+        player: u32,
+        facing: u8,
+    ) -> Result<(), JsValue> {
+        let unit =
+            build_unit(id, unit_type, player, 0, 0, facing).map_err(|e| JsValue::from_str(&e))?;
+        self.setup_state()?.add_reserve(unit);
+        Ok(())
+    }
 
-        let facing = Facing::from_index(facing)
-            .ok_or_else(|| JsValue::from_str("Invalid facing (must be 0-5)"))?;
+    /// Schedule a unit {id, type, player, q, r, facing} to arrive on hex (q, r)
+    /// at the start of a turn
+    ///
+    /// If the hex is blocked when the unit is due, it tries again each turn.
+    #[wasm_bindgen(js_name = addReinforcement)]
+    pub fn add_reinforcement(&mut self, turn: u32, unit_json: JsValue) -> Result<(), JsValue> {
+        self.setup_state()?;
+        let spec: UnitSpecJson = serde_wasm_bindgen::from_value(unit_json)
+            .map_err(|e| JsValue::from_str(&e.to_string()))?;
+        let unit = build_unit(
+            spec.id,
+            &spec.unit_type,
+            spec.player,
+            spec.q,
+            spec.r,
+            spec.facing,
+        )
+        .map_err(|e| JsValue::from_str(&e))?;
+        if !self.state.map.is_valid(unit.position) {
+            return Err(JsValue::from_str("Arrival hex is off the map"));
+        }
 
-        let unit = Unit::new(id, unit_type, owner, HexCoord::new(q, r), facing);
-        self.state.add_unit(unit);
+        let position = unit.position;
+        self.state.add_reinforcement(turn, unit, position);
         Ok(())
     }
 
+    /// Add many units in one call from an array of {id, type, player, q, r, facing}
+    ///
+    /// Bad entries (unknown type, player or facing, or an off-map hex) are
+    /// skipped without stopping the rest. Returns {index, id, error} for each
+    /// entry that was skipped, so an empty array means all were added.
+    #[wasm_bindgen(js_name = addUnits)]
+    pub fn add_units(&mut self, units_json: JsValue) -> Result<JsValue, JsValue> {
+        let units: Vec<UnitSpecJson> = serde_wasm_bindgen::from_value(units_json)
+            .map_err(|e| JsValue::from_str(&e.to_string()))?;
+        let errors = add_unit_batch(self.setup_state()?, units);
+        serde_wasm_bindgen::to_value(&errors).map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+
     /// Get reachable hexes for a unit
     #[wasm_bindgen(js_name = getReachableHexes)]
     pub fn get_reachable_hexes(&self, unit_id: u32) -> Result<JsValue, JsValue> {
         let unit = self
             .state
-            .get_unit(unit_id)
+            .visible_unit(unit_id)
             .ok_or_else(|| JsValue::from_str("Unit not found"))?;
 
         let reachable = find_reachable(&self.state, unit);
@@ -98,12 +236,32 @@ impl TitanHuntEngine {
             .map_err(|e| JsValue::from_str(&e.to_string()))
     }
 
+    /// Get reachable hexes nearest first, then by q and r, for tab-cycling
+    #[wasm_bindgen(js_name = reachableSorted)]
+    pub fn reachable_sorted(&self, unit_id: u32) -> Result<JsValue, JsValue> {
+        let unit = self
+            .state
+            .visible_unit(unit_id)
+            .ok_or_else(|| JsValue::from_str("Unit not found"))?;
+
+        let result: Vec<ReachableHex> = reachable_sorted(&self.state, unit)
+            .into_iter()
+            .map(|(coord, remaining)| ReachableHex {
+                q: coord.q,
+                r: coord.r,
+                remaining,
+            })
+            .collect();
+
+        serde_wasm_bindgen::to_value(&result).map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+
     /// Find path from a unit to a target hex
     #[wasm_bindgen(js_name = findPath)]
     pub fn find_path_to(&self, unit_id: u32, target_q: i32, target_r: i32) -> Result<JsValue, JsValue> {
         let unit = self
             .state
-            .get_unit(unit_id)
+            .visible_unit(unit_id)
             .ok_or_else(|| JsValue::from_str("Unit not found"))?;
 
         let target = HexCoord::new(target_q, target_r);
@@ -137,6 +295,119 @@ impl TitanHuntEngine {
         }
     }
 
+    /// Find the path that brings a unit closest to a hex it may not reach this turn
+    #[wasm_bindgen(js_name = bestApproach)]
+    pub fn best_approach(&self, unit_id: u32, q: i32, r: i32) -> Result<JsValue, JsValue> {
+        let unit = self
+            .state
+            .visible_unit(unit_id)
+            .ok_or_else(|| JsValue::from_str("Unit not found"))?;
+
+        let result = match best_approach(&self.state, unit, HexCoord::new(q, r)) {
+            Some((path, cost)) => PathResult {
+                path: path
+                    .into_iter()
+                    .map(|coord| HexJson { q: coord.q, r: coord.r })
+                    .collect(),
+                cost,
+                valid: true,
+            },
+            None => PathResult {
+                path: vec![],
+                cost: 0,
+                valid: false,
+            },
+        };
+
+        serde_wasm_bindgen::to_value(&result).map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+
+    /// Measure the movement cost from a unit to a hex, ignoring its remaining MP
+    #[wasm_bindgen(js_name = measureTo)]
+    pub fn measure_to(&self, unit_id: u32, q: i32, r: i32) -> Result<Option<u32>, JsValue> {
+        let unit = self
+            .state
+            .visible_unit(unit_id)
+            .ok_or_else(|| JsValue::from_str("Unit not found"))?;
+
+        Ok(measure(&self.state, unit, HexCoord::new(q, r)))
+    }
+
+    /// Validate a drag path, returning each hex with the MP left on reaching it
+    #[wasm_bindgen(js_name = pathWithCosts)]
+    pub fn path_with_costs(&self, unit_id: u32, path_json: JsValue) -> Result<JsValue, JsValue> {
+        let unit = self
+            .state
+            .visible_unit(unit_id)
+            .ok_or_else(|| JsValue::from_str("Unit not found"))?;
+
+        let path_data: Vec<HexJson> = serde_wasm_bindgen::from_value(path_json)
+            .map_err(|e| JsValue::from_str(&e.to_string()))?;
+
+        let path: Vec<HexCoord> = path_data
+            .into_iter()
+            .map(|h| HexCoord::new(h.q, h.r))
+            .collect();
+
+        let steps: Vec<ReachableHex> = path_with_costs(&self.state, unit, &path)
+            .map_err(|e| JsValue::from_str(&e))?
+            .into_iter()
+            .map(|(coord, remaining)| ReachableHex {
+                q: coord.q,
+                r: coord.r,
+                remaining,
+            })
+            .collect();
+
+        serde_wasm_bindgen::to_value(&steps).map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+
+    /// Place a unit during the deployment phase
+    #[wasm_bindgen(js_name = deployUnit)]
+    pub fn deploy_unit(
+        &mut self,
+        unit_id: u32,
+        q: i32,
+        r: i32,
+        facing: u8,
+    ) -> Result<JsValue, JsValue> {
+        let facing =
+            Facing::from_index(facing).ok_or_else(|| JsValue::from_str("Invalid facing"))?;
+
+        let command = Command::Deploy {
+            unit_id,
+            position: HexCoord::new(q, r),
+            facing,
+        };
+
+        match self.state.process_command(command) {
+            Ok(events) => serde_wasm_bindgen::to_value(&events)
+                .map_err(|e| JsValue::from_str(&e.to_string())),
+            Err(e) => Err(command_error(e)),
+        }
+    }
+
+    /// Lock in the active player's blind deployment and pass to the opponent
+    #[wasm_bindgen(js_name = confirmDeployment)]
+    pub fn confirm_deployment(&mut self) -> Result<JsValue, JsValue> {
+        match self.state.process_command(Command::ConfirmDeployment) {
+            Ok(events) => serde_wasm_bindgen::to_value(&events)
+                .map_err(|e| JsValue::from_str(&e.to_string())),
+            Err(e) => Err(command_error(e)),
+        }
+    }
+
+    /// Put a unit on a hex directly, for scenario scripting
+    #[cfg(feature = "scripting")]
+    #[wasm_bindgen(js_name = placeUnit)]
+    pub fn place_unit(&mut self, unit_id: u32, q: i32, r: i32, facing: u8) -> Result<(), JsValue> {
+        let facing =
+            Facing::from_index(facing).ok_or_else(|| JsValue::from_str("Invalid facing"))?;
+        self.setup_state()?
+            .place_unit(unit_id, HexCoord::new(q, r), facing)
+            .map_err(|e| JsValue::from_str(&e))
+    }
+
     /// Execute a move command
     #[wasm_bindgen(js_name = moveUnit)]
     pub fn move_unit(
@@ -165,7 +436,173 @@ impl TitanHuntEngine {
         match self.state.process_command(command) {
             Ok(events) => serde_wasm_bindgen::to_value(&events)
                 .map_err(|e| JsValue::from_str(&e.to_string())),
-            Err(e) => Err(JsValue::from_str(&e)),
+            Err(e) => Err(command_error(e)),
+        }
+    }
+
+    /// Move a unit one hex, so the move can be played out step by step
+    #[wasm_bindgen(js_name = stepUnit)]
+    pub fn step_unit(&mut self, unit_id: u32, q: i32, r: i32) -> Result<JsValue, JsValue> {
+        let command = Command::StepMove {
+            unit_id,
+            to: HexCoord::new(q, r),
+        };
+
+        match self.state.process_command(command) {
+            Ok(events) => serde_wasm_bindgen::to_value(&events)
+                .map_err(|e| JsValue::from_str(&e.to_string())),
+            Err(e) => Err(command_error(e)),
+        }
+    }
+
+    /// Load a unit into an adjacent friendly transport
+    #[wasm_bindgen(js_name = embarkUnit)]
+    pub fn embark_unit(&mut self, unit_id: u32, transport_id: u32) -> Result<JsValue, JsValue> {
+        let command = Command::Embark {
+            unit_id,
+            transport_id,
+        };
+
+        match self.state.process_command(command) {
+            Ok(events) => serde_wasm_bindgen::to_value(&events)
+                .map_err(|e| JsValue::from_str(&e.to_string())),
+            Err(e) => Err(command_error(e)),
+        }
+    }
+
+    /// Unload a unit from its transport into an adjacent hex
+    #[wasm_bindgen(js_name = disembarkUnit)]
+    pub fn disembark_unit(&mut self, unit_id: u32, q: i32, r: i32) -> Result<JsValue, JsValue> {
+        let command = Command::Disembark {
+            unit_id,
+            position: HexCoord::new(q, r),
+        };
+
+        match self.state.process_command(command) {
+            Ok(events) => serde_wasm_bindgen::to_value(&events)
+                .map_err(|e| JsValue::from_str(&e.to_string())),
+            Err(e) => Err(command_error(e)),
+        }
+    }
+
+    /// Execute an attack command, firing the main weapon unless another is chosen
+    #[wasm_bindgen(js_name = attackUnit)]
+    pub fn attack_unit(
+        &mut self,
+        attacker_id: u32,
+        target_id: u32,
+        weapon_index: Option<u32>,
+    ) -> Result<JsValue, JsValue> {
+        let command = Command::Attack {
+            attacker_id,
+            target_id,
+            weapon_index: weapon_index.unwrap_or(0) as usize,
+        };
+
+        match self.state.process_command(command) {
+            Ok(events) => serde_wasm_bindgen::to_value(&events)
+                .map_err(|e| JsValue::from_str(&e.to_string())),
+            Err(e) => Err(command_error(e)),
+        }
+    }
+
+    /// Shell a hex, hitting any units there
+    #[wasm_bindgen(js_name = bombardHex)]
+    pub fn bombard_hex(&mut self, attacker_id: u32, q: i32, r: i32) -> Result<JsValue, JsValue> {
+        let command = Command::Bombard {
+            attacker_id,
+            target: HexCoord::new(q, r),
+        };
+
+        match self.state.process_command(command) {
+            Ok(events) => serde_wasm_bindgen::to_value(&events)
+                .map_err(|e| JsValue::from_str(&e.to_string())),
+            Err(e) => Err(command_error(e)),
+        }
+    }
+
+    /// Have an engineer repair an adjacent friendly unit
+    #[wasm_bindgen(js_name = repairUnit)]
+    pub fn repair_unit(&mut self, engineer_id: u32, target_id: u32) -> Result<JsValue, JsValue> {
+        let command = Command::Repair {
+            engineer_id,
+            target_id,
+        };
+
+        match self.state.process_command(command) {
+            Ok(events) => serde_wasm_bindgen::to_value(&events)
+                .map_err(|e| JsValue::from_str(&e.to_string())),
+            Err(e) => Err(command_error(e)),
+        }
+    }
+
+    /// Give a unit a standing order to move toward a hex over several turns
+    #[wasm_bindgen(js_name = setOrder)]
+    pub fn set_order(&mut self, unit_id: u32, q: i32, r: i32) -> Result<JsValue, JsValue> {
+        let command = Command::SetOrder {
+            unit_id,
+            destination: HexCoord::new(q, r),
+        };
+
+        match self.state.process_command(command) {
+            Ok(events) => serde_wasm_bindgen::to_value(&events)
+                .map_err(|e| JsValue::from_str(&e.to_string())),
+            Err(e) => Err(command_error(e)),
+        }
+    }
+
+    /// Start a unit's activation
+    #[wasm_bindgen(js_name = activateUnit)]
+    pub fn activate_unit(&mut self, unit_id: u32) -> Result<JsValue, JsValue> {
+        match self.state.process_command(Command::Activate { unit_id }) {
+            Ok(events) => serde_wasm_bindgen::to_value(&events)
+                .map_err(|e| JsValue::from_str(&e.to_string())),
+            Err(e) => Err(command_error(e)),
+        }
+    }
+
+    /// Finish the open activation
+    #[wasm_bindgen(js_name = endActivation)]
+    pub fn end_activation(&mut self) -> Result<JsValue, JsValue> {
+        match self.state.process_command(Command::EndActivation) {
+            Ok(events) => serde_wasm_bindgen::to_value(&events)
+                .map_err(|e| JsValue::from_str(&e.to_string())),
+            Err(e) => Err(command_error(e)),
+        }
+    }
+
+    /// Handicap a player (1 or 2) for teaching games
+    ///
+    /// Scales the structure damage their attacks deal and adds a movement
+    /// bonus (negative to slow them) each time their units' movement refreshes.
+    #[wasm_bindgen(js_name = setHandicap)]
+    pub fn set_handicap(
+        &mut self,
+        player: u32,
+        damage_mult: f32,
+        move_bonus: i32,
+    ) -> Result<(), JsValue> {
+        let player = parse_player(player).map_err(|e| JsValue::from_str(&e))?;
+
+        self.setup_state()?.set_stat_modifier(
+            player,
+            StatModifier {
+                damage_multiplier: damage_mult,
+                movement_bonus: move_bonus,
+            },
+        );
+        Ok(())
+    }
+
+    /// Surrender the game on behalf of a player (1 or 2)
+    #[wasm_bindgen]
+    pub fn concede(&mut self, player: u32) -> Result<JsValue, JsValue> {
+        let player = parse_player(player).map_err(|e| JsValue::from_str(&e))?;
+
+        match self.state.process_command(Command::Concede { player }) {
+            Ok(events) => serde_wasm_bindgen::to_value(&events)
+                .map_err(|e| JsValue::from_str(&e.to_string())),
+            Err(e) => Err(command_error(e)),
         }
     }
 
@@ -175,20 +612,215 @@ impl TitanHuntEngine {
         match self.state.process_command(Command::EndPhase) {
             Ok(events) => serde_wasm_bindgen::to_value(&events)
                 .map_err(|e| JsValue::from_str(&e.to_string())),
-            Err(e) => Err(JsValue::from_str(&e)),
+            Err(e) => Err(command_error(e)),
         }
     }
 
+    /// Opt in to EndPhase skipping phases with nothing for the player to do
+    #[wasm_bindgen(js_name = setAutoSkipPhases)]
+    pub fn set_auto_skip_phases(&mut self, enabled: bool) -> Result<(), JsValue> {
+        self.setup_state()?.auto_skip_empty_phases = enabled;
+        Ok(())
+    }
+
+    /// Opt in to blind deployment, hiding each side's setup until both confirm
+    #[wasm_bindgen(js_name = setHiddenDeployment)]
+    pub fn set_hidden_deployment(&mut self, enabled: bool) -> Result<(), JsValue> {
+        self.setup_state()?.hidden_deployment = enabled;
+        Ok(())
+    }
+
+    /// Opt in to alternating activation, where units must be activated to act
+    #[wasm_bindgen(js_name = setAlternatingActivation)]
+    pub fn set_alternating_activation(&mut self, enabled: bool) -> Result<(), JsValue> {
+        self.setup_state()?.alternating_activation = enabled;
+        Ok(())
+    }
+
+    /// Draw the game after this many turns in a row without an attack (0 to disable)
+    #[wasm_bindgen(js_name = setStalemateLimit)]
+    pub fn set_stalemate_limit(&mut self, turns: u32) -> Result<(), JsValue> {
+        self.setup_state()?.stalemate_limit = turns;
+        Ok(())
+    }
+
+    /// Choose whether area attacks also hit the attacker's own units
+    #[wasm_bindgen(js_name = setFriendlyFire)]
+    pub fn set_friendly_fire(&mut self, enabled: bool) -> Result<(), JsValue> {
+        self.setup_state()?.friendly_fire = enabled;
+        Ok(())
+    }
+
+    /// Replace which commands each phase allows, for rule variants
+    #[wasm_bindgen(js_name = setPhaseRules)]
+    pub fn set_phase_rules(&mut self, rules_json: JsValue) -> Result<(), JsValue> {
+        self.setup_state()?.phase_rules = serde_wasm_bindgen::from_value(rules_json)
+            .map_err(|e| JsValue::from_str(&e.to_string()))?;
+        Ok(())
+    }
+
+    /// Replace how the game can be won, e.g.
+    /// `["Annihilation", {"SurviveTurns": 6}, {"ExitUnits": {"edge": "East", "count": 2}}]`
+    #[wasm_bindgen(js_name = setVictoryConditions)]
+    pub fn set_victory_conditions(&mut self, conditions_json: JsValue) -> Result<(), JsValue> {
+        self.setup_state()?.victory_conditions = serde_wasm_bindgen::from_value(conditions_json)
+            .map_err(|e| JsValue::from_str(&e.to_string()))?;
+        Ok(())
+    }
+
     /// End the current turn
     #[wasm_bindgen(js_name = endTurn)]
     pub fn end_turn(&mut self) -> Result<JsValue, JsValue> {
         match self.state.process_command(Command::EndTurn) {
             Ok(events) => serde_wasm_bindgen::to_value(&events)
                 .map_err(|e| JsValue::from_str(&e.to_string())),
-            Err(e) => Err(JsValue::from_str(&e)),
+            Err(e) => Err(command_error(e)),
         }
     }
 
+    /// Get the IDs of live units the active player can see within a hex radius of a point
+    #[wasm_bindgen(js_name = unitsWithin)]
+    pub fn units_within(&self, q: i32, r: i32, radius: u32) -> Vec<u32> {
+        self.state
+            .units_within(HexCoord::new(q, r), radius)
+            .iter()
+            .map(|u| u.id)
+            .filter(|&id| self.state.visible_unit(id).is_some())
+            .collect()
+    }
+
+    /// Check every unit's placement, returning all issues for the scenario editor
+    #[wasm_bindgen(js_name = validateDeployment)]
+    pub fn validate_deployment(&self) -> Result<JsValue, JsValue> {
+        serde_wasm_bindgen::to_value(&self.state.validate_deployment())
+            .map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+
+    /// Get the IDs of a player's units standing in their own deployment zone
+    #[wasm_bindgen(js_name = unitsInZone)]
+    pub fn units_in_zone(&self, player: u32) -> Result<JsValue, JsValue> {
+        let player = parse_player(player).map_err(|e| JsValue::from_str(&e))?;
+
+        serde_wasm_bindgen::to_value(&self.state.units_in_zone(player))
+            .map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+
+    /// Get a unit's current movement, armor, range and weapon arcs after modifiers
+    #[wasm_bindgen(js_name = effectiveStats)]
+    pub fn effective_stats(&self, unit_id: u32) -> Result<JsValue, JsValue> {
+        let unit = self
+            .state
+            .visible_unit(unit_id)
+            .ok_or_else(|| JsValue::from_str("Unit not found"))?;
+
+        serde_wasm_bindgen::to_value(&unit.effective_stats(&self.state))
+            .map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+
+    /// Get per-player totals of moves, attacks, damage and kills for a turn
+    #[wasm_bindgen(js_name = turnSummary)]
+    pub fn turn_summary(&self, turn: u32) -> Result<JsValue, JsValue> {
+        serde_wasm_bindgen::to_value(&self.state.turn_summary(turn))
+            .map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+
+    /// List the commands the active player can issue in the current phase
+    #[wasm_bindgen(js_name = legalCommands)]
+    pub fn legal_commands(&self) -> Result<JsValue, JsValue> {
+        serde_wasm_bindgen::to_value(&self.state.legal_commands())
+            .map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+
+    /// Check whether a command would succeed without applying it
+    ///
+    /// Resolves to undefined when the command is legal and throws the same
+    /// coded error the command itself would.
+    #[wasm_bindgen(js_name = canProcess)]
+    pub fn can_process(&self, command_json: JsValue) -> Result<(), JsValue> {
+        let command: Command = serde_wasm_bindgen::from_value(command_json)
+            .map_err(|e| JsValue::from_str(&e.to_string()))?;
+        self.state.can_process(&command).map_err(command_error)
+    }
+
+    /// Get every hex a unit could fire on without moving
+    #[wasm_bindgen(js_name = attackableHexes)]
+    pub fn attackable_hexes(&self, unit_id: u32) -> Result<JsValue, JsValue> {
+        let hexes: Vec<HexJson> = self
+            .state
+            .attackable_hexes(unit_id)
+            .into_iter()
+            .map(|coord| HexJson {
+                q: coord.q,
+                r: coord.r,
+            })
+            .collect();
+
+        serde_wasm_bindgen::to_value(&hexes).map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+
+    /// Get a unit's reachable hexes and the hexes it threatens from them as
+    /// {reachable: [{q, r, remaining}], threat: [{q, r}]}
+    #[wasm_bindgen(js_name = movementAndThreatOverlay)]
+    pub fn movement_and_threat_overlay(&self, unit_id: u32) -> Result<JsValue, JsValue> {
+        let (reachable, threat) = self.state.movement_and_threat_overlay(unit_id);
+        let overlay = OverlayJson {
+            reachable: reachable
+                .into_iter()
+                .map(|(coord, remaining)| ReachableHex {
+                    q: coord.q,
+                    r: coord.r,
+                    remaining,
+                })
+                .collect(),
+            threat: threat
+                .into_iter()
+                .map(|coord| HexJson {
+                    q: coord.q,
+                    r: coord.r,
+                })
+                .collect(),
+        };
+
+        serde_wasm_bindgen::to_value(&overlay).map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+
+    /// Get the nearest live enemy of a unit as {id, distance}, or null
+    #[wasm_bindgen(js_name = nearestEnemy)]
+    pub fn nearest_enemy(&self, unit_id: u32) -> Result<JsValue, JsValue> {
+        let nearest = self
+            .state
+            .nearest_enemy(unit_id)
+            .map(|(id, distance)| NearestUnit { id, distance });
+
+        serde_wasm_bindgen::to_value(&nearest)
+            .map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+
+    /// Check if a unit is adjacent to a live enemy
+    #[wasm_bindgen(js_name = isEngaged)]
+    pub fn is_engaged(&self, unit_id: u32) -> bool {
+        self.state.is_engaged(unit_id)
+    }
+
+    /// Get the nearest live friendly unit as {id, distance}, or null
+    #[wasm_bindgen(js_name = nearestAlly)]
+    pub fn nearest_ally(&self, unit_id: u32) -> Result<JsValue, JsValue> {
+        let nearest = self
+            .state
+            .nearest_ally(unit_id)
+            .map(|(id, distance)| NearestUnit { id, distance });
+
+        serde_wasm_bindgen::to_value(&nearest)
+            .map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+
+    /// Get all events from an index onwards (for polling clients)
+    #[wasm_bindgen(js_name = eventsSince)]
+    pub fn events_since(&self, index: usize) -> Result<JsValue, JsValue> {
+        serde_wasm_bindgen::to_value(self.state.events_since(index))
+            .map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+
     /// Select a unit
     #[wasm_bindgen(js_name = selectUnit)]
     pub fn select_unit(&mut self, unit_id: Option<u32>) {
@@ -204,21 +836,30 @@ impl TitanHuntEngine {
     /// Get the current phase
     #[wasm_bindgen(js_name = getCurrentPhase)]
     pub fn get_current_phase(&self) -> String {
-        match self.state.current_phase {
-            Phase::Deployment => "deployment".to_string(),
-            Phase::Movement => "movement".to_string(),
-            Phase::Combat => "combat".to_string(),
-            Phase::End => "end".to_string(),
-        }
+        phase_name(self.state.current_phase).to_string()
     }
 
     /// Get the active player (1 or 2)
     #[wasm_bindgen(js_name = getActivePlayer)]
     pub fn get_active_player(&self) -> u32 {
-        match self.state.active_player {
-            Player::Player1 => 1,
-            Player::Player2 => 2,
-        }
+        player_number(self.state.active_player)
+    }
+
+    /// Get the selection, active player, phase and turn in one call, for
+    /// restoring the UI after loading a game
+    ///
+    /// Returns {selected_unit, active_player, phase, turn} using the same
+    /// values as the single getters.
+    #[wasm_bindgen(js_name = getUiContext)]
+    pub fn get_ui_context(&self) -> Result<JsValue, JsValue> {
+        let context = self.state.ui_context();
+        let json = UiContextJson {
+            selected_unit: context.selected_unit,
+            active_player: player_number(context.active_player),
+            phase: phase_name(context.phase).to_string(),
+            turn: context.turn,
+        };
+        serde_wasm_bindgen::to_value(&json).map_err(|e| JsValue::from_str(&e.to_string()))
     }
 
     /// Get the current turn number
@@ -227,44 +868,36 @@ impl TitanHuntEngine {
         self.state.current_turn
     }
 
-    /// Get all units as JSON
+    /// Get the units the active player can see as JSON
+    ///
+    /// The opponent's units are left out while a blind deployment is unconfirmed.
     #[wasm_bindgen(js_name = getUnits)]
     pub fn get_units(&self) -> Result<JsValue, JsValue> {
         let units: Vec<UnitJson> = self
             .state
-            .units
-            .iter()
-            .map(|u| UnitJson {
-                id: u.id,
-                unit_type: u.unit_type.sprite_key().to_string(),
-                display_name: u.unit_type.display_name().to_string(),
-                owner: match u.owner {
-                    Player::Player1 => 1,
-                    Player::Player2 => 2,
-                },
-                q: u.position.q,
-                r: u.position.r,
-                facing: u.facing.index(),
-                sprite_frame: u.sprite_frame(),
-                armor: u.armor,
-                max_armor: u.unit_type.base_armor(),
-                structure: u.structure,
-                max_structure: u.unit_type.base_structure(),
-                void_shields: u.void_shields,
-                max_void_shields: u.unit_type.void_shields(),
-                movement_remaining: u.movement_remaining,
-                max_movement: u.unit_type.base_movement(),
-                has_moved: u.has_moved,
-                has_attacked: u.has_attacked,
-                is_destroyed: u.is_destroyed(),
-                is_titan: u.unit_type.is_titan(),
-            })
+            .visible_units(self.state.active_player)
+            .into_iter()
+            .map(UnitJson::from)
             .collect();
 
         serde_wasm_bindgen::to_value(&units)
             .map_err(|e| JsValue::from_str(&e.to_string()))
     }
 
+    /// Get a player's (1 or 2) units still waiting to be deployed
+    #[wasm_bindgen(js_name = getReserves)]
+    pub fn get_reserves(&self, player: u32) -> Result<JsValue, JsValue> {
+        let player = parse_player(player).map_err(|e| JsValue::from_str(&e))?;
+        let units: Vec<UnitJson> = self
+            .state
+            .reserves(player)
+            .into_iter()
+            .map(UnitJson::from)
+            .collect();
+
+        serde_wasm_bindgen::to_value(&units).map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+
     /// Get all valid hex coordinates on the map
     #[wasm_bindgen(js_name = getMapHexes)]
     pub fn get_map_hexes(&self) -> Result<JsValue, JsValue> {
@@ -280,6 +913,28 @@ impl TitanHuntEngine {
             .map_err(|e| JsValue::from_str(&e.to_string()))
     }
 
+    /// Get every hex's elevation plus the map's range, for heightmap shading
+    ///
+    /// Returns {min, max, hexes: [{q, r, elevation}]}.
+    #[wasm_bindgen(js_name = elevationGrid)]
+    pub fn elevation_grid(&self) -> Result<JsValue, JsValue> {
+        let (min, max) = self.state.map.elevation_range();
+        let hexes = self
+            .state
+            .map
+            .elevation_grid()
+            .into_iter()
+            .map(|(coord, elevation)| HexElevation {
+                q: coord.q,
+                r: coord.r,
+                elevation,
+            })
+            .collect();
+        let grid = ElevationGrid { min, max, hexes };
+
+        serde_wasm_bindgen::to_value(&grid).map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+
     /// Get map dimensions
     #[wasm_bindgen(js_name = getMapSize)]
     pub fn get_map_size(&self) -> Result<JsValue, JsValue> {
@@ -294,16 +949,19 @@ impl TitanHuntEngine {
 
     /// Start the game (transition from deployment to movement)
     #[wasm_bindgen(js_name = startGame)]
-    pub fn start_game(&mut self) {
-        if self.state.current_phase == Phase::Deployment {
-            self.state.current_phase = Phase::Movement;
+    pub fn start_game(&mut self) -> Result<(), JsValue> {
+        let state = self.setup_state()?;
+        if state.current_phase == Phase::Deployment {
+            state.current_phase = Phase::Movement;
         }
+        Ok(())
     }
 
-    /// Convert pixel coordinates to hex
+    /// Convert pixel coordinates to hex, rejecting a NaN or infinite
+    /// position and a hex size that isn't positive
     #[wasm_bindgen(js_name = pixelToHex)]
     pub fn pixel_to_hex(&self, x: f64, y: f64, hex_size: f64) -> Result<JsValue, JsValue> {
-        let coord = HexCoord::from_pixel(x, y, hex_size);
+        let coord = HexCoord::from_pixel(x, y, hex_size).map_err(|e| JsValue::from_str(&e))?;
         let hex = HexJson { q: coord.q, r: coord.r };
         serde_wasm_bindgen::to_value(&hex)
             .map_err(|e| JsValue::from_str(&e.to_string()))
@@ -319,6 +977,153 @@ impl TitanHuntEngine {
     }
 }
 
+impl TitanHuntEngine {
+    /// Get the state for a setup change, refusing once a command has been played
+    ///
+    /// Changes made outside commands aren't logged, so allowing them mid-game
+    /// would make `state_at_turn` replays drift from the live game.
+    fn setup_state(&mut self) -> Result<&mut GameState, JsValue> {
+        if self.state.origin.is_some() {
+            return Err(JsValue::from_str(
+                "The game has started; setup can't change until it is reset",
+            ));
+        }
+        Ok(&mut self.state)
+    }
+
+    /// Build an empty state on the current map that keeps the rule settings
+    fn fresh_state(&self, turn: u32, phase: Phase, active_player: Player) -> GameState {
+        let mut state = GameState::new_at(self.state.map.clone(), turn, phase, active_player);
+        state.auto_skip_empty_phases = self.state.auto_skip_empty_phases;
+        state.max_stack = self.state.max_stack;
+        state.pass_through_penalty = self.state.pass_through_penalty;
+        state.friendly_fire = self.state.friendly_fire;
+        state.stalemate_limit = self.state.stalemate_limit;
+        state.alternating_activation = self.state.alternating_activation;
+        state.hidden_deployment = self.state.hidden_deployment;
+        state.initiative_mode = self.state.initiative_mode;
+        state.victory_conditions = self.state.victory_conditions.clone();
+        state.stat_modifiers = self.state.stat_modifiers.clone();
+        state.phase_rules = self.state.phase_rules.clone();
+        state
+    }
+}
+
+/// Build the catalog entry for every unit type
+fn unit_catalog() -> Vec<UnitCatalogEntry> {
+    UnitType::ALL
+        .iter()
+        .map(|unit_type| UnitCatalogEntry {
+            sprite_key: unit_type.sprite_key().to_string(),
+            display_name: unit_type.display_name().to_string(),
+            base_movement: unit_type.base_movement(),
+            base_armor: unit_type.base_armor(),
+            base_structure: unit_type.base_structure(),
+            void_shields: unit_type.void_shields(),
+            weapon_cooldown: unit_type.weapon_cooldown(),
+            shield_piercing: unit_type.shield_piercing(),
+            weapons: unit_type.weapons(),
+            transport_capacity: unit_type.transport_capacity(),
+            repair_amount: unit_type.repair_amount(),
+            is_titan: unit_type.is_titan(),
+        })
+        .collect()
+}
+
+/// Get the lowercase name the frontend uses for a phase
+fn phase_name(phase: Phase) -> &'static str {
+    match phase {
+        Phase::Deployment => "deployment",
+        Phase::Movement => "movement",
+        Phase::Combat => "combat",
+        Phase::End => "end",
+    }
+}
+
+/// Get the number (1 or 2) the frontend uses for a player
+fn player_number(player: Player) -> u32 {
+    match player {
+        Player::Player1 => 1,
+        Player::Player2 => 2,
+    }
+}
+
+/// Get the player the frontend means by a number (1 or 2)
+fn parse_player(player: u32) -> Result<Player, String> {
+    match player {
+        1 => Ok(Player::Player1),
+        2 => Ok(Player::Player2),
+        _ => Err("Invalid player (must be 1 or 2)".to_string()),
+    }
+}
+
+/// Build a unit from the loosely typed values the frontend sends
+fn build_unit(
+    id: u32,
+    unit_type: &str,
+    player: u32,
+    q: i32,
+    r: i32,
+    facing: u8,
+) -> Result<Unit, String> {
+    let unit_type = match unit_type {
+        "Reaver_Titan" => UnitType::ReaverTitan,
+        "Warlord_Titan" => UnitType::WarlordTitan,
+        "shadowsword" => UnitType::Shadowsword,
+        "shadowsword2" => UnitType::Shadowsword2,
+        "shadowsword3" => UnitType::Shadowsword3,
+        "krieg" => UnitType::KriegSquad,
+        _ => return Err(format!("Unknown unit type: {}", unit_type)),
+    };
+
+    let owner = parse_player(player)?;
+
+    let facing = Facing::from_index(facing).ok_or("Invalid facing (must be 0-5)")?;
+
+    Ok(Unit::new(id, unit_type, owner, HexCoord::new(q, r), facing))
+}
+
+/// Add every valid unit in a batch, collecting an error for each invalid one
+fn add_unit_batch(state: &mut GameState, units: Vec<UnitSpecJson>) -> Vec<UnitBatchError> {
+    let mut errors = Vec::new();
+    for (index, spec) in units.into_iter().enumerate() {
+        let result = build_unit(
+            spec.id,
+            &spec.unit_type,
+            spec.player,
+            spec.q,
+            spec.r,
+            spec.facing,
+        )
+        .and_then(|unit| {
+            if state.map.is_valid(unit.position) {
+                Ok(unit)
+            } else {
+                Err(format!("Hex ({}, {}) is off the map", spec.q, spec.r))
+            }
+        });
+
+        match result {
+            Ok(unit) => state.add_unit(unit),
+            Err(error) => errors.push(UnitBatchError {
+                index,
+                id: spec.id,
+                error,
+            }),
+        }
+    }
+    errors
+}
+
+/// Convert a rejected command into a JS error object with a branchable code
+fn command_error(error: CommandError) -> JsValue {
+    let json = CommandErrorJson {
+        message: error.to_string(),
+        error,
+    };
+    serde_wasm_bindgen::to_value(&json).unwrap_or_else(|_| JsValue::from_str(&json.message))
+}
+
 // JSON serialization helpers
 
 #[derive(Serialize, Deserialize)]
@@ -327,6 +1132,12 @@ struct HexJson {
     r: i32,
 }
 
+#[derive(Serialize, Deserialize)]
+struct OverlayJson {
+    reachable: Vec<ReachableHex>,
+    threat: Vec<HexJson>,
+}
+
 #[derive(Serialize, Deserialize)]
 struct PixelPos {
     x: f64,
@@ -363,16 +1174,269 @@ struct UnitJson {
     max_structure: u32,
     void_shields: u32,
     max_void_shields: u32,
+    next_void_shields: u32,
     movement_remaining: u32,
     max_movement: u32,
     has_moved: bool,
     has_attacked: bool,
+    main_weapon_cooldown: u32,
+    embarked_on: Option<u32>,
+    damage_dealt: u32,
+    damage_taken: u32,
+    status_effects: Vec<StatusEffect>,
+    activated: bool,
     is_destroyed: bool,
     is_titan: bool,
 }
 
+impl From<&Unit> for UnitJson {
+    fn from(u: &Unit) -> Self {
+        UnitJson {
+            id: u.id,
+            unit_type: u.unit_type.sprite_key().to_string(),
+            display_name: u.unit_type.display_name().to_string(),
+            owner: player_number(u.owner),
+            q: u.position.q,
+            r: u.position.r,
+            facing: u.facing.index(),
+            sprite_frame: u.sprite_frame(),
+            armor: u.armor,
+            max_armor: u.unit_type.base_armor(),
+            structure: u.structure,
+            max_structure: u.unit_type.base_structure(),
+            void_shields: u.void_shields,
+            max_void_shields: u.unit_type.void_shields(),
+            next_void_shields: u.shield_forecast().2,
+            movement_remaining: u.effective_movement(),
+            max_movement: u.unit_type.base_movement(),
+            has_moved: u.has_moved,
+            has_attacked: u.has_attacked,
+            main_weapon_cooldown: u.main_weapon_cooldown,
+            embarked_on: u.embarked_on,
+            damage_dealt: u.damage_dealt,
+            damage_taken: u.damage_taken,
+            status_effects: u.status_effects.clone(),
+            activated: u.activated,
+            is_destroyed: u.is_destroyed(),
+            is_titan: u.unit_type.is_titan(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct UnitCatalogEntry {
+    sprite_key: String,
+    display_name: String,
+    base_movement: u32,
+    base_armor: u32,
+    base_structure: u32,
+    void_shields: u32,
+    weapon_cooldown: u32,
+    shield_piercing: u32,
+    weapons: &'static [Weapon],
+    transport_capacity: u32,
+    repair_amount: u32,
+    is_titan: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+struct PixelBounds {
+    min_x: f64,
+    min_y: f64,
+    max_x: f64,
+    max_y: f64,
+}
+
+#[derive(Serialize, Deserialize)]
+struct NearestUnit {
+    id: u32,
+    distance: u32,
+}
+
+#[derive(Serialize, Deserialize)]
+struct CommandErrorJson {
+    error: CommandError,
+    message: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct UnitSpecJson {
+    id: u32,
+    #[serde(rename = "type")]
+    unit_type: String,
+    player: u32,
+    q: i32,
+    r: i32,
+    facing: u8,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct UnitBatchError {
+    index: usize,
+    id: u32,
+    error: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct HexElevation {
+    q: i32,
+    r: i32,
+    elevation: i32,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ElevationGrid {
+    min: i32,
+    max: i32,
+    hexes: Vec<HexElevation>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct UiContextJson {
+    selected_unit: Option<u32>,
+    active_player: u32,
+    phase: String,
+    turn: u32,
+}
+
 #[derive(Serialize, Deserialize)]
 struct MapSize {
     width: i32,
     height: i32,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::{CommandKind, TerrainType};
+
+    #[test]
+    fn test_reset_keeps_map() {
+        let mut engine = TitanHuntEngine::new(8, 8, None).unwrap();
+        engine.state.map.tiles.get_mut(&(2, 3)).unwrap().terrain = TerrainType::Woods;
+        engine.state.add_unit(Unit::new(
+            1,
+            UnitType::ReaverTitan,
+            Player::Player1,
+            HexCoord::new(0, 0),
+            Facing::East,
+        ));
+        engine.state.current_phase = Phase::Movement;
+        engine.state.process_command(Command::EndTurn).unwrap();
+        engine
+            .state
+            .process_command(Command::Concede {
+                player: Player::Player2,
+            })
+            .unwrap();
+        let map = engine.state.map.clone();
+
+        engine.reset();
+
+        assert!(engine.state.units.is_empty());
+        assert!(engine.state.events.is_empty());
+        assert_eq!(engine.state.current_turn, 1);
+        assert_eq!(engine.state.current_phase, Phase::Deployment);
+        assert_eq!(engine.state.active_player, Player::Player1);
+        assert!(!engine.state.game_over);
+        assert_eq!(engine.state.winner, None);
+        assert_eq!(engine.state.map.tiles, map.tiles);
+        assert_eq!(
+            engine.state.map.terrain_at(HexCoord::new(2, 3)),
+            TerrainType::Woods
+        );
+    }
+
+    #[test]
+    fn test_handicaps_survive_reset() {
+        let mut engine = TitanHuntEngine::new(8, 8, None).unwrap();
+        engine.set_handicap(2, 0.5, -1).unwrap();
+        engine.reset();
+
+        let modifier = engine.state.stat_modifier(Player::Player2);
+        assert_eq!(modifier.damage_multiplier, 0.5);
+        assert_eq!(modifier.movement_bonus, -1);
+    }
+
+    #[test]
+    fn test_phase_rules_survive_reset() {
+        let mut engine = TitanHuntEngine::new(8, 8, None).unwrap();
+        engine
+            .state
+            .phase_rules
+            .allowed
+            .entry(Phase::Movement)
+            .or_default()
+            .push(CommandKind::Attack);
+        let rules = engine.state.phase_rules.clone();
+
+        engine.reset();
+        assert_eq!(engine.state.phase_rules, rules);
+        assert!(engine
+            .state
+            .phase_rules
+            .allows(Phase::Movement, CommandKind::Attack));
+    }
+
+    #[test]
+    fn test_add_unit_batch_skips_bad_entries() {
+        let mut engine = TitanHuntEngine::new(8, 8, None).unwrap();
+        let units: Vec<UnitSpecJson> = serde_json::from_str(
+            r#"[
+                {"id": 1, "type": "Reaver_Titan", "player": 1, "q": 0, "r": 0, "facing": 0},
+                {"id": 2, "type": "Baneblade", "player": 2, "q": 3, "r": 3, "facing": 3},
+                {"id": 3, "type": "krieg", "player": 2, "q": 4, "r": 2, "facing": 3}
+            ]"#,
+        )
+        .unwrap();
+
+        let errors = add_unit_batch(&mut engine.state, units);
+
+        assert_eq!(
+            errors,
+            vec![UnitBatchError {
+                index: 1,
+                id: 2,
+                error: "Unknown unit type: Baneblade".to_string(),
+            }]
+        );
+        let ids: Vec<u32> = engine.state.units.iter().map(|u| u.id).collect();
+        assert_eq!(ids, vec![1, 3]);
+    }
+
+    #[test]
+    fn test_unit_catalog_covers_every_type() {
+        let catalog = unit_catalog();
+        assert_eq!(catalog.len(), UnitType::ALL.len());
+
+        for (entry, unit_type) in catalog.iter().zip(UnitType::ALL) {
+            assert_eq!(entry.sprite_key, unit_type.sprite_key());
+            assert_eq!(entry.display_name, unit_type.display_name());
+            assert_eq!(entry.base_movement, unit_type.base_movement());
+            assert_eq!(entry.base_armor, unit_type.base_armor());
+            assert_eq!(entry.base_structure, unit_type.base_structure());
+            assert_eq!(entry.void_shields, unit_type.void_shields());
+            assert_eq!(entry.is_titan, unit_type.is_titan());
+        }
+
+        // Fails to compile if a variant is added without updating the catalog
+        let position = |unit_type: UnitType| match unit_type {
+            UnitType::ReaverTitan => 0,
+            UnitType::WarlordTitan => 1,
+            UnitType::Shadowsword => 2,
+            UnitType::Shadowsword2 => 3,
+            UnitType::Shadowsword3 => 4,
+            UnitType::KriegSquad => 5,
+        };
+
+        // Every variant appears exactly once
+        for unit_type in UnitType::ALL {
+            assert_eq!(UnitType::ALL[position(unit_type)], unit_type);
+            let matches = catalog
+                .iter()
+                .filter(|entry| entry.sprite_key == unit_type.sprite_key())
+                .count();
+            assert_eq!(matches, 1);
+        }
+    }
+}